@@ -0,0 +1,60 @@
+use std::iter;
+
+use criterion::black_box;
+use criterion::criterion_group;
+use criterion::criterion_main;
+use criterion::BenchmarkId;
+use criterion::Criterion;
+use typed_arena::Arena;
+
+use chapter_03::term;
+
+/// `if (if (... (if iszero 0 then true else false) ...) then true else false) then 1 else 0`,
+/// whose guards are nested `depth` deep, so that every small step has to
+/// descend through all of them.
+fn guards<'a>(arena: &'a Arena<term::T<'a>>, depth: usize) -> term::T<'a> {
+    use term::T::*;
+    let guard = (0..depth).fold(IsZero(&Zero), |guard, _| IfElse(arena.alloc(guard), &True, &False));
+    IfElse(arena.alloc(guard), arena.alloc(term::T::nat(arena, 1)), &Zero)
+}
+
+/// `if iszero 1 then 0 else if iszero 1 then 0 else ... else 1`, with `depth`
+/// conditionals chained through their else branches.
+fn chain<'a>(arena: &'a Arena<term::T<'a>>, depth: usize) -> term::T<'a> {
+    use term::T::*;
+    let one = term::T::nat(arena, 1);
+    let guard = &*arena.alloc(IsZero(arena.alloc(one)));
+    (0..depth).fold(one, |r#else, _| IfElse(guard, &Zero, arena.alloc(r#else)))
+}
+
+type Build = for<'a> fn(&'a Arena<term::T<'a>>, usize) -> term::T<'a>;
+
+fn if_chains(criterion: &mut Criterion) {
+    for (name, build) in [("if_guards", guards as Build), ("if_chain", chain)] {
+        let mut group = criterion.benchmark_group(name);
+
+        for depth in [10, 100, 1000] {
+            let input: &'static Arena<term::T<'static>> = Box::leak(Box::new(Arena::new()));
+            let term = build(input, depth);
+
+            group.bench_with_input(BenchmarkId::new("big-step", depth), &depth, |bencher, _| {
+                bencher.iter(|| {
+                    let arena = Arena::new();
+                    black_box(term.eval(&arena));
+                })
+            });
+
+            group.bench_with_input(BenchmarkId::new("small-step", depth), &depth, |bencher, _| {
+                bencher.iter(|| {
+                    let arena = Arena::new();
+                    black_box(iter::successors(Some(term), |term| term.step(&arena)).last());
+                })
+            });
+        }
+
+        group.finish();
+    }
+}
+
+criterion_group!(benches, if_chains);
+criterion_main!(benches);
@@ -0,0 +1,113 @@
+use std::io;
+use std::iter;
+
+use tapl_core::dot;
+use tapl_core::dot::Tree;
+use typed_arena::Arena;
+
+use crate::term;
+
+impl<'a> term::T<'a> {
+    /// The abstract syntax tree of this term, with the subterm at `redex`
+    /// highlighted. Numerals are drawn as chains of `succ` nodes.
+    pub fn to_dot(&self, redex: Option<&[usize]>) -> Tree {
+        let mut tree = Tree::default();
+        self._to_dot(&mut tree, redex);
+        tree
+    }
+
+    fn _to_dot(&self, tree: &mut Tree, redex: Option<&[usize]>) -> usize {
+        use term::T::*;
+        let (label, operands) = match self {
+        | True => ("true", vec![]),
+        | False => ("false", vec![]),
+        | Zero => ("0", vec![]),
+        | Wrong => ("wrong", vec![]),
+        | Succ(t_1) => ("succ", vec![*t_1]),
+        | Pred(t_1) => ("pred", vec![*t_1]),
+        | IsZero(t_1) => ("iszero", vec![*t_1]),
+        | IfElse(t_1, t_2, t_3) => ("if", vec![*t_1, *t_2, *t_3]),
+        | Plus(t_1, t_2) => ("plus", vec![*t_1, *t_2]),
+        | Times(t_1, t_2) => ("times", vec![*t_1, *t_2]),
+        | Eq(t_1, t_2) => ("eq", vec![*t_1, *t_2]),
+        | Lt(t_1, t_2) => ("lt", vec![*t_1, *t_2]),
+        };
+
+        let node = tree.node(label);
+        if let Some([]) = redex {
+            tree.highlight(node);
+        }
+
+        for (operand, t) in operands.into_iter().enumerate() {
+            let child = t._to_dot(tree, dot::descend(redex, operand));
+            tree.child(node, "", child);
+        }
+
+        node
+    }
+
+    /// Writes the abstract syntax tree of this term in the Graphviz DOT
+    /// language.
+    pub fn write_dot<W: io::Write>(&self, writer: &mut W) -> anyhow::Result<()> {
+        self.to_dot(None).write("term", writer)
+    }
+
+    /// Writes every term in the evaluation sequence of this term as its own
+    /// graph, highlighting the redex contracted by the following step.
+    pub fn write_trace<W: io::Write>(&self, arena: &'a Arena<term::T<'a>>, writer: &mut W) -> anyhow::Result<()> {
+        iter::successors(Some(*self), |t| t.step(arena))
+            .enumerate()
+            .try_for_each(|(step, t)| t.to_dot(t.redex().as_deref()).write(&format!("step{}", step), writer))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use typed_arena::Arena;
+
+    use crate::term;
+
+    #[test]
+    fn trace() {
+        use term::T::*;
+        let arena = Arena::new();
+        let term = IfElse(&IsZero(&Zero), &Succ(&Zero), &Zero);
+        let mut dot = Vec::new();
+        term.write_trace(&arena, &mut dot).unwrap();
+        assert_eq!(String::from_utf8(dot).unwrap(), "\
+digraph step0 {
+    node [shape=box];
+    0 [label=\"if\"];
+    1 [label=\"iszero\", style=filled, fillcolor=yellow];
+    2 [label=\"0\", style=filled, fillcolor=yellow];
+    3 [label=\"succ\"];
+    4 [label=\"0\"];
+    5 [label=\"0\"];
+    1 -> 2;
+    0 -> 1;
+    3 -> 4;
+    0 -> 3;
+    0 -> 5;
+}
+digraph step1 {
+    node [shape=box];
+    0 [label=\"if\", style=filled, fillcolor=yellow];
+    1 [label=\"true\", style=filled, fillcolor=yellow];
+    2 [label=\"succ\", style=filled, fillcolor=yellow];
+    3 [label=\"0\", style=filled, fillcolor=yellow];
+    4 [label=\"0\", style=filled, fillcolor=yellow];
+    0 -> 1;
+    2 -> 3;
+    0 -> 2;
+    0 -> 4;
+}
+digraph step2 {
+    node [shape=box];
+    0 [label=\"succ\"];
+    1 [label=\"0\"];
+    0 -> 1;
+}
+");
+    }
+}
@@ -12,48 +12,131 @@ impl<'a> term::T<'a> {
         // v ↓ v
         | &v if v.is_value() => v,
 
-        //    t₁ ↓ true    t₂ ↓ v₂
-        // -------------------------- (B-IfTrue)
-        // if t₁ then t₂ else t₃ ↓ v₂
-        | IfElse(t_1, t_2, _) if t_1.eval(arena) == True => t_2.eval(arena),
-
-        //   t₁ ↓ false    t₃ ↓ v₃
-        // -------------------------- (B-IfFalse)
-        // if t₁ then t₂ else t₃ ↓ v₃
-        | IfElse(t_1, _, t_3) if t_1.eval(arena) == False => t_3.eval(arena),
-
-        //      t₁ ↓ nv₁
-        // ------------------ (B-Succ)
-        // succ t₁ ↓ succ nv₁
-        | Succ(t_1) if t_1.eval(arena).is_numeric() => Succ(arena.alloc(t_1.eval(arena))),
-
-        //   t₁ ↓ 0
-        // ----------- (B-PredZero)
-        // pred t₁ ↓ 0
-        | Pred(t_1) if t_1.eval(arena) == Zero => Zero,
-
-        // t₁ ↓ succ nv₁
-        // ------------- (B-PredSucc)
-        // pred t₁ ↓ nv₁
+        | IfElse(t_1, t_2, t_3) => {
+            match t_1.eval(arena) {
+            //    t₁ ↓ true    t₂ ↓ v₂
+            // -------------------------- (B-IfTrue)
+            // if t₁ then t₂ else t₃ ↓ v₂
+            | True => t_2.eval(arena),
+
+            //   t₁ ↓ false    t₃ ↓ v₃
+            // -------------------------- (B-IfFalse)
+            // if t₁ then t₂ else t₃ ↓ v₃
+            | False => t_3.eval(arena),
+
+            // Stuck.
+            | _ => *self,
+            }
+        }
+
+        | Succ(t_1) => {
+            match t_1.eval(arena) {
+            //      t₁ ↓ nv₁
+            // ------------------ (B-Succ)
+            // succ t₁ ↓ succ nv₁
+            | nv_1 if nv_1.is_numeric() => Succ(arena.alloc(nv_1)),
+
+            // Stuck.
+            | _ => *self,
+            }
+        }
+
         | Pred(t_1) => {
             match t_1.eval(arena) {
+            //   t₁ ↓ 0
+            // ----------- (B-PredZero)
+            // pred t₁ ↓ 0
+            | Zero => Zero,
+
+            // t₁ ↓ succ nv₁
+            // ------------- (B-PredSucc)
+            // pred t₁ ↓ nv₁
             | Succ(&nv_1) if nv_1.is_numeric() => nv_1,
-            | _ => Pred(t_1),
+
+            // Stuck.
+            | _ => *self,
             }
         }
 
-        //      t₁ ↓ 0
-        // ---------------- (B-IsZeroZero)
-        // iszero t₁ ↓ true
-        | IsZero(t_1) if t_1.eval(arena) == Zero => True,
-
-        //   t₁ ↓ succ nv₁
-        // ----------------- (B-IsZeroSucc)
-        // iszero t₁ ↓ false
         | IsZero(t_1) => {
             match t_1.eval(arena) {
+            //      t₁ ↓ 0
+            // ---------------- (B-IsZeroZero)
+            // iszero t₁ ↓ true
+            | Zero => True,
+
+            //   t₁ ↓ succ nv₁
+            // ----------------- (B-IsZeroSucc)
+            // iszero t₁ ↓ false
             | Succ(&nv_1) if nv_1.is_numeric() => False,
-            | _ => IsZero(t_1),
+
+            // Stuck.
+            | _ => *self,
+            }
+        }
+
+        | Plus(t_1, t_2) => {
+            match (t_1.eval(arena).as_nat(), t_2.eval(arena)) {
+            //  t₁ ↓ nv₁    t₂ ↓ nv₂
+            // ---------------------- (B-Plus)
+            // plus t₁ t₂ ↓ nv₁ + nv₂
+            | (Some(n_1), nv_2) if nv_2.is_numeric() => {
+                (0..n_1).fold(nv_2, |nv, _| Succ(arena.alloc(nv)))
+            }
+
+            // Stuck.
+            | _ => *self,
+            }
+        }
+
+        | Times(t_1, t_2) => {
+            match (t_1.eval(arena).as_nat(), t_2.eval(arena).as_nat()) {
+            //  t₁ ↓ nv₁    t₂ ↓ nv₂
+            // ----------------------- (B-Times)
+            // times t₁ t₂ ↓ nv₁ × nv₂
+            | (Some(n_1), Some(n_2)) => {
+                match n_1.checked_mul(n_2) {
+                | Some(n) if n <= term::MAX_NUMERAL => term::T::nat(arena, n),
+                | _ => *self,
+                }
+            }
+
+            // Stuck, or a product past `MAX_NUMERAL`.
+            | _ => *self,
+            }
+        }
+
+        | Eq(t_1, t_2) => {
+            match (t_1.eval(arena).as_nat(), t_2.eval(arena).as_nat()) {
+            // t₁ ↓ nv₁    t₂ ↓ nv₂    nv₁ = nv₂
+            // --------------------------------- (B-EqTrue)
+            //          eq t₁ t₂ ↓ true
+            | (Some(n_1), Some(n_2)) if n_1 == n_2 => True,
+
+            // t₁ ↓ nv₁    t₂ ↓ nv₂    nv₁ ≠ nv₂
+            // --------------------------------- (B-EqFalse)
+            //          eq t₁ t₂ ↓ false
+            | (Some(_), Some(_)) => False,
+
+            // Stuck.
+            | _ => *self,
+            }
+        }
+
+        | Lt(t_1, t_2) => {
+            match (t_1.eval(arena).as_nat(), t_2.eval(arena).as_nat()) {
+            // t₁ ↓ nv₁    t₂ ↓ nv₂    nv₁ < nv₂
+            // --------------------------------- (B-LtTrue)
+            //          lt t₁ t₂ ↓ true
+            | (Some(n_1), Some(n_2)) if n_1 < n_2 => True,
+
+            // t₁ ↓ nv₁    t₂ ↓ nv₂    nv₁ ≥ nv₂
+            // --------------------------------- (B-LtFalse)
+            //          lt t₁ t₂ ↓ false
+            | (Some(_), Some(_)) => False,
+
+            // Stuck.
+            | _ => *self,
             }
         }
 
@@ -116,6 +199,105 @@ impl<'a> term::T<'a> {
         // iszero t₁ -> iszero t₁'
         | IsZero(t_1) => IsZero(arena.alloc(t_1.step(arena)?)),
 
+        //
+        // ----------------- (E-PlusZero)
+        // plus 0 nv₂ -> nv₂
+        | Plus(Zero, &nv_2) if nv_2.is_numeric() => nv_2,
+
+        //
+        // ------------------------------------------ (E-PlusSucc)
+        // plus (succ nv₁) nv₂ -> succ (plus nv₁ nv₂)
+        | Plus(Succ(nv_1), nv_2) if nv_1.is_numeric() && nv_2.is_numeric() => {
+            Succ(arena.alloc(Plus(nv_1, nv_2)))
+        }
+
+        //           t₂ -> t₂'
+        // ---------------------------- (E-Plus2)
+        // plus nv₁ t₂ -> plus nv₁ t₂'
+        | Plus(nv_1, t_2) if nv_1.is_numeric() => Plus(nv_1, arena.alloc(t_2.step(arena)?)),
+
+        //          t₁ -> t₁'
+        // ------------------------- (E-Plus1)
+        // plus t₁ t₂ -> plus t₁' t₂
+        | Plus(t_1, t_2) => Plus(arena.alloc(t_1.step(arena)?), t_2),
+
+        //
+        // ----------------- (E-TimesZero)
+        // times 0 nv₂ -> 0
+        | Times(Zero, nv_2) if nv_2.is_numeric() => Zero,
+
+        //
+        // ------------------------------------------------ (E-TimesSucc)
+        // times (succ nv₁) nv₂ -> plus nv₂ (times nv₁ nv₂)
+        | Times(Succ(nv_1), nv_2) if nv_1.is_numeric() && nv_2.is_numeric() => {
+            Plus(nv_2, arena.alloc(Times(nv_1, nv_2)))
+        }
+
+        //            t₂ -> t₂'
+        // ------------------------------ (E-Times2)
+        // times nv₁ t₂ -> times nv₁ t₂'
+        | Times(nv_1, t_2) if nv_1.is_numeric() => Times(nv_1, arena.alloc(t_2.step(arena)?)),
+
+        //           t₁ -> t₁'
+        // --------------------------- (E-Times1)
+        // times t₁ t₂ -> times t₁' t₂
+        | Times(t_1, t_2) => Times(arena.alloc(t_1.step(arena)?), t_2),
+
+        //
+        // -------------- (E-EqZeroZero)
+        // eq 0 0 -> true
+        | Eq(Zero, Zero) => True,
+
+        //
+        // ------------------------ (E-EqZeroSucc)
+        // eq 0 (succ nv₂) -> false
+        | Eq(Zero, Succ(nv_2)) if nv_2.is_numeric() => False,
+
+        //
+        // ------------------------ (E-EqSuccZero)
+        // eq (succ nv₁) 0 -> false
+        | Eq(Succ(nv_1), Zero) if nv_1.is_numeric() => False,
+
+        //
+        // ------------------------------------- (E-EqSuccSucc)
+        // eq (succ nv₁) (succ nv₂) -> eq nv₁ nv₂
+        | Eq(Succ(nv_1), Succ(nv_2)) if nv_1.is_numeric() && nv_2.is_numeric() => Eq(nv_1, nv_2),
+
+        //         t₂ -> t₂'
+        // ------------------------ (E-Eq2)
+        // eq nv₁ t₂ -> eq nv₁ t₂'
+        | Eq(nv_1, t_2) if nv_1.is_numeric() => Eq(nv_1, arena.alloc(t_2.step(arena)?)),
+
+        //        t₁ -> t₁'
+        // --------------------- (E-Eq1)
+        // eq t₁ t₂ -> eq t₁' t₂
+        | Eq(t_1, t_2) => Eq(arena.alloc(t_1.step(arena)?), t_2),
+
+        //
+        // ------------------ (E-LtZero)
+        // lt nv₁ 0 -> false
+        | Lt(nv_1, Zero) if nv_1.is_numeric() => False,
+
+        //
+        // ----------------------- (E-LtZeroSucc)
+        // lt 0 (succ nv₂) -> true
+        | Lt(Zero, Succ(nv_2)) if nv_2.is_numeric() => True,
+
+        //
+        // ------------------------------------- (E-LtSuccSucc)
+        // lt (succ nv₁) (succ nv₂) -> lt nv₁ nv₂
+        | Lt(Succ(nv_1), Succ(nv_2)) if nv_1.is_numeric() && nv_2.is_numeric() => Lt(nv_1, nv_2),
+
+        //         t₂ -> t₂'
+        // ------------------------ (E-Lt2)
+        // lt nv₁ t₂ -> lt nv₁ t₂'
+        | Lt(nv_1, t_2) if nv_1.is_numeric() => Lt(nv_1, arena.alloc(t_2.step(arena)?)),
+
+        //        t₁ -> t₁'
+        // --------------------- (E-Lt1)
+        // lt t₁ t₂ -> lt t₁' t₂
+        | Lt(t_1, t_2) => Lt(arena.alloc(t_1.step(arena)?), t_2),
+
         // Stuck.
         | _ => return None,
         };
@@ -123,6 +305,40 @@ impl<'a> term::T<'a> {
         Some(next)
     }
 
+    /// Position of the redex contracted by `step`, as the indices of the
+    /// operands leading to it, or `None` if this term doesn't step.
+    pub fn redex(&self) -> Option<Vec<usize>> {
+        use term::T::*;
+        let (operand, t) = match self {
+        | IfElse(True, _, _)
+        | IfElse(False, _, _) => return Some(Vec::new()),
+        | Pred(Zero)
+        | IsZero(Zero) => return Some(Vec::new()),
+        | Pred(Succ(nv_1))
+        | IsZero(Succ(nv_1)) if nv_1.is_numeric() => return Some(Vec::new()),
+        | Plus(nv_1, nv_2)
+        | Times(nv_1, nv_2)
+        | Eq(nv_1, nv_2)
+        | Lt(nv_1, nv_2) if nv_1.is_numeric() && nv_2.is_numeric() => return Some(Vec::new()),
+        | Plus(nv_1, t_2)
+        | Times(nv_1, t_2)
+        | Eq(nv_1, t_2)
+        | Lt(nv_1, t_2) if nv_1.is_numeric() => (1, t_2),
+        | IfElse(t_1, _, _)
+        | Succ(t_1)
+        | Pred(t_1)
+        | IsZero(t_1)
+        | Plus(t_1, _)
+        | Times(t_1, _)
+        | Eq(t_1, _)
+        | Lt(t_1, _) => (0, t_1),
+        | _ => return None,
+        };
+        let mut position = t.redex()?;
+        position.insert(0, operand);
+        Some(position)
+    }
+
     pub fn is_value(&self) -> bool {
         use term::T::*;
         match self {
@@ -193,4 +409,36 @@ mod tests {
             }
         }
     }
+
+    /// A term has a redex exactly when it steps.
+    #[test]
+    fn redex_iff_step() {
+        let arena = Arena::new();
+        for term in term::T::generate(&arena, 3) {
+            assert_eq!(term.redex().is_some(), term.step(&arena).is_some());
+        }
+    }
+
+    /// Each premise is evaluated once, so nested guards take linear time.
+    #[test]
+    fn nested_guards() {
+        let arena = Arena::new();
+        let mut term = term::T::False;
+        for _ in 0..64 {
+            term = term::T::IfElse(arena.alloc(term), &term::T::False, &term::T::False);
+        }
+        assert_eq!(term.eval(&arena), term::T::False);
+    }
+
+    /// A product past `MAX_NUMERAL` is left unevaluated rather than unfolded.
+    #[test]
+    fn large_product() {
+        let arena = Arena::new();
+        let term = term::T::parse(&arena, "times 16 16").unwrap();
+        assert_eq!(term.eval(&arena).as_nat(), Some(256));
+        let term = term::T::parse(&arena, "times 300 300").unwrap();
+        assert_eq!(term.eval(&arena), term);
+        let term = term::T::parse(&arena, "iszero times (times 300 300) 300").unwrap();
+        assert_eq!(term.eval(&arena), term);
+    }
 }
@@ -0,0 +1,205 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::hash::Hasher;
+use std::iter;
+use std::mem;
+use std::ptr;
+use std::rc::Rc;
+
+use typed_arena::Arena;
+
+use crate::term;
+
+/// Generates the sets `S_i` of Definition 3.2.3, sharing work across levels.
+///
+/// Every `S_i` needed to build a higher level is materialized once, and its
+/// terms are hash-consed: each distinct subterm is allocated exactly once in
+/// the arena, no matter how many levels or terms it appears in.
+pub struct Generator<'a> {
+    arena: &'a Arena<term::T<'a>>,
+    interned: HashMap<Shallow<'a>, &'a term::T<'a>>,
+    levels: Vec<Rc<Vec<&'a term::T<'a>>>>,
+}
+
+impl<'a> Generator<'a> {
+    pub fn new(arena: &'a Arena<term::T<'a>>) -> Self {
+        Generator {
+            arena,
+            interned: HashMap::new(),
+            levels: vec![Rc::new(Vec::new())],
+        }
+    }
+
+    /// The materialized, hash-consed set `S_index`.
+    pub fn level(&mut self, index: usize) -> Rc<Vec<&'a term::T<'a>>> {
+        while self.levels.len() <= index {
+            let next = self.stream(self.levels.len())
+                .map(|term| self.intern(term))
+                .collect();
+            self.levels.push(Rc::new(next));
+        }
+        Rc::clone(&self.levels[index])
+    }
+
+    /// Lazily enumerates `S_index`, materializing only `S_{index - 1}`. Every
+    /// term is produced exactly once.
+    ///
+    /// ```text
+    /// S_{0}     = ∅
+    /// S_{i + 1} = { true, false, 0 }
+    ///           ∪ { succ t₁, pred t₁, iszero t₁ | t₁ ∈ S_i }
+    ///           ∪ { plus t₁ t₂, times t₁ t₂, eq t₁ t₂, lt t₁ t₂ | t₁, t₂ ∈ S_i }
+    ///           ∪ { if t₁ then t₂ else t₃ | t₁, t₂, t₃ ∈ S_i }
+    /// ```
+    pub fn stream(&mut self, index: usize) -> Box<dyn Iterator<Item = term::T<'a>> + 'a> {
+        use term::T::*;
+
+        if index == 0 {
+            return Box::new(iter::empty());
+        }
+
+        let s_i = self.level(index - 1);
+        let n = s_i.len();
+
+        // { true, false, 0 }
+        let constants = vec![True, False, Zero].into_iter();
+
+        // ∪ { succ t₁, pred t₁, iszero t₁ | t₁ ∈ S_i }
+        let unary = {
+            let s_i = Rc::clone(&s_i);
+            (0..n).flat_map(move |i| vec![Succ(s_i[i]), Pred(s_i[i]), IsZero(s_i[i])])
+        };
+
+        // ∪ { plus t₁ t₂, times t₁ t₂, eq t₁ t₂, lt t₁ t₂ | t₁, t₂ ∈ S_i }
+        let binary = {
+            let s_i = Rc::clone(&s_i);
+            (0..n * n).flat_map(move |i| {
+                let (t_1, t_2) = (s_i[i / n], s_i[i % n]);
+                vec![Plus(t_1, t_2), Times(t_1, t_2), Eq(t_1, t_2), Lt(t_1, t_2)]
+            })
+        };
+
+        // ∪ { if t₁ then t₂ else t₃ | t₁, t₂, t₃ ∈ S_i }
+        let ternary = (0..n * n * n).map(move |i| {
+            IfElse(s_i[i / (n * n)], s_i[i / n % n], s_i[i % n])
+        });
+
+        Box::new(constants.chain(unary).chain(binary).chain(ternary))
+    }
+
+    /// Returns the unique allocation of `term`, whose subterms must already be
+    /// hash-consed.
+    fn intern(&mut self, term: term::T<'a>) -> &'a term::T<'a> {
+        let arena = self.arena;
+        self.interned
+            .entry(Shallow(term))
+            .or_insert_with(|| arena.alloc(term))
+    }
+}
+
+impl<'a> term::T<'a> {
+    /// Definition 3.2.3 - Lazily enumerates the set `S_index`.
+    pub fn generate(arena: &'a Arena<term::T<'a>>, index: usize) -> impl Iterator<Item = term::T<'a>> {
+        Generator::new(arena).stream(index)
+    }
+
+    /// Exercise 3.2.4 - The number of terms `|S_index|`, or `None` if it
+    /// overflows a `u128`.
+    ///
+    /// ```text
+    /// |S_{0}|     = 0
+    /// |S_{i + 1}| = 3 + 3|S_i| + 4|S_i|² + |S_i|³
+    /// ```
+    pub fn count(index: usize) -> Option<u128> {
+        (0..index).try_fold(0u128, |s_i, _| {
+            s_i.checked_pow(3)?
+                .checked_add(s_i.checked_pow(2)?.checked_mul(4)?)?
+                .checked_add(s_i.checked_mul(3)?)?
+                .checked_add(3)
+        })
+    }
+}
+
+/// Compares terms by constructor and by the addresses of their immediate
+/// subterms, which coincides with structural equality on hash-consed terms.
+#[derive(Copy, Clone, Debug)]
+struct Shallow<'a>(term::T<'a>);
+
+impl<'a> Shallow<'a> {
+    fn children(&self) -> Vec<*const term::T<'a>> {
+        use term::T::*;
+        match self.0 {
+        | True | False | Zero | Wrong => vec![],
+        | Succ(t_1) | Pred(t_1) | IsZero(t_1) => vec![t_1],
+        | Plus(t_1, t_2) | Times(t_1, t_2) | Eq(t_1, t_2) | Lt(t_1, t_2) => vec![t_1, t_2],
+        | IfElse(t_1, t_2, t_3) => vec![t_1, t_2, t_3],
+        }
+    }
+}
+
+impl<'a> PartialEq for Shallow<'a> {
+    fn eq(&self, other: &Self) -> bool {
+        mem::discriminant(&self.0) == mem::discriminant(&other.0)
+            && self.children()
+                .into_iter()
+                .zip(other.children())
+                .all(|(l, r)| ptr::eq(l, r))
+    }
+}
+
+impl<'a> Eq for Shallow<'a> {}
+
+impl<'a> Hash for Shallow<'a> {
+    fn hash<H: Hasher>(&self, hasher: &mut H) {
+        mem::discriminant(&self.0).hash(hasher);
+        self.children().hash(hasher);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use std::collections::HashSet;
+
+    use typed_arena::Arena;
+
+    use crate::generate::Generator;
+    use crate::term;
+
+    /// Exercise 3.2.4 - `|S_i|` matches the number of terms generated, and
+    /// no term is generated twice.
+    #[test]
+    fn count_matches_generate() {
+        let arena = Arena::new();
+        for index in 0..=3 {
+            let count = term::T::generate(&arena, index).count();
+            let terms = term::T::generate(&arena, index).collect::<HashSet<_>>();
+            assert_eq!(count, terms.len());
+            assert_eq!(count as u128, term::T::count(index).unwrap());
+        }
+        assert_eq!(term::T::count(2), Some(75));
+    }
+
+    /// Exercise 3.2.5 - `S_i ⊆ S_{i + 1}`, and terms in both are allocated
+    /// only once.
+    #[test]
+    fn levels_shared() {
+        let arena = Arena::new();
+        let mut generator = Generator::new(&arena);
+        let s_2 = generator.level(2);
+        let s_3 = generator.level(3);
+        for t in s_2.iter() {
+            assert!(s_3.iter().any(|u| std::ptr::eq(*t, *u)));
+        }
+    }
+
+    /// `S_4` can be sampled lazily without materializing it.
+    #[test]
+    fn stream_s_4() {
+        let arena = Arena::new();
+        let term = term::T::generate(&arena, 4)
+            .nth(100_000)
+            .unwrap();
+        assert!(term.depth() <= 4);
+    }
+}
@@ -0,0 +1,89 @@
+use anyhow::anyhow;
+use serde_json::Value;
+use tapl_core::json;
+use typed_arena::Arena;
+
+use crate::term;
+
+impl<'a> term::T<'a> {
+    /// Reads back a term serialized as JSON, allocating it in `arena`. See
+    /// the variants of `T` for the schema.
+    pub fn from_json(arena: &'a Arena<term::T<'a>>, json: &str) -> anyhow::Result<Self> {
+        json::read(json, |value| Self::from_value(arena, value))
+    }
+
+    pub fn from_value(arena: &'a Arena<term::T<'a>>, value: &Value) -> anyhow::Result<Self> {
+        use term::T::*;
+        let subterm = |value: &Value| -> anyhow::Result<&'a term::T<'a>> {
+            Ok(arena.alloc(json::grow(|| Self::from_value(arena, value))?))
+        };
+        let term = match json::variant(value)? {
+        | ("True", _) => True,
+        | ("False", _) => False,
+        | ("Zero", _) => Zero,
+        | ("Wrong", _) => Wrong,
+        | ("Succ", t_1) => Succ(subterm(t_1)?),
+        | ("Pred", t_1) => Pred(subterm(t_1)?),
+        | ("IsZero", t_1) => IsZero(subterm(t_1)?),
+        | ("IfElse", content) => {
+            let [t_1, t_2, t_3] = json::elements(content)?;
+            IfElse(subterm(t_1)?, subterm(t_2)?, subterm(t_3)?)
+        }
+        | (tag @ ("Plus" | "Times" | "Eq" | "Lt"), content) => {
+            let [t_1, t_2] = json::elements(content)?;
+            let (t_1, t_2) = (subterm(t_1)?, subterm(t_2)?);
+            match tag {
+            | "Plus" => Plus(t_1, t_2),
+            | "Times" => Times(t_1, t_2),
+            | "Eq" => Eq(t_1, t_2),
+            | _ => Lt(t_1, t_2),
+            }
+        }
+        | (tag, _) => return Err(anyhow!("Unknown term variant: {}", tag)),
+        };
+        Ok(term)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use tapl_core::json;
+    use typed_arena::Arena;
+
+    use crate::term;
+
+    /// Serializing and deserializing a term gives back the same term.
+    #[test]
+    fn round_trip() {
+        let arena = Arena::new();
+        for term in term::T::generate(&arena, 3) {
+            let json = serde_json::to_string(&term).unwrap();
+            assert_eq!(term::T::from_json(&arena, &json).unwrap(), term);
+        }
+    }
+
+    /// JSON nested up to `MAX_DEPTH` levels is read back, and deeper JSON is
+    /// rejected rather than overflowing the stack.
+    #[test]
+    fn deep() {
+        let arena = Arena::new();
+        let succ = |n| r#"{"Succ":"#.repeat(n) + r#""Zero""# + &"}".repeat(n);
+        let term = term::T::from_json(&arena, &succ(json::MAX_DEPTH)).unwrap();
+        assert_eq!(term.as_nat(), Some(json::MAX_DEPTH));
+        assert!(term::T::from_json(&arena, &succ(json::MAX_DEPTH + 1)).is_err());
+        assert!(term::T::from_json(&arena, &succ(100_000)).is_err());
+    }
+
+    #[test]
+    fn schema() {
+        let arena = Arena::new();
+        let json = r#"{"IfElse":[{"IsZero":"Zero"},{"Plus":[{"Succ":"Zero"},"Wrong"]},"False"]}"#;
+        let term = term::T::from_json(&arena, json).unwrap();
+        assert_eq!(term.to_string(), "if iszero 0 then plus 1 wrong else false");
+        assert_eq!(serde_json::to_string(&term).unwrap(), json);
+        let error = term::T::from_json(&arena, r#"{"Minus":["Zero"]}"#).unwrap_err();
+        assert_eq!(error.to_string(), "Unknown term variant: Minus");
+        assert!(term::T::from_json(&arena, r#"{"Plus":["Zero"]}"#).is_err());
+    }
+}
@@ -1,2 +1,9 @@
+pub mod dot;
 pub mod eval;
+pub mod generate;
+pub mod json;
+pub mod metatheory;
+pub mod parse;
+pub mod sexp;
 pub mod term;
+pub mod wrong;
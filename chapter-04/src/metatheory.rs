@@ -0,0 +1,387 @@
+//! Executable versions of the metatheorems of chapter 3, which return a
+//! counterexample instead of panicking when a property fails for some term.
+
+use std::collections::HashSet;
+use std::collections::VecDeque;
+
+use typed_arena::Arena;
+
+use crate::term;
+
+/// A term violating, or that could not be checked against, one of the
+/// properties below.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Counterexample<'a> {
+    /// `term -> left` and `term -> right`, but `left ≠ right`.
+    Nondeterministic {
+        term: term::T<'a>,
+        left: term::T<'a>,
+        right: term::T<'a>,
+    },
+
+    /// `term ->* left` and `term ->* right` for normal forms `left ≠ right`.
+    NormalForms {
+        term: term::T<'a>,
+        left: term::T<'a>,
+        right: term::T<'a>,
+    },
+
+    /// `term -> next`, but `measure(next) ≥ measure(term)`.
+    NonDecreasing {
+        term: term::T<'a>,
+        next: term::T<'a>,
+    },
+
+    /// `measure(term)` overflows a `u128`, so no step from `term` is checked.
+    Unmeasured {
+        term: term::T<'a>,
+    },
+}
+
+/// Theorem 3.5.4 - If `t -> t'` and `t -> t''`, then `t' = t''`.
+///
+/// Checked against every derivation of the evaluation relation, so that
+/// overlapping rules are caught even though `step` picks only one.
+pub fn deterministic<'a>(arena: &'a Arena<term::T<'a>>, term: term::T<'a>) -> Result<(), Counterexample<'a>> {
+    let mut steps = term.steps(arena);
+    steps.extend(term.step(arena));
+    steps.dedup();
+    match steps.as_slice() {
+    | [] | [_] => Ok(()),
+    | [left, right, ..] => Err(Counterexample::Nondeterministic { term, left: *left, right: *right }),
+    }
+}
+
+/// Theorem 3.5.11 - If `t ->* u` and `t ->* u'`, where `u` and `u'` are both
+/// normal forms, then `u = u'`.
+///
+/// Explores every reduction sequence from `term`, so it should only be given
+/// terms that terminate (see `terminating`).
+pub fn unique_normal_forms<'a>(arena: &'a Arena<term::T<'a>>, term: term::T<'a>) -> Result<(), Counterexample<'a>> {
+    let mut seen = HashSet::new();
+    let mut queue = VecDeque::new();
+    let mut normal = None;
+
+    seen.insert(term);
+    queue.push_back(term);
+
+    while let Some(next) = queue.pop_front() {
+        let steps = next.steps(arena);
+        if steps.is_empty() {
+            match normal {
+            | None => normal = Some(next),
+            | Some(left) if left == next => (),
+            | Some(left) => return Err(Counterexample::NormalForms { term, left, right: next }),
+            }
+        }
+        for step in steps {
+            if seen.insert(step) {
+                queue.push_back(step);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Theorem 3.5.12 - For every term `t` there is some normal form `t'` such
+/// that `t ->* t'`.
+///
+/// Shown by checking that every step from every term reachable from `term`
+/// strictly decreases `measure`. Terms whose measure overflows a `u128` are
+/// reported as `Unmeasured` rather than skipped or taken for counterexamples.
+pub fn terminating<'a>(arena: &'a Arena<term::T<'a>>, term: term::T<'a>) -> Result<(), Counterexample<'a>> {
+    let mut stack = vec![term];
+    while let Some(term) = stack.pop() {
+        let measure = term.measure().ok_or(Counterexample::Unmeasured { term })?;
+        for next in term.steps(arena) {
+            match next.measure() {
+            | Some(next_measure) if next_measure < measure => stack.push(next),
+            | Some(_) => return Err(Counterexample::NonDecreasing { term, next }),
+            | None => return Err(Counterexample::Unmeasured { term: next }),
+            }
+        }
+    }
+    Ok(())
+}
+
+impl<'a> term::T<'a> {
+    /// Every `t'` such that `t -> t'` is derivable, trying every evaluation
+    /// rule rather than only the first that applies.
+    pub fn steps(&self, arena: &'a Arena<term::T<'a>>) -> Vec<term::T<'a>> {
+        use term::T::*;
+        let mut steps = Vec::new();
+        let alloc = |term| &*arena.alloc(term);
+        let numeric = |term: &term::T| term.is_numeric();
+
+        match *self {
+        | True | False | Zero | Wrong => (),
+        | IfElse(t_1, t_2, t_3) => {
+            // (E-IfTrue)
+            if *t_1 == True {
+                steps.push(*t_2);
+            }
+            // (E-IfFalse)
+            if *t_1 == False {
+                steps.push(*t_3);
+            }
+            // (E-If)
+            for t_1 in t_1.steps(arena) {
+                steps.push(IfElse(alloc(t_1), t_2, t_3));
+            }
+        }
+        | Succ(t_1) => {
+            // (E-Succ)
+            for t_1 in t_1.steps(arena) {
+                steps.push(Succ(alloc(t_1)));
+            }
+        }
+        | Pred(t_1) => {
+            // (E-PredZero)
+            if *t_1 == Zero {
+                steps.push(Zero);
+            }
+            // (E-PredSucc)
+            if let Succ(nv_1) = t_1 {
+                if numeric(nv_1) {
+                    steps.push(**nv_1);
+                }
+            }
+            // (E-Pred)
+            for t_1 in t_1.steps(arena) {
+                steps.push(Pred(alloc(t_1)));
+            }
+        }
+        | IsZero(t_1) => {
+            // (E-IsZeroZero)
+            if *t_1 == Zero {
+                steps.push(True);
+            }
+            // (E-IsZeroSucc)
+            if let Succ(nv_1) = t_1 {
+                if numeric(nv_1) {
+                    steps.push(False);
+                }
+            }
+            // (E-IsZero)
+            for t_1 in t_1.steps(arena) {
+                steps.push(IsZero(alloc(t_1)));
+            }
+        }
+        | Plus(t_1, t_2) => {
+            // (E-PlusZero)
+            if *t_1 == Zero && numeric(t_2) {
+                steps.push(*t_2);
+            }
+            // (E-PlusSucc)
+            if let Succ(nv_1) = t_1 {
+                if numeric(nv_1) && numeric(t_2) {
+                    steps.push(Succ(alloc(Plus(nv_1, t_2))));
+                }
+            }
+            // (E-Plus2)
+            if numeric(t_1) {
+                for t_2 in t_2.steps(arena) {
+                    steps.push(Plus(t_1, alloc(t_2)));
+                }
+            }
+            // (E-Plus1)
+            for t_1 in t_1.steps(arena) {
+                steps.push(Plus(alloc(t_1), t_2));
+            }
+        }
+        | Times(t_1, t_2) => {
+            // (E-TimesZero)
+            if *t_1 == Zero && numeric(t_2) {
+                steps.push(Zero);
+            }
+            // (E-TimesSucc)
+            if let Succ(nv_1) = t_1 {
+                if numeric(nv_1) && numeric(t_2) {
+                    steps.push(Plus(t_2, alloc(Times(nv_1, t_2))));
+                }
+            }
+            // (E-Times2)
+            if numeric(t_1) {
+                for t_2 in t_2.steps(arena) {
+                    steps.push(Times(t_1, alloc(t_2)));
+                }
+            }
+            // (E-Times1)
+            for t_1 in t_1.steps(arena) {
+                steps.push(Times(alloc(t_1), t_2));
+            }
+        }
+        | Eq(t_1, t_2) => {
+            match (t_1, t_2) {
+            // (E-EqZeroZero)
+            | (Zero, Zero) => steps.push(True),
+            // (E-EqZeroSucc)
+            | (Zero, Succ(nv_2)) if numeric(nv_2) => steps.push(False),
+            // (E-EqSuccZero)
+            | (Succ(nv_1), Zero) if numeric(nv_1) => steps.push(False),
+            // (E-EqSuccSucc)
+            | (Succ(nv_1), Succ(nv_2)) if numeric(nv_1) && numeric(nv_2) => steps.push(Eq(nv_1, nv_2)),
+            | _ => (),
+            }
+            // (E-Eq2)
+            if numeric(t_1) {
+                for t_2 in t_2.steps(arena) {
+                    steps.push(Eq(t_1, alloc(t_2)));
+                }
+            }
+            // (E-Eq1)
+            for t_1 in t_1.steps(arena) {
+                steps.push(Eq(alloc(t_1), t_2));
+            }
+        }
+        | Lt(t_1, t_2) => {
+            match (t_1, t_2) {
+            // (E-LtZero)
+            | (nv_1, Zero) if numeric(nv_1) => steps.push(False),
+            // (E-LtZeroSucc)
+            | (Zero, Succ(nv_2)) if numeric(nv_2) => steps.push(True),
+            // (E-LtSuccSucc)
+            | (Succ(nv_1), Succ(nv_2)) if numeric(nv_1) && numeric(nv_2) => steps.push(Lt(nv_1, nv_2)),
+            | _ => (),
+            }
+            // (E-Lt2)
+            if numeric(t_1) {
+                for t_2 in t_2.steps(arena) {
+                    steps.push(Lt(t_1, alloc(t_2)));
+                }
+            }
+            // (E-Lt1)
+            for t_1 in t_1.steps(arena) {
+                steps.push(Lt(alloc(t_1), t_2));
+            }
+        }
+        }
+
+        steps
+    }
+
+    /// A measure that strictly decreases with every evaluation step, or `None`
+    /// if it overflows a `u128`. It coincides with `size(t)` on terms without
+    /// `plus` or `times`, whose rules can grow a term, and so are weighted to
+    /// pay for the work they unfold into.
+    ///
+    /// ```text
+    /// μ(true) = μ(false) = μ(0) = μ(wrong) = 1
+    /// μ(succ t₁) = μ(pred t₁) = μ(iszero t₁) = μ(t₁) + 1
+    /// μ(if t₁ then t₂ else t₃) = μ(t₁) + μ(t₂) + μ(t₃) + 1
+    /// μ(eq t₁ t₂) = μ(lt t₁ t₂) = μ(t₁) + μ(t₂) + 1
+    /// μ(plus t₁ t₂) = 2μ(t₁) + μ(t₂) + 1
+    /// μ(times t₁ t₂) = 2μ(t₁)μ(t₂) + 2μ(t₁) + μ(t₂) + 1
+    /// ```
+    pub fn measure(&self) -> Option<u128> {
+        use term::T::*;
+        match self {
+        | True | False | Zero | Wrong => Some(1),
+        | Succ(t_1) | Pred(t_1) | IsZero(t_1) => t_1.measure()?.checked_add(1),
+        | IfElse(t_1, t_2, t_3) => t_1.measure()?
+            .checked_add(t_2.measure()?)?
+            .checked_add(t_3.measure()?)?
+            .checked_add(1),
+        | Eq(t_1, t_2) | Lt(t_1, t_2) => t_1.measure()?
+            .checked_add(t_2.measure()?)?
+            .checked_add(1),
+        | Plus(t_1, t_2) => t_1.measure()?
+            .checked_mul(2)?
+            .checked_add(t_2.measure()?)?
+            .checked_add(1),
+        | Times(t_1, t_2) => {
+            let (m_1, m_2) = (t_1.measure()?, t_2.measure()?);
+            m_1.checked_mul(m_2)?
+                .checked_add(m_1)?
+                .checked_mul(2)?
+                .checked_add(m_2)?
+                .checked_add(1)
+        }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    //! Note: as with the other tests in this crate, these properties are only
+    //! checked for S₃ and a sample of larger terms.
+
+    use typed_arena::Arena;
+
+    use crate::metatheory;
+    use crate::term;
+
+    /// Generates a pseudorandom term of depth at most `depth`, using the
+    /// xorshift generator state `seed`.
+    fn random<'a>(arena: &'a Arena<term::T<'a>>, seed: &mut u64, depth: usize) -> term::T<'a> {
+        use term::T::*;
+
+        *seed ^= *seed << 13;
+        *seed ^= *seed >> 7;
+        *seed ^= *seed << 17;
+
+        let subterm = |seed: &mut u64| &*arena.alloc(random(arena, seed, depth - 1));
+        match if depth == 0 { *seed % 3 } else { *seed % 14 } {
+        | 0 | 11 => True,
+        | 1 | 12 => False,
+        | 2 | 13 => Zero,
+        | 3 => Succ(subterm(seed)),
+        | 4 => Pred(subterm(seed)),
+        | 5 => IsZero(subterm(seed)),
+        | 6 => IfElse(subterm(seed), subterm(seed), subterm(seed)),
+        | 7 => Plus(subterm(seed), subterm(seed)),
+        | 8 => Times(subterm(seed), subterm(seed)),
+        | 9 => Eq(subterm(seed), subterm(seed)),
+        | _ => Lt(subterm(seed), subterm(seed)),
+        }
+    }
+
+    fn terms<'a>(arena: &'a Arena<term::T<'a>>) -> impl Iterator<Item = term::T<'a>> {
+        let mut seed = 0x2545_F491_4F6C_DD1D;
+        let random = (0..10_000)
+            .map(move |_| random(arena, &mut seed, 12))
+            .filter(|term| term.measure().is_some_and(|measure| measure < 100_000))
+            .collect::<Vec<_>>();
+        term::T::generate(arena, 3).chain(random)
+    }
+
+    /// Theorem 3.5.4
+    #[test]
+    fn deterministic() {
+        let arena = Arena::new();
+        for term in terms(&arena) {
+            assert_eq!(metatheory::deterministic(&arena, term), Ok(()));
+        }
+    }
+
+    /// Theorem 3.5.11
+    #[test]
+    fn unique_normal_forms() {
+        let arena = Arena::new();
+        for term in terms(&arena) {
+            assert_eq!(metatheory::unique_normal_forms(&arena, term), Ok(()));
+        }
+    }
+
+    /// Theorem 3.5.12, where every term is measured, including one whose
+    /// measure is past any that `terms` samples.
+    #[test]
+    fn terminating() {
+        let arena = Arena::new();
+        let source = "if true then 0 else times (times (times 1000 1000) (times 1000 1000)) (times (times 1000 1000) (times 1000 1000))";
+        let large = term::T::parse(&arena, source).unwrap();
+        assert!(large.measure().unwrap() > u64::MAX as u128);
+        for term in terms(&arena).chain(Some(large)) {
+            assert_eq!(metatheory::terminating(&arena, term), Ok(()));
+        }
+
+        let mut huge = term::T::Zero;
+        for _ in 0..8 {
+            huge = term::T::Times(arena.alloc(huge), arena.alloc(large));
+        }
+        assert_eq!(huge.measure(), None);
+        assert_eq!(metatheory::terminating(&arena, huge), Err(metatheory::Counterexample::Unmeasured { term: huge }));
+    }
+}
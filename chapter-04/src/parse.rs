@@ -0,0 +1,174 @@
+use std::vec;
+
+use anyhow::anyhow;
+use anyhow::Context as _;
+use tapl_core::source;
+use typed_arena::Arena;
+
+use crate::term;
+
+type Tokens<'s> = vec::IntoIter<&'s str>;
+
+impl<'a> term::T<'a> {
+    /// Parses the concrete syntax of terms, in which decimal numerals `n`
+    /// abbreviate `succ (succ (... 0))` with `n` applications of `succ`.
+    ///
+    /// ```text
+    /// t ::= true | false | 0 | 1 | 2 | ... | wrong
+    ///     | succ t | pred t | iszero t
+    ///     | plus t t | times t t | eq t t | lt t t
+    ///     | if t then t else t
+    ///     | (t)
+    /// ```
+    pub fn parse(arena: &'a Arena<term::T<'a>>, source: &str) -> anyhow::Result<Self> {
+        parse_all(arena, lex(source)?.into_iter())
+    }
+
+    /// Parses the `;`-terminated commands of a `.f` file for the book's
+    /// `arith` implementation, each of which is a term.
+    pub fn parse_file(arena: &'a Arena<term::T<'a>>, source: &str) -> Vec<anyhow::Result<Self>> {
+        source::commands(source)
+            .into_iter()
+            .map(|source::Command { line, tokens }| {
+                parse_all(arena, tokens.into_iter())
+                    .with_context(|| format!("Command on line {}", line))
+            })
+            .collect()
+    }
+}
+
+fn lex(source: &str) -> anyhow::Result<Vec<&str>> {
+    let mut tokens = Vec::new();
+    let mut chars = source.char_indices().peekable();
+    while let Some((start, char)) = chars.next() {
+        match char {
+        | '(' | ')' => tokens.push(&source[start..start + 1]),
+        | char if char.is_whitespace() => (),
+        | char if char.is_ascii_alphanumeric() => {
+            let mut end = start + 1;
+            while let Some(&(index, char)) = chars.peek() {
+                if !char.is_ascii_alphanumeric() {
+                    break;
+                }
+                end = index + 1;
+                chars.next();
+            }
+            tokens.push(&source[start..end]);
+        }
+        | char => return Err(anyhow!("Unexpected character: {}", char)),
+        }
+    }
+    Ok(tokens)
+}
+
+fn parse_all<'a>(arena: &'a Arena<term::T<'a>>, mut tokens: Tokens) -> anyhow::Result<term::T<'a>> {
+    let term = parse(arena, &mut tokens)?;
+    match tokens.next() {
+    | None => Ok(term),
+    | Some(token) => Err(anyhow!("Unexpected token after term: {}", token)),
+    }
+}
+
+fn parse<'a>(arena: &'a Arena<term::T<'a>>, tokens: &mut Tokens) -> anyhow::Result<term::T<'a>> {
+    use term::T::*;
+    let subterm = |tokens: &mut Tokens| -> anyhow::Result<&'a term::T<'a>> {
+        Ok(arena.alloc(parse(arena, tokens)?))
+    };
+    let term = match next(tokens)? {
+    | "true" => True,
+    | "false" => False,
+    | "wrong" => Wrong,
+    | "succ" => Succ(subterm(tokens)?),
+    | "pred" => Pred(subterm(tokens)?),
+    | "iszero" => IsZero(subterm(tokens)?),
+    | "plus" => Plus(subterm(tokens)?, subterm(tokens)?),
+    | "times" => Times(subterm(tokens)?, subterm(tokens)?),
+    | "eq" => Eq(subterm(tokens)?, subterm(tokens)?),
+    | "lt" => Lt(subterm(tokens)?, subterm(tokens)?),
+    | "if" => {
+        let t_1 = subterm(tokens)?;
+        expect(tokens, "then")?;
+        let t_2 = subterm(tokens)?;
+        expect(tokens, "else")?;
+        let t_3 = subterm(tokens)?;
+        IfElse(t_1, t_2, t_3)
+    }
+    | "(" => {
+        let term = parse(arena, tokens)?;
+        expect(tokens, ")")?;
+        term
+    }
+    | numeral if numeral.chars().all(|char| char.is_ascii_digit()) => term::T::numeral(arena, numeral)?,
+    | token => return Err(anyhow!("Unexpected token: {}", token)),
+    };
+    Ok(term)
+}
+
+fn next<'s>(tokens: &mut Tokens<'s>) -> anyhow::Result<&'s str> {
+    tokens
+        .next()
+        .ok_or_else(|| anyhow!("Unexpected end of input"))
+}
+
+fn expect(tokens: &mut Tokens, expected: &str) -> anyhow::Result<()> {
+    match next(tokens)? {
+    | token if token == expected => Ok(()),
+    | token => Err(anyhow!("Expected {}, but found {}", expected, token)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use typed_arena::Arena;
+
+    use crate::term;
+
+    /// Printing and parsing a term gives back the same term.
+    #[test]
+    fn round_trip() {
+        let arena = Arena::new();
+        for term in term::T::generate(&arena, 3) {
+            assert_eq!(term::T::parse(&arena, &term.to_string()).unwrap(), term);
+        }
+    }
+
+    #[test]
+    fn numerals() {
+        let arena = Arena::new();
+        let term = term::T::parse(&arena, "times (plus 2 3) succ 4").unwrap();
+        assert_eq!(term.to_string(), "times plus 2 3 5");
+        assert_eq!(term.eval(&arena).to_string(), "25");
+        assert_eq!(term::T::parse(&arena, "lt 3 (pred 4)").unwrap().eval(&arena), term::T::False);
+        assert!(term::T::parse(&arena, "if 0 then").is_err());
+        assert!(term::T::parse(&arena, "65536").is_ok());
+        assert!(term::T::parse(&arena, "65537").is_err());
+        assert!(term::T::parse(&arena, "99999999999999999999999").is_err());
+    }
+
+    /// The examples from the `test.f` file of the book's `arith`
+    /// implementation, with an ill-formed command that does not stop the
+    /// others from being read.
+    #[test]
+    fn file() {
+        let arena = Arena::new();
+        let source = "
+            /* Examples for testing */
+
+            true;
+            if false then true else false;
+
+            0;
+            succ (pred 0);
+            iszero (pred (succ (succ 0)));
+            succ succ;
+        ";
+        let terms = term::T::parse_file(&arena, source);
+        let values = terms[..5]
+            .iter()
+            .map(|term| term.as_ref().unwrap().eval(&arena).to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(values, ["true", "false", "0", "1", "false"]);
+        assert_eq!(format!("{:#}", terms[5].as_ref().unwrap_err()), "Command on line 10: Unexpected end of input");
+    }
+}
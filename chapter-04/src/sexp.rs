@@ -0,0 +1,105 @@
+use anyhow::anyhow;
+use tapl_core::sexp;
+use tapl_core::Sexp;
+use typed_arena::Arena;
+
+use crate::term;
+
+impl<'a> term::T<'a> {
+    /// Writes this term as an S-expression.
+    ///
+    /// ```text
+    /// t ::= true | false | 0 | wrong
+    ///     | (succ t) | (pred t) | (iszero t)
+    ///     | (plus t t) | (times t t) | (eq t t) | (lt t t)
+    ///     | (if t t t)
+    /// ```
+    pub fn to_sexp(&self) -> Sexp {
+        use term::T::*;
+        match self {
+        | True => Sexp::atom("true"),
+        | False => Sexp::atom("false"),
+        | Zero => Sexp::atom("0"),
+        | Wrong => Sexp::atom("wrong"),
+        | Succ(t_1) => Sexp::form("succ", vec![t_1.to_sexp()]),
+        | Pred(t_1) => Sexp::form("pred", vec![t_1.to_sexp()]),
+        | IsZero(t_1) => Sexp::form("iszero", vec![t_1.to_sexp()]),
+        | Plus(t_1, t_2) => Sexp::form("plus", vec![t_1.to_sexp(), t_2.to_sexp()]),
+        | Times(t_1, t_2) => Sexp::form("times", vec![t_1.to_sexp(), t_2.to_sexp()]),
+        | Eq(t_1, t_2) => Sexp::form("eq", vec![t_1.to_sexp(), t_2.to_sexp()]),
+        | Lt(t_1, t_2) => Sexp::form("lt", vec![t_1.to_sexp(), t_2.to_sexp()]),
+        | IfElse(t_1, t_2, t_3) => Sexp::form("if", vec![t_1.to_sexp(), t_2.to_sexp(), t_3.to_sexp()]),
+        }
+    }
+
+    /// Reads a term written by `to_sexp`, allocating it in `arena`. Decimal
+    /// numerals are also accepted as atoms.
+    pub fn from_sexp(arena: &'a Arena<term::T<'a>>, sexp: &Sexp) -> anyhow::Result<Self> {
+        use term::T::*;
+        let subterm = |sexp: &Sexp| -> anyhow::Result<&'a term::T<'a>> {
+            Ok(arena.alloc(Self::from_sexp(arena, sexp)?))
+        };
+        if let Sexp::Atom(atom) = sexp {
+            return match atom.as_str() {
+            | "true" => Ok(True),
+            | "false" => Ok(False),
+            | "wrong" => Ok(Wrong),
+            | numeral if numeral.chars().all(|char| char.is_ascii_digit()) => term::T::numeral(arena, numeral),
+            | _ => Err(anyhow!("Unknown term: {}", atom)),
+            };
+        }
+        let term = match sexp.as_form()? {
+        | ("succ", tail) => Succ(subterm(&sexp::arguments::<1>("succ", tail)?[0])?),
+        | ("pred", tail) => Pred(subterm(&sexp::arguments::<1>("pred", tail)?[0])?),
+        | ("iszero", tail) => IsZero(subterm(&sexp::arguments::<1>("iszero", tail)?[0])?),
+        | ("if", tail) => {
+            let [t_1, t_2, t_3] = sexp::arguments("if", tail)?;
+            IfElse(subterm(t_1)?, subterm(t_2)?, subterm(t_3)?)
+        }
+        | (head, tail) => {
+            let [t_1, t_2] = sexp::arguments(head, tail)?;
+            let (t_1, t_2) = (subterm(t_1)?, subterm(t_2)?);
+            match head {
+            | "plus" => Plus(t_1, t_2),
+            | "times" => Times(t_1, t_2),
+            | "eq" => Eq(t_1, t_2),
+            | "lt" => Lt(t_1, t_2),
+            | _ => return Err(anyhow!("Unknown term form: {}", head)),
+            }
+        }
+        };
+        Ok(term)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use tapl_core::Sexp;
+    use typed_arena::Arena;
+
+    use crate::term;
+
+    /// Writing and reading a term gives back the same term.
+    #[test]
+    fn round_trip() {
+        let arena = Arena::new();
+        for term in term::T::generate(&arena, 3) {
+            let sexp = term.to_sexp().to_string().parse::<Sexp>().unwrap();
+            assert_eq!(term::T::from_sexp(&arena, &sexp).unwrap(), term);
+        }
+    }
+
+    #[test]
+    fn numerals() {
+        let arena = Arena::new();
+        let sexp = "(if (lt 2 3) (times 2 (succ 0)) wrong)".parse::<Sexp>().unwrap();
+        let term = term::T::from_sexp(&arena, &sexp).unwrap();
+        assert_eq!(term.to_string(), "if lt 2 3 then times 2 1 else wrong");
+        assert_eq!(term.to_sexp().to_string(), "(if (lt (succ (succ 0)) (succ (succ (succ 0)))) (times (succ (succ 0)) (succ 0)) wrong)");
+        assert!(term::T::from_sexp(&arena, &"(minus 1 0)".parse().unwrap()).is_err());
+        assert!(term::T::from_sexp(&arena, &"(succ 1 0)".parse().unwrap()).is_err());
+        assert!(term::T::from_sexp(&arena, &"65537".parse().unwrap()).is_err());
+        assert!(term::T::from_sexp(&arena, &"99999999999999999999999".parse().unwrap()).is_err());
+    }
+}
@@ -1,13 +1,19 @@
 use std::collections::HashSet;
 use std::fmt;
 
+use anyhow::anyhow;
 use maplit::hashset;
+use serde::Serialize;
 use typed_arena::Arena;
 
+/// The largest numeral that is read or computed, since each one is unfolded
+/// into as many `succ` nodes.
+pub const MAX_NUMERAL: usize = 1 << 16;
+
 /// The set of terms `T`.
-#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Serialize)]
 pub enum T<'a> {
-    /// Constant true.
+    /// Constant true, serialized as `"True"`.
     ///
     /// ```text
     ///
@@ -16,7 +22,7 @@ pub enum T<'a> {
     /// ```
     True,
 
-    /// Constant false.
+    /// Constant false, serialized as `"False"`.
     ///
     /// ```text
     ///
@@ -25,7 +31,7 @@ pub enum T<'a> {
     /// ```
     False,
 
-    /// Constant zero.
+    /// Constant zero, serialized as `"Zero"`.
     ///
     /// ```text
     ///
@@ -34,7 +40,7 @@ pub enum T<'a> {
     /// ```
     Zero,
 
-    /// Successor.
+    /// Successor, serialized as `{"Succ": t₁}`.
     ///
     /// ```text
     ///   t₁ ∈ T
@@ -43,7 +49,7 @@ pub enum T<'a> {
     /// ```
     Succ(&'a T<'a>),
 
-    /// Predecessor.
+    /// Predecessor, serialized as `{"Pred": t₁}`.
     ///
     /// ```text
     ///   t₁ ∈ T
@@ -52,7 +58,7 @@ pub enum T<'a> {
     /// ```
     Pred(&'a T<'a>),
 
-    /// Zero test.
+    /// Zero test, serialized as `{"IsZero": t₁}`.
     ///
     /// ```text
     ///    t₁ ∈ T
@@ -61,7 +67,7 @@ pub enum T<'a> {
     /// ```
     IsZero(&'a T<'a>),
 
-    /// Conditional.
+    /// Conditional, serialized as `{"IfElse": [t₁, t₂, t₃]}`.
     ///
     /// ```text
     /// t₁ ∈ T    t₂ ∈ T    t₃ ∈ T
@@ -69,46 +75,77 @@ pub enum T<'a> {
     /// if t₁ then t₂ else t₃ ∈ T
     /// ```
     IfElse(&'a T<'a>, &'a T<'a>, &'a T<'a>),
-}
 
-impl<'a> T<'a> {
-    /// Definition 3.2.3 - The set `S_i` such that:
+    /// Addition, serialized as `{"Plus": [t₁, t₂]}`.
     ///
     /// ```text
-    /// S_{0}     = ∅
-    /// S_{i + 1} = { true, false, 0 }
-    ///           ∪ { succ t₁, pred t₁, iszero t₁ | t₁ ∈ S_i }
-    ///           ∪ { if t₁ then t₂ else t₃ | t₁, t₂, t₃ ∈ S_i }
+    ///  t₁ ∈ T    t₂ ∈ T
+    /// ----------------
+    /// plus t₁ t₂ ∈ T
     /// ```
-    pub fn generate(arena: &'a Arena<T<'a>>, index: usize) -> HashSet<T<'a>> {
-        use T::*;
-        match index {
-        | 0 => hashset!(),
-        | j => {
-            let i = j - 1;
-            let s_i = Self::generate(arena, i);
-
-            // { true, false, 0 }
-            let mut s_j = hashset!(True, False, Zero);
-
-            // ∪ { succ t₁, pred t₁, iszero t₁ | t₁ ∈ S_i }
-            for &t_1 in &s_i {
-                s_j.insert(Succ(arena.alloc(t_1)));
-                s_j.insert(Pred(arena.alloc(t_1)));
-                s_j.insert(IsZero(arena.alloc(t_1)));
-            }
-
-            // ∪ { if t₁ then t₂ else t₃ | t₁, t₂, t₃ ∈ S_i }
-            for &t_1 in &s_i {
-                for &t_2 in &s_i {
-                    for &t_3 in &s_i {
-                        s_j.insert(IfElse(arena.alloc(t_1), arena.alloc(t_2), arena.alloc(t_3)));
-                    }
-                }
-            }
-
-            s_j
+    Plus(&'a T<'a>, &'a T<'a>),
+
+    /// Multiplication, serialized as `{"Times": [t₁, t₂]}`.
+    ///
+    /// ```text
+    ///  t₁ ∈ T    t₂ ∈ T
+    /// ----------------
+    /// times t₁ t₂ ∈ T
+    /// ```
+    Times(&'a T<'a>, &'a T<'a>),
+
+    /// Equality test, serialized as `{"Eq": [t₁, t₂]}`.
+    ///
+    /// ```text
+    /// t₁ ∈ T    t₂ ∈ T
+    /// ----------------
+    ///  eq t₁ t₂ ∈ T
+    /// ```
+    Eq(&'a T<'a>, &'a T<'a>),
+
+    /// Less-than test, serialized as `{"Lt": [t₁, t₂]}`.
+    ///
+    /// ```text
+    /// t₁ ∈ T    t₂ ∈ T
+    /// ----------------
+    ///  lt t₁ t₂ ∈ T
+    /// ```
+    Lt(&'a T<'a>, &'a T<'a>),
+
+    /// Exercise 3.5.16 - Runtime error, produced only by the evaluation
+    /// relation in `wrong`, and not included in any `S_i`. Serialized as
+    /// `"Wrong"`.
+    ///
+    /// ```text
+    ///
+    /// ---------
+    /// wrong ∈ T
+    /// ```
+    Wrong,
+}
+
+impl<'a> T<'a> {
+    /// The numeral `succ (succ (... 0))` with `n` applications of `succ`.
+    pub fn nat(arena: &'a Arena<T<'a>>, n: usize) -> Self {
+        (0..n).fold(T::Zero, |nv, _| T::Succ(arena.alloc(nv)))
+    }
+
+    /// The numeral denoted by a decimal `numeral`, which may not exceed
+    /// `MAX_NUMERAL`.
+    pub fn numeral(arena: &'a Arena<T<'a>>, numeral: &str) -> anyhow::Result<Self> {
+        match numeral.parse() {
+        | Ok(n) if n <= MAX_NUMERAL => Ok(T::nat(arena, n)),
+        | _ => Err(anyhow!("Numeral exceeds {}: {}", MAX_NUMERAL, numeral)),
         }
+    }
+
+    /// The number denoted by this term, if it is a numeric value.
+    pub fn as_nat(&self) -> Option<usize> {
+        use T::*;
+        match self {
+        | Zero => Some(0),
+        | Succ(t_1) => t_1.as_nat().map(|n| n + 1),
+        | _ => None,
         }
     }
 
@@ -125,6 +162,9 @@ impl<'a> T<'a> {
         // Consts(0) = { 0 }
         | Zero => hashset!(Zero),
 
+        // Consts(wrong) = { wrong }
+        | Wrong => hashset!(Wrong),
+
         // Consts(succ t₁) = Consts(t₁)
         | Succ(t_1) => t_1.consts(),
 
@@ -140,6 +180,18 @@ impl<'a> T<'a> {
             .chain(t_2.consts())
             .chain(t_3.consts())
             .collect(),
+
+        // Consts(plus t₁ t₂) = Consts(t₁) ∪ Consts(t₂)
+        // Consts(times t₁ t₂) = Consts(t₁) ∪ Consts(t₂)
+        // Consts(eq t₁ t₂) = Consts(t₁) ∪ Consts(t₂)
+        // Consts(lt t₁ t₂) = Consts(t₁) ∪ Consts(t₂)
+        | Plus(t_1, t_2)
+        | Times(t_1, t_2)
+        | Eq(t_1, t_2)
+        | Lt(t_1, t_2) => t_1.consts()
+            .into_iter()
+            .chain(t_2.consts())
+            .collect(),
         }
     }
 
@@ -157,6 +209,9 @@ impl<'a> T<'a> {
         // size(0) = 1
         | Zero => 1,
 
+        // size(wrong) = 1
+        | Wrong => 1,
+
         // size(succ t₁) = size(t₁) + 1
         | Succ(t_1) => t_1.size() + 1,
 
@@ -171,6 +226,17 @@ impl<'a> T<'a> {
             + t_2.size()
             + t_3.size()
             + 1,
+
+        // size(plus t₁ t₂) = size(t₁) + size(t₂) + 1
+        // size(times t₁ t₂) = size(t₁) + size(t₂) + 1
+        // size(eq t₁ t₂) = size(t₁) + size(t₂) + 1
+        // size(lt t₁ t₂) = size(t₁) + size(t₂) + 1
+        | Plus(t_1, t_2)
+        | Times(t_1, t_2)
+        | Eq(t_1, t_2)
+        | Lt(t_1, t_2) => t_1.size()
+            + t_2.size()
+            + 1,
         }
     }
 
@@ -188,6 +254,9 @@ impl<'a> T<'a> {
         // depth(0) = 1
         | Zero => 1,
 
+        // depth(wrong) = 1
+        | Wrong => 1,
+
         // depth(succ t₁) = depth(t₁) + 1
         | Succ(t_1) => t_1.depth() + 1,
 
@@ -202,6 +271,17 @@ impl<'a> T<'a> {
             .max(t_2.depth())
             .max(t_3.depth())
             + 1,
+
+        // depth(plus t₁ t₂) = max(depth(t₁), depth(t₂)) + 1
+        // depth(times t₁ t₂) = max(depth(t₁), depth(t₂)) + 1
+        // depth(eq t₁ t₂) = max(depth(t₁), depth(t₂)) + 1
+        // depth(lt t₁ t₂) = max(depth(t₁), depth(t₂)) + 1
+        | Plus(t_1, t_2)
+        | Times(t_1, t_2)
+        | Eq(t_1, t_2)
+        | Lt(t_1, t_2) => t_1.depth()
+            .max(t_2.depth())
+            + 1,
         }
     }
 }
@@ -213,10 +293,16 @@ impl<'a> fmt::Display for T<'a> {
         | True => write!(fmt, "true"),
         | False => write!(fmt, "false"),
         | Zero => write!(fmt, "0"),
+        | nv if nv.is_numeric() => write!(fmt, "{}", nv.as_nat().unwrap()),
         | Succ(t_1) => write!(fmt, "succ {}", t_1),
         | Pred(t_1) => write!(fmt, "pred {}", t_1),
         | IsZero(t_1) => write!(fmt, "iszero {}", t_1),
         | IfElse(t_1, t_2, t_3) => write!(fmt, "if {} then {} else {}", t_1, t_2, t_3),
+        | Plus(t_1, t_2) => write!(fmt, "plus {} {}", t_1, t_2),
+        | Times(t_1, t_2) => write!(fmt, "times {} {}", t_1, t_2),
+        | Eq(t_1, t_2) => write!(fmt, "eq {} {}", t_1, t_2),
+        | Lt(t_1, t_2) => write!(fmt, "lt {} {}", t_1, t_2),
+        | Wrong => write!(fmt, "wrong"),
         }
     }
 }
@@ -0,0 +1,288 @@
+//! Exercise 3.5.16 - An alternative semantics in which nonsensical terms
+//! evaluate to an explicit `wrong` term instead of getting stuck.
+//!
+//! ```text
+//! badnat  ::= wrong | true | false
+//! badbool ::= wrong | nv
+//! ```
+
+use std::iter;
+
+use crate::term;
+
+use typed_arena::Arena;
+
+impl<'a> term::T<'a> {
+    /// The multi-step evaluation relation, extended with rules for `wrong`.
+    /// Every term evaluates either to a value or to `wrong`.
+    pub fn eval_wrong(&self, arena: &'a Arena<term::T<'a>>) -> term::T<'a> {
+        iter::successors(Some(*self), |term| term.step_wrong(arena))
+            .last()
+            .unwrap()
+    }
+
+    /// The one-step evaluation relation, extended with rules for `wrong`.
+    pub fn step_wrong(&self, arena: &'a Arena<term::T<'a>>) -> Option<term::T<'a>> {
+        use term::T::*;
+        let next = match self {
+        //
+        // ----------------------------- (E-IfTrue)
+        // if true then t₂ else t₃ -> t₂
+        | IfElse(True, &t_2, _) => t_2,
+
+        //
+        // ------------------------------ (E-IfFalse)
+        // if false then t₂ else t₃ -> t₂
+        | IfElse(False, _, &t_3) => t_3,
+
+        //
+        // ------------------------------------ (E-IfWrong)
+        // if badbool then t₂ else t₃ -> wrong
+        | IfElse(t_1, _, _) if t_1.is_badbool() => Wrong,
+
+        //                    t₁ -> t₁'
+        // ----------------------------------------------- (E-If)
+        // if t₁ then t₂ else t₃ -> if t₁' then t₂ else t₃
+        | IfElse(t_1, t_2, t_3) => IfElse(arena.alloc(t_1.step_wrong(arena)?), t_2, t_3),
+
+        //
+        // --------------------- (E-SuccWrong)
+        // succ badnat -> wrong
+        | Succ(t_1) if t_1.is_badnat() => Wrong,
+
+        //      t₁ -> t₁'
+        // ------------------- (E-Succ)
+        // succ t₁ -> succ t₁'
+        | Succ(t_1) => Succ(arena.alloc(t_1.step_wrong(arena)?)),
+
+        //
+        // ----------- (E-PredZero)
+        // pred 0 -> 0
+        | Pred(Zero) => Zero,
+
+        //
+        // ---------------------- (E-PredSucc)
+        // pred (succ nv₁) -> nv₁
+        | Pred(&Succ(&nv_1)) if nv_1.is_numeric() => nv_1,
+
+        //
+        // --------------------- (E-PredWrong)
+        // pred badnat -> wrong
+        | Pred(t_1) if t_1.is_badnat() => Wrong,
+
+        //      t₁ -> t₁'
+        // ------------------- (E-Pred)
+        // pred t₁ -> pred t₁'
+        | Pred(t_1) => Pred(arena.alloc(t_1.step_wrong(arena)?)),
+
+        //
+        // ---------------- (E-IsZeroZero)
+        // iszero 0 -> true
+        | IsZero(Zero) => True,
+
+        //
+        // -------------------------- (E-IsZeroSucc)
+        // iszero (succ nv₁) -> false
+        | IsZero(Succ(nv_1)) if nv_1.is_numeric() => False,
+
+        //
+        // ----------------------- (E-IsZeroWrong)
+        // iszero badnat -> wrong
+        | IsZero(t_1) if t_1.is_badnat() => Wrong,
+
+        //        t₁ -> t₁'
+        // ----------------------- (E-IsZero)
+        // iszero t₁ -> iszero t₁'
+        | IsZero(t_1) => IsZero(arena.alloc(t_1.step_wrong(arena)?)),
+
+        //
+        // ----------------- (E-PlusZero)
+        // plus 0 nv₂ -> nv₂
+        | Plus(Zero, &nv_2) if nv_2.is_numeric() => nv_2,
+
+        //
+        // ------------------------------------------ (E-PlusSucc)
+        // plus (succ nv₁) nv₂ -> succ (plus nv₁ nv₂)
+        | Plus(Succ(nv_1), nv_2) if nv_1.is_numeric() && nv_2.is_numeric() => {
+            Succ(arena.alloc(Plus(nv_1, nv_2)))
+        }
+
+        //
+        // ----------------------- (E-PlusWrong1)
+        // plus badnat t₂ -> wrong
+        | Plus(t_1, _) if t_1.is_badnat() => Wrong,
+
+        //
+        // ------------------------ (E-PlusWrong2)
+        // plus nv₁ badnat -> wrong
+        | Plus(nv_1, t_2) if nv_1.is_numeric() && t_2.is_badnat() => Wrong,
+
+        //           t₂ -> t₂'
+        // ---------------------------- (E-Plus2)
+        // plus nv₁ t₂ -> plus nv₁ t₂'
+        | Plus(nv_1, t_2) if nv_1.is_numeric() => Plus(nv_1, arena.alloc(t_2.step_wrong(arena)?)),
+
+        //          t₁ -> t₁'
+        // ------------------------- (E-Plus1)
+        // plus t₁ t₂ -> plus t₁' t₂
+        | Plus(t_1, t_2) => Plus(arena.alloc(t_1.step_wrong(arena)?), t_2),
+
+        //
+        // ----------------- (E-TimesZero)
+        // times 0 nv₂ -> 0
+        | Times(Zero, nv_2) if nv_2.is_numeric() => Zero,
+
+        //
+        // ------------------------------------------------ (E-TimesSucc)
+        // times (succ nv₁) nv₂ -> plus nv₂ (times nv₁ nv₂)
+        | Times(Succ(nv_1), nv_2) if nv_1.is_numeric() && nv_2.is_numeric() => {
+            Plus(nv_2, arena.alloc(Times(nv_1, nv_2)))
+        }
+
+        //
+        // ------------------------ (E-TimesWrong1)
+        // times badnat t₂ -> wrong
+        | Times(t_1, _) if t_1.is_badnat() => Wrong,
+
+        //
+        // ------------------------- (E-TimesWrong2)
+        // times nv₁ badnat -> wrong
+        | Times(nv_1, t_2) if nv_1.is_numeric() && t_2.is_badnat() => Wrong,
+
+        //            t₂ -> t₂'
+        // ------------------------------ (E-Times2)
+        // times nv₁ t₂ -> times nv₁ t₂'
+        | Times(nv_1, t_2) if nv_1.is_numeric() => Times(nv_1, arena.alloc(t_2.step_wrong(arena)?)),
+
+        //           t₁ -> t₁'
+        // --------------------------- (E-Times1)
+        // times t₁ t₂ -> times t₁' t₂
+        | Times(t_1, t_2) => Times(arena.alloc(t_1.step_wrong(arena)?), t_2),
+
+        //
+        // -------------- (E-EqZeroZero)
+        // eq 0 0 -> true
+        | Eq(Zero, Zero) => True,
+
+        //
+        // ------------------------ (E-EqZeroSucc)
+        // eq 0 (succ nv₂) -> false
+        | Eq(Zero, Succ(nv_2)) if nv_2.is_numeric() => False,
+
+        //
+        // ------------------------ (E-EqSuccZero)
+        // eq (succ nv₁) 0 -> false
+        | Eq(Succ(nv_1), Zero) if nv_1.is_numeric() => False,
+
+        //
+        // ------------------------------------- (E-EqSuccSucc)
+        // eq (succ nv₁) (succ nv₂) -> eq nv₁ nv₂
+        | Eq(Succ(nv_1), Succ(nv_2)) if nv_1.is_numeric() && nv_2.is_numeric() => Eq(nv_1, nv_2),
+
+        //
+        // --------------------- (E-EqWrong1)
+        // eq badnat t₂ -> wrong
+        | Eq(t_1, _) if t_1.is_badnat() => Wrong,
+
+        //
+        // ---------------------- (E-EqWrong2)
+        // eq nv₁ badnat -> wrong
+        | Eq(nv_1, t_2) if nv_1.is_numeric() && t_2.is_badnat() => Wrong,
+
+        //         t₂ -> t₂'
+        // ------------------------ (E-Eq2)
+        // eq nv₁ t₂ -> eq nv₁ t₂'
+        | Eq(nv_1, t_2) if nv_1.is_numeric() => Eq(nv_1, arena.alloc(t_2.step_wrong(arena)?)),
+
+        //        t₁ -> t₁'
+        // --------------------- (E-Eq1)
+        // eq t₁ t₂ -> eq t₁' t₂
+        | Eq(t_1, t_2) => Eq(arena.alloc(t_1.step_wrong(arena)?), t_2),
+
+        //
+        // ------------------ (E-LtZero)
+        // lt nv₁ 0 -> false
+        | Lt(nv_1, Zero) if nv_1.is_numeric() => False,
+
+        //
+        // ----------------------- (E-LtZeroSucc)
+        // lt 0 (succ nv₂) -> true
+        | Lt(Zero, Succ(nv_2)) if nv_2.is_numeric() => True,
+
+        //
+        // ------------------------------------- (E-LtSuccSucc)
+        // lt (succ nv₁) (succ nv₂) -> lt nv₁ nv₂
+        | Lt(Succ(nv_1), Succ(nv_2)) if nv_1.is_numeric() && nv_2.is_numeric() => Lt(nv_1, nv_2),
+
+        //
+        // --------------------- (E-LtWrong1)
+        // lt badnat t₂ -> wrong
+        | Lt(t_1, _) if t_1.is_badnat() => Wrong,
+
+        //
+        // ---------------------- (E-LtWrong2)
+        // lt nv₁ badnat -> wrong
+        | Lt(nv_1, t_2) if nv_1.is_numeric() && t_2.is_badnat() => Wrong,
+
+        //         t₂ -> t₂'
+        // ------------------------ (E-Lt2)
+        // lt nv₁ t₂ -> lt nv₁ t₂'
+        | Lt(nv_1, t_2) if nv_1.is_numeric() => Lt(nv_1, arena.alloc(t_2.step_wrong(arena)?)),
+
+        //        t₁ -> t₁'
+        // --------------------- (E-Lt1)
+        // lt t₁ t₂ -> lt t₁' t₂
+        | Lt(t_1, t_2) => Lt(arena.alloc(t_1.step_wrong(arena)?), t_2),
+
+        // Normal form.
+        | _ => return None,
+        };
+
+        Some(next)
+    }
+
+    /// `badnat ::= wrong | true | false`, i.e. normal forms that cannot be
+    /// used as numbers.
+    pub fn is_badnat(&self) -> bool {
+        use term::T::*;
+        matches!(self, Wrong | True | False)
+    }
+
+    /// `badbool ::= wrong | nv`, i.e. normal forms that cannot be used as
+    /// booleans.
+    pub fn is_badbool(&self) -> bool {
+        use term::T::*;
+        matches!(self, Wrong) || self.is_numeric()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    //! Note: as with the original semantics, this property is verified *only*
+    //! for the set of terms in S₃.
+
+    use std::iter;
+
+    use typed_arena::Arena;
+
+    use crate::term;
+
+    /// Exercise 3.5.16 - A term gets stuck under the original semantics
+    /// exactly when it evaluates to `wrong` under the extended semantics.
+    #[test]
+    fn stuck_iff_wrong() {
+        let arena = Arena::new();
+        for term in term::T::generate(&arena, 3) {
+            let original = iter::successors(Some(term), |term| term.step(&arena))
+                .last()
+                .unwrap();
+            let extended = term.eval_wrong(&arena);
+
+            assert_eq!(!original.is_value(), extended == term::T::Wrong);
+            if original.is_value() {
+                assert_eq!(original, extended);
+            }
+        }
+    }
+}
@@ -0,0 +1,84 @@
+use criterion::black_box;
+use criterion::criterion_group;
+use criterion::criterion_main;
+use criterion::BenchmarkId;
+use criterion::Criterion;
+use tapl_core::Interner;
+use tapl_core::Sexp;
+use typed_arena::Arena;
+
+use chapter_07::term::Term;
+
+/// `λm. λn. λs. λz. m s (n s z)`
+const PLUS: &str = "(abs m (abs n (abs s (abs z (app (app (var 3) (var 1)) (app (app (var 2) (var 1)) (var 0)))))))";
+
+/// `λm. λn. λs. m (n s)`
+const TIMES: &str = "(abs m (abs n (abs s (app (var 2) (app (var 1) (var 0))))))";
+
+/// `λx. x`
+const ID: &str = "(abs x (var 0))";
+
+/// The Church numeral `λs. λz. s (s (... z))` with `n` applications of `s`.
+fn church<'a>(arena: &'a Arena<Term<'a>>, n: usize) -> &'a Term<'a> {
+    let mut term = Term::Var { index: 0 };
+    for _ in 0..n {
+        term = Term::App {
+            fun: arena.alloc(Term::Var { index: 1 }),
+            arg: arena.alloc(term),
+        };
+    }
+    arena.alloc(Term::Abs {
+        hint: String::from("s"),
+        term: arena.alloc(Term::Abs {
+            hint: String::from("z"),
+            term: arena.alloc(term),
+        }),
+    })
+}
+
+/// `op m n (λx. x) (λx. x)`, which forces every application of `s` in the
+/// resulting numeral, since call-by-value evaluation stops at abstractions.
+fn arithmetic<'a>(arena: &'a Arena<Term<'a>>, op: &str, m: usize, n: usize) -> &'a Term<'a> {
+    let parse = |source: &str| &*arena.alloc(Term::from_sexp(arena, &source.parse::<Sexp>().unwrap()).unwrap());
+    let app = |fun, arg| &*arena.alloc(Term::App { fun, arg });
+    let op = parse(op);
+    let id = parse(ID);
+    app(app(app(app(op, church(arena, m)), church(arena, n)), id), id)
+}
+
+fn church_arithmetic(criterion: &mut Criterion) {
+    for (name, op, sizes) in [("church_plus", PLUS, [10, 100, 1000]), ("church_times", TIMES, [3, 10, 30])] {
+        let mut group = criterion.benchmark_group(name);
+
+        for n in sizes {
+            let input: &'static Arena<Term<'static>> = Box::leak(Box::new(Arena::new()));
+            let term = arithmetic(input, op, n, n);
+
+            group.bench_with_input(BenchmarkId::new("arena", n), &n, |bencher, _| {
+                bencher.iter(|| {
+                    let arena = Arena::new();
+                    black_box(term.eval(&arena).clone());
+                })
+            });
+
+            group.bench_with_input(BenchmarkId::new("interner", n), &n, |bencher, _| {
+                bencher.iter(|| {
+                    let interner = Interner::default();
+                    black_box(interner.intern(term).eval(&interner).clone());
+                })
+            });
+
+            group.bench_with_input(BenchmarkId::new("explicit", n), &n, |bencher, _| {
+                bencher.iter(|| {
+                    let arena = Arena::new();
+                    black_box(term.eval_explicit(&arena).0);
+                })
+            });
+        }
+
+        group.finish();
+    }
+}
+
+criterion_group!(benches, church_arithmetic);
+criterion_main!(benches);
@@ -0,0 +1,80 @@
+use criterion::black_box;
+use criterion::criterion_group;
+use criterion::criterion_main;
+use criterion::BenchmarkId;
+use criterion::Criterion;
+use tapl_core::DeBruijn;
+use typed_arena::Arena;
+
+use chapter_07::term::Term;
+
+/// The body `λs. λz. s (s (... (x z)))` of a Church numeral, with `n`
+/// applications of `s`, and the variable `x` free just outside it.
+fn body<'a>(arena: &'a Arena<Term<'a>>, n: usize) -> &'a Term<'a> {
+    let mut term = Term::App {
+        fun: arena.alloc(Term::Var { index: 2 }),
+        arg: arena.alloc(Term::Var { index: 0 }),
+    };
+    for _ in 0..n {
+        term = Term::App {
+            fun: arena.alloc(Term::Var { index: 1 }),
+            arg: arena.alloc(term),
+        };
+    }
+    arena.alloc(Term::Abs {
+        hint: String::from("s"),
+        term: arena.alloc(Term::Abs {
+            hint: String::from("z"),
+            term: arena.alloc(term),
+        }),
+    })
+}
+
+/// The Church numeral `λs. λz. s (s (... z))` with `n` applications of `s`.
+fn church<'a>(arena: &'a Arena<Term<'a>>, n: usize) -> &'a Term<'a> {
+    let mut term = Term::Var { index: 0 };
+    for _ in 0..n {
+        term = Term::App {
+            fun: arena.alloc(Term::Var { index: 1 }),
+            arg: arena.alloc(term),
+        };
+    }
+    arena.alloc(Term::Abs {
+        hint: String::from("s"),
+        term: arena.alloc(Term::Abs {
+            hint: String::from("z"),
+            term: arena.alloc(term),
+        }),
+    })
+}
+
+fn substitute_top(criterion: &mut Criterion) {
+    let mut group = criterion.benchmark_group("substitute_top");
+
+    for n in [10, 100, 1000] {
+        let input: &'static Arena<Term<'static>> = Box::leak(Box::new(Arena::new()));
+        let body = body(input, n);
+        let to = church(input, n);
+
+        group.bench_with_input(BenchmarkId::new("fused", n), &n, |bencher, _| {
+            bencher.iter(|| {
+                let arena = Arena::new();
+                black_box(body.substitute_top(&arena, to));
+            })
+        });
+
+        group.bench_with_input(BenchmarkId::new("unfused", n), &n, |bencher, _| {
+            bencher.iter(|| {
+                let arena = Arena::new();
+                black_box(body
+                    .substitute(&arena, 0, arena.alloc(to.shift(&arena, 1)))
+                    .shift(&arena, -1));
+            })
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, substitute_top);
+criterion_main!(benches);
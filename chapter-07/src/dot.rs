@@ -0,0 +1,129 @@
+use std::io;
+use std::iter;
+
+use tapl_core::dot;
+use tapl_core::dot::Tree;
+use typed_arena::Arena;
+
+use crate::reduce::Child;
+use crate::term::Term;
+
+impl<'a> Term<'a> {
+    /// The abstract syntax tree of this term, with the subterm at `redex`
+    /// highlighted. Variables are labeled with their de Bruijn index, and
+    /// bound variables point back to their abstraction.
+    pub fn to_dot(&self, redex: Option<&[Child]>) -> Tree {
+        let mut tree = Tree::default();
+        self._to_dot(&mut tree, &mut Vec::new(), redex);
+        tree
+    }
+
+    fn _to_dot(&self, tree: &mut Tree, binders: &mut Vec<usize>, redex: Option<&[Child]>) -> usize {
+        let node = match self {
+        | Term::Var { index } => {
+            let node = tree.node(index.to_string());
+            if let Some(&binder) = binders.iter().rev().nth(*index as usize) {
+                tree.binder(node, binder);
+            }
+            node
+        }
+        | Term::Abs { hint, term } => {
+            let node = tree.node(format!("λ{}", hint));
+            binders.push(node);
+            let body = term._to_dot(tree, binders, dot::descend(redex, Child::Body));
+            binders.pop();
+            tree.child(node, "body", body);
+            node
+        }
+        | Term::App { fun, arg } => {
+            let node = tree.node("app");
+            let fun = fun._to_dot(tree, binders, dot::descend(redex, Child::Fun));
+            let arg = arg._to_dot(tree, binders, dot::descend(redex, Child::Arg));
+            tree.child(node, "fun", fun);
+            tree.child(node, "arg", arg);
+            node
+        }
+        };
+
+        if let Some([]) = redex {
+            tree.highlight(node);
+        }
+
+        node
+    }
+
+    /// Writes the abstract syntax tree of this term in the Graphviz DOT
+    /// language.
+    pub fn write_dot<W: io::Write>(&self, writer: &mut W) -> anyhow::Result<()> {
+        self.to_dot(None).write("term", writer)
+    }
+
+    /// Writes every term in the call-by-value evaluation sequence of this term
+    /// as its own graph, highlighting the redex contracted by the following
+    /// step.
+    pub fn write_trace<W: io::Write>(&'a self, arena: &'a Arena<Term<'a>>, writer: &mut W) -> anyhow::Result<()> {
+        iter::successors(Some(self), |term| {
+                term.step(arena)
+                    .map(|next| &*arena.alloc(next))
+            })
+            .enumerate()
+            .try_for_each(|(step, term)| term.to_dot(term.redex().as_deref()).write(&format!("step{}", step), writer))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use tapl_core::Sexp;
+    use typed_arena::Arena;
+
+    use crate::term::Term;
+
+    #[test]
+    fn trace() {
+        let arena = Arena::new();
+        let sexp = "(app (abs x (abs y (var 1))) (abs z (var 0)))".parse::<Sexp>().unwrap();
+        let term = Term::from_sexp(&arena, &sexp).unwrap();
+        let mut dot = Vec::new();
+        term.write_trace(&arena, &mut dot).unwrap();
+        assert_eq!(String::from_utf8(dot).unwrap(), "\
+digraph step0 {
+    node [shape=box];
+    0 [label=\"app\", style=filled, fillcolor=yellow];
+    1 [label=\"λx\", style=filled, fillcolor=yellow];
+    2 [label=\"λy\", style=filled, fillcolor=yellow];
+    3 [label=\"1\", style=filled, fillcolor=yellow];
+    4 [label=\"λz\", style=filled, fillcolor=yellow];
+    5 [label=\"0\", style=filled, fillcolor=yellow];
+    2 -> 3 [label=\"body\"];
+    1 -> 2 [label=\"body\"];
+    4 -> 5 [label=\"body\"];
+    0 -> 1 [label=\"fun\"];
+    0 -> 4 [label=\"arg\"];
+    3 -> 1 [style=dashed, constraint=false];
+    5 -> 4 [style=dashed, constraint=false];
+}
+digraph step1 {
+    node [shape=box];
+    0 [label=\"λy\"];
+    1 [label=\"λz\"];
+    2 [label=\"0\"];
+    1 -> 2 [label=\"body\"];
+    0 -> 1 [label=\"body\"];
+    2 -> 1 [style=dashed, constraint=false];
+}
+");
+    }
+
+    /// Free variables have no binder to point to.
+    #[test]
+    fn free() {
+        let arena = Arena::new();
+        let term = Term::from_sexp(&arena, &"(abs x (app (var 0) (var 3)))".parse().unwrap()).unwrap();
+        let mut dot = Vec::new();
+        term.write_dot(&mut dot).unwrap();
+        let dot = String::from_utf8(dot).unwrap();
+        assert!(dot.contains("2 -> 0 [style=dashed"));
+        assert!(!dot.contains("3 -> "));
+    }
+}
@@ -0,0 +1,80 @@
+use anyhow::anyhow;
+use serde_json::Value;
+use tapl_core::json;
+use typed_arena::Arena;
+
+use crate::term::Term;
+
+impl<'a> Term<'a> {
+    /// Reads back a term serialized as JSON, allocating it in `arena`. See
+    /// the variants of `Term` for the schema.
+    pub fn from_json(arena: &'a Arena<Term<'a>>, json: &str) -> anyhow::Result<Self> {
+        json::read(json, |value| Self::from_value(arena, value))
+    }
+
+    pub fn from_value(arena: &'a Arena<Term<'a>>, value: &Value) -> anyhow::Result<Self> {
+        let subterm = |value: &Value| -> anyhow::Result<&'a Term<'a>> {
+            Ok(arena.alloc(json::grow(|| Self::from_value(arena, value))?))
+        };
+        match json::variant(value)? {
+        | ("Var", content) => {
+            Ok(Term::Var {
+                index: json::leaf(json::field(content, "index")?)?,
+            })
+        }
+        | ("Abs", content) => {
+            Ok(Term::Abs {
+                hint: json::leaf(json::field(content, "hint")?)?,
+                term: subterm(json::field(content, "term")?)?,
+            })
+        }
+        | ("App", content) => {
+            Ok(Term::App {
+                fun: subterm(json::field(content, "fun")?)?,
+                arg: subterm(json::field(content, "arg")?)?,
+            })
+        }
+        | (tag, _) => Err(anyhow!("Unknown term variant: {}", tag)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use typed_arena::Arena;
+
+    use crate::term::Term;
+
+    /// Serializing and deserializing a term gives back the same term,
+    /// including its hints.
+    #[test]
+    fn round_trip() {
+        let arena = Arena::new();
+        for term in Term::generate(&arena, 7) {
+            let json = serde_json::to_string(&term).unwrap();
+            assert_eq!(Term::from_json(&arena, &json).unwrap(), term);
+        }
+
+        let body = (0..100).fold(Term::Var { index: 0 }, |z, _| Term::App {
+            fun: arena.alloc(Term::Var { index: 1 }),
+            arg: arena.alloc(z),
+        });
+        let hundred = ["z", "s"].iter().fold(body, |term, hint| Term::Abs {
+            hint: String::from(*hint),
+            term: arena.alloc(term),
+        });
+        let json = serde_json::to_string(&hundred).unwrap();
+        assert_eq!(Term::from_json(&arena, &json).unwrap(), hundred);
+    }
+
+    #[test]
+    fn schema() {
+        let arena = Arena::new();
+        let json = r#"{"Abs":{"hint":"x","term":{"App":{"fun":{"Var":{"index":0}},"arg":{"Var":{"index":1}}}}}}"#;
+        let term = Term::from_json(&arena, json).unwrap();
+        assert_eq!(serde_json::to_string(&term).unwrap(), json);
+        assert!(Term::from_json(&arena, r#"{"Var":{}}"#).is_err());
+        assert!(Term::from_json(&arena, r#"{"Let":{}}"#).is_err());
+    }
+}
@@ -1 +1,37 @@
+pub mod dot;
+pub mod explicit;
+pub mod json;
+pub mod named;
+pub mod owned;
+pub mod parse;
+pub mod reduce;
+pub mod sexp;
 pub mod term;
+
+#[doc(hidden)]
+pub use tapl_macros;
+
+/// Builds a `Term` from its concrete syntax, resolving variables to de Bruijn
+/// indices at compile time, and allocating its subterms in the given arena.
+///
+/// ```
+/// use typed_arena::Arena;
+/// use chapter_07::term::Term;
+///
+/// let arena = Arena::new();
+/// let term = chapter_07::term!(arena, (λx. λy. x) (λz. z));
+/// assert_eq!(term, Term::from_sexp(&arena, &"(app (abs x (abs y (var 1))) (abs z (var 0)))".parse().unwrap()).unwrap());
+/// ```
+///
+/// Unbound variables are rejected at compile time:
+///
+/// ```compile_fail
+/// let arena = typed_arena::Arena::new();
+/// chapter_07::term!(arena, λx. y);
+/// ```
+#[macro_export]
+macro_rules! term {
+    ($arena:expr, $($term:tt)*) => {
+        $crate::tapl_macros::term!(untyped, $crate, $arena, $($term)*)
+    };
+}
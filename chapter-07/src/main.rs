@@ -1,96 +1,171 @@
+use std::env;
 use std::io;
+use std::io::Read as _;
 use std::io::Write as _;
 
+use anyhow::anyhow;
+use tapl_core::Sexp;
 use typed_arena::Arena;
 
+use chapter_07::term;
+use chapter_07::parse::Command;
 use chapter_07::term::Context;
 use chapter_07::term::Term;
 
+/// Evaluates example terms, printing every step.
+///
+/// With `--read-sexp`, the terms are instead read as S-expressions from
+/// standard input, and with `--write-sexp`, every step is printed as an
+/// S-expression. With `--read-tapl`, the terms are instead read from the
+/// commands of a `.f` file for the book's reference implementation on
+/// standard input, and commands that fail to parse are reported. With
+/// `--write-dot`, every step is instead drawn as a Graphviz DOT graph, with
+/// the redex contracted by the next step highlighted. With `--stats`, each
+/// term is instead evaluated both by substitution and with explicit
+/// substitutions, printing the steps and allocations each took.
 pub fn main() -> anyhow::Result<()> {
 
+    let mut read_sexp = false;
+    let mut read_tapl = false;
+    let mut write_sexp = false;
+    let mut write_dot = false;
+    let mut stats = false;
+
+    for flag in env::args().skip(1) {
+        match flag.as_str() {
+        | "--read-sexp" => read_sexp = true,
+        | "--read-tapl" => read_tapl = true,
+        | "--write-sexp" => write_sexp = true,
+        | "--write-dot" => write_dot = true,
+        | "--stats" => stats = true,
+        | flag => return Err(anyhow!("Unknown flag: {}", flag)),
+        }
+    }
+
     let arena = Arena::new();
+    let mut context = Context::default();
+
+    let terms = if read_tapl {
+        read_file(&arena, &mut context)?
+    } else if read_sexp {
+        read(&arena)?
+    } else {
+        examples(&arena)
+    };
 
-    let terms = vec![
+    for term in terms {
+        if stats {
+            compare(term, &arena, &mut context)?;
+        } else if write_dot {
+            trace(term, &arena)?;
+        } else {
+            step(term, &arena, &mut context, write_sexp)?;
+        }
+    }
+
+    Ok(())
+}
+
+fn examples<'a>(arena: &'a Arena<Term<'a>>) -> Vec<Term<'a>> {
+    vec![
         // (λx. x) (λy. y) -->* λy. y
-        Term::App {
-            fun: arena.alloc(Term::Abs {
-                hint: String::from("x"),
-                term: arena.alloc(Term::Var { index: 0 }),
-            }),
-            arg: arena.alloc(Term::Abs {
-                hint: String::from("y"),
-                term: arena.alloc(Term::Var { index: 0 }),
-            }),
-        },
+        term!(arena, (λx. x) (λy. y)),
 
         // (λx. λx. x) (λa. λb. a) (λa. λb. b) -->* λa. λb. b
-        Term::App {
-            fun: arena.alloc(Term::App {
-                fun: arena.alloc(Term::Abs {
-                    hint: String::from("x"),
-                    term: arena.alloc(Term::Abs {
-                        hint: String::from("x"),
-                        term: arena.alloc(Term::Var { index: 0 }),
-                    })
-                }),
-                arg: arena.alloc(Term::Abs {
-                    hint: String::from("a"),
-                    term: arena.alloc(Term::Abs {
-                        hint: String::from("b"),
-                        term: arena.alloc(Term::Var { index: 1 }),
-                    })
-                }),
-            }),
-            arg: arena.alloc(Term::Abs {
-                hint: String::from("a"),
-                term: arena.alloc(Term::Abs {
-                    hint: String::from("b"),
-                    term: arena.alloc(Term::Var { index: 0 }),
-                })
-            }),
-        },
+        term!(arena, (λx. λx. x) (λa. λb. a) (λa. λb. b)),
 
         // (λx. λz. x) (λz. z) -->* λz. λz'. z'
-        Term::App {
-            fun: arena.alloc(Term::Abs {
-                hint: String::from("x"),
-                term: arena.alloc(Term::Abs {
-                    hint: String::from("z"),
-                    term: arena.alloc(Term::Var { index: 1 }),
-                }),
-            }),
-            arg: arena.alloc(Term::Abs {
-                hint: String::from("z"),
-                term: arena.alloc(Term::Var { index: 0 }),
-            }),
-        },
-    ];
+        term!(arena, (λx. λz. x) (λz. z)),
+    ]
+}
 
-    for term in terms {
-        step(term, &arena)?;
-    }
+fn read<'a>(arena: &'a Arena<Term<'a>>) -> anyhow::Result<Vec<Term<'a>>> {
+    let mut source = String::new();
+    io::stdin().read_to_string(&mut source)?;
+    Sexp::parse_all(&source)?
+        .iter()
+        .map(|sexp| Term::from_sexp(arena, sexp))
+        .collect()
+}
 
-    Ok(())
+/// Reads the terms to evaluate from a `.f` file, naming the free variables it
+/// binds in `context`.
+fn read_file<'a>(arena: &'a Arena<Term<'a>>, context: &mut Context) -> anyhow::Result<Vec<Term<'a>>> {
+    let mut source = String::new();
+    io::stdin().read_to_string(&mut source)?;
+    let mut terms = Vec::new();
+    for command in Term::parse_file(arena, &source) {
+        match command {
+        | Ok(Command::Eval(term)) => terms.push(term),
+        | Ok(Command::Bind(name)) => {
+            context.push(name);
+        }
+        | Ok(_) => (),
+        | Err(error) => eprintln!("{:#}", error),
+        }
+    }
+    Ok(terms)
 }
 
 fn step<'a>(
     mut term: Term<'a>,
     arena: &'a Arena<Term<'a>>,
+    context: &mut Context,
+    write_sexp: bool,
 ) -> anyhow::Result<()> {
 
     let stdout = io::stdout();
     let mut stdout = stdout.lock();
-    let mut context = Context::default();
 
-    term.write(&mut context, &mut stdout)?;
-    write!(&mut stdout, "\n")?;
+    write(&term, context, &mut stdout, write_sexp)?;
 
-    while let Some(next) = term.step(&arena) {
+    while let Some(next) = term.step(arena) {
         term = next;
-        term.write(&mut context, &mut stdout)?;
-        write!(&mut stdout, "\n")?;
+        write(&term, context, &mut stdout, write_sexp)?;
     }
 
-    write!(&mut stdout, "\n")?;
+    writeln!(&mut stdout)?;
+    Ok(())
+}
+
+fn trace<'a>(term: Term<'a>, arena: &'a Arena<Term<'a>>) -> anyhow::Result<()> {
+    let stdout = io::stdout();
+    let mut stdout = stdout.lock();
+    arena.alloc(term).write_trace(arena, &mut stdout)
+}
+
+fn compare<'a>(term: Term<'a>, arena: &'a Arena<Term<'a>>, context: &mut Context) -> anyhow::Result<()> {
+    let stdout = io::stdout();
+    let mut stdout = stdout.lock();
+
+    let term = &*arena.alloc(term);
+    let (eager, eager_stats) = term.eval_stats(arena);
+    let (explicit, explicit_stats) = term.eval_explicit(arena);
+
+    term.write(context, &mut stdout)?;
+    writeln!(&mut stdout)?;
+
+    for (name, value, stats) in [("eager", eager, eager_stats), ("explicit", &explicit, explicit_stats)].iter() {
+        write!(&mut stdout, "{}: ", name)?;
+        value.write(context, &mut stdout)?;
+        writeln!(&mut stdout, " ({} steps, {} allocations)", stats.steps, stats.allocations)?;
+    }
+
+    writeln!(&mut stdout)?;
+    Ok(())
+}
+
+fn write<W: io::Write>(
+    term: &Term,
+    context: &mut Context,
+    writer: &mut W,
+    write_sexp: bool,
+) -> anyhow::Result<()> {
+    if write_sexp {
+        write!(writer, "{}", term.to_sexp())?;
+    } else {
+        term.write(context, writer)?;
+    }
+    writeln!(writer)?;
     Ok(())
 }
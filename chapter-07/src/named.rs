@@ -0,0 +1,304 @@
+//! Terms with named variables (§5.3), and conversions between them and
+//! nameless terms (§6.1.5).
+//!
+//! Substitution on named terms renames binders to avoid capture instead of
+//! shifting indices, so evaluating here serves as an independent check on
+//! the de Bruijn machinery.
+
+use std::collections::HashSet;
+use std::fmt;
+use std::iter;
+
+use anyhow::anyhow;
+use typed_arena::Arena;
+
+use crate::term::Context;
+use crate::term::Term;
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Named<'a> {
+    Var {
+        name: String,
+    },
+    Abs {
+        name: String,
+        term: &'a Named<'a>,
+    },
+    App {
+        fun: &'a Named<'a>,
+        arg: &'a Named<'a>,
+    },
+}
+
+impl<'a> Named<'a> {
+    /// The free variables `FV(t)` of this term (Definition 5.3.2).
+    pub fn free(&self) -> HashSet<&str> {
+        match self {
+        | Named::Var { name } => iter::once(name.as_str()).collect(),
+        | Named::Abs { name, term } => {
+            let mut free = term.free();
+            free.remove(name.as_str());
+            free
+        }
+        | Named::App { fun, arg } => {
+            let mut free = fun.free();
+            free.extend(arg.free());
+            free
+        }
+        }
+    }
+
+    /// Capture-avoiding substitution `[x ↦ s] t` (Definition 5.3.5), where
+    /// binders that would capture a free variable of `s` are first renamed.
+    ///
+    /// ```text
+    /// [x ↦ s] x       = s
+    /// [x ↦ s] y       = y                       if y ≠ x
+    /// [x ↦ s] (λy. t) = λy. t                   if y = x
+    /// [x ↦ s] (λy. t) = λy. [x ↦ s] t           if y ≠ x and y ∉ FV(s)
+    /// [x ↦ s] (λy. t) = λy'. [x ↦ s] [y ↦ y'] t if y ≠ x, y ∈ FV(s), and y' is fresh
+    /// [x ↦ s] (t t)   = ([x ↦ s] t) ([x ↦ s] t)
+    /// ```
+    pub fn substitute(&'a self, arena: &'a Arena<Named<'a>>, var: &str, to: &'a Named<'a>) -> &'a Self {
+        match self {
+        | Named::Var { name } if name == var => to,
+        | Named::Var { .. } => self,
+        | Named::Abs { name, .. } if name == var => self,
+        | Named::Abs { name, term } if to.free().contains(name.as_str()) => {
+            let mut avoid = to.free();
+            avoid.extend(term.free());
+            avoid.insert(var);
+
+            let mut fresh = name.clone();
+            while avoid.contains(fresh.as_str()) {
+                fresh.push('\'');
+            }
+
+            let renamed = term.substitute(arena, name, arena.alloc(Named::Var { name: fresh.clone() }));
+            arena.alloc(Named::Abs {
+                name: fresh,
+                term: renamed.substitute(arena, var, to),
+            })
+        }
+        | Named::Abs { name, term } => {
+            arena.alloc(Named::Abs {
+                name: name.clone(),
+                term: term.substitute(arena, var, to),
+            })
+        }
+        | Named::App { fun, arg } => {
+            arena.alloc(Named::App {
+                fun: fun.substitute(arena, var, to),
+                arg: arg.substitute(arena, var, to),
+            })
+        }
+        }
+    }
+
+    pub fn eval(&'a self, arena: &'a Arena<Named<'a>>) -> &'a Self {
+        iter::successors(Some(self), |term| term.step(arena))
+            .last()
+            .unwrap_or(self)
+    }
+
+    /// The call-by-value evaluation relation, as for `Term::step`.
+    pub fn step(&'a self, arena: &'a Arena<Named<'a>>) -> Option<&'a Self> {
+        match self {
+        | Named::App { fun: Named::Abs { name, term }, arg } if arg.is_value() => {
+            Some(term.substitute(arena, name, arg))
+        }
+        | Named::App { fun, arg } if fun.is_value() => {
+            Some(arena.alloc(Named::App {
+                fun,
+                arg: arg.step(arena)?,
+            }))
+        }
+        | Named::App { fun, arg } => {
+            Some(arena.alloc(Named::App {
+                fun: fun.step(arena)?,
+                arg,
+            }))
+        }
+        | _ => None,
+        }
+    }
+
+    pub fn is_value(&self) -> bool {
+        match self {
+        | Named::Var { .. }
+        | Named::Abs { .. } => true,
+        | Named::App { .. } => false,
+        }
+    }
+
+    /// `removenames_Γ(t)`, the nameless term for this term in the naming
+    /// context `Γ`, listed from the outermost binder in. Fails if a free
+    /// variable of this term is not in `Γ`.
+    pub fn remove_names<'b>(&self, arena: &'b Arena<Term<'b>>, context: &[&str]) -> anyhow::Result<Term<'b>> {
+        self._remove_names(arena, &mut context.to_vec())
+    }
+
+    fn _remove_names<'b, 'c>(&'c self, arena: &'b Arena<Term<'b>>, context: &mut Vec<&'c str>) -> anyhow::Result<Term<'b>> {
+        match self {
+        | Named::Var { name } => {
+            context.iter()
+                .rev()
+                .position(|bound| bound == name)
+                .map(|index| Term::Var { index: index as i64 })
+                .ok_or_else(|| anyhow!("Unbound variable: {}", name))
+        }
+        | Named::Abs { name, term } => {
+            context.push(name);
+            let term = term._remove_names(arena, context);
+            context.pop();
+            Ok(Term::Abs {
+                hint: name.clone(),
+                term: arena.alloc(term?),
+            })
+        }
+        | Named::App { fun, arg } => {
+            Ok(Term::App {
+                fun: arena.alloc(fun._remove_names(arena, context)?),
+                arg: arena.alloc(arg._remove_names(arena, context)?),
+            })
+        }
+        }
+    }
+}
+
+impl<'a> Term<'a> {
+    /// `restorenames_Γ(t)`, a named term for this term in the naming context
+    /// `Γ`. Binders are named after their hints, primed where they would
+    /// otherwise shadow a name in scope.
+    pub fn restore_names<'b>(&self, arena: &'b Arena<Named<'b>>, context: &mut Context) -> Named<'b> {
+        match self {
+        | Term::Var { index } => Named::Var { name: context.name(*index).into_owned() },
+        | Term::Abs { hint, term } => {
+            context.bind(hint, |context, name| Named::Abs {
+                name: name.to_owned(),
+                term: arena.alloc(term.restore_names(arena, context)),
+            })
+        }
+        | Term::App { fun, arg } => {
+            Named::App {
+                fun: arena.alloc(fun.restore_names(arena, context)),
+                arg: arena.alloc(arg.restore_names(arena, context)),
+            }
+        }
+        }
+    }
+}
+
+impl<'a> fmt::Display for Named<'a> {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+        | Named::Var { name } => write!(fmt, "{}", name),
+        | Named::Abs { name, term } => write!(fmt, "(λ{}. {})", name, term),
+        | Named::App { fun, arg } => write!(fmt, "({} {})", fun, arg),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use std::iter;
+
+    use tapl_core::DeBruijn;
+    use typed_arena::Arena;
+
+    use crate::named::Named;
+    use crate::term::Alpha;
+    use crate::term::Context;
+    use crate::term::Term;
+
+    /// Every named term with exactly `size` nodes over the variables `names`.
+    fn generate<'a>(arena: &'a Arena<Named<'a>>, size: usize, names: &[&str]) -> Vec<&'a Named<'a>> {
+        match size {
+        | 0 => Vec::new(),
+        | 1 => names.iter().map(|name| &*arena.alloc(Named::Var { name: name.to_string() })).collect(),
+        | _ => {
+            let mut terms = Vec::new();
+            for name in names {
+                for term in generate(arena, size - 1, names) {
+                    terms.push(&*arena.alloc(Named::Abs { name: name.to_string(), term }));
+                }
+            }
+            for fun_size in 1..size - 1 {
+                for fun in generate(arena, fun_size, names) {
+                    for arg in generate(arena, size - 1 - fun_size, names) {
+                        terms.push(&*arena.alloc(Named::App { fun, arg }));
+                    }
+                }
+            }
+            terms
+        }
+        }
+    }
+
+    /// `removenames` is a left inverse of `restorenames` up to α-equivalence.
+    #[test]
+    fn round_trip() {
+        let terms = Arena::new();
+        let nameds = Arena::new();
+        for term in Term::generate(&terms, 8) {
+            let named = term.restore_names(&nameds, &mut Context::default());
+            let nameless = named.remove_names(&terms, &[]).unwrap();
+            assert_eq!(Alpha(&nameless), Alpha(&term));
+        }
+    }
+
+    /// Named substitution agrees with `substitute_top`, including where it
+    /// has to rename a binder to avoid capture.
+    #[test]
+    fn substitute() {
+        let terms = Arena::new();
+        let nameds = Arena::new();
+        let names = ["x", "y", "z"];
+        let bodies = (1..=5).flat_map(|size| generate(&nameds, size, &names)).collect::<Vec<_>>();
+        let tos = (1..=3).flat_map(|size| generate(&nameds, size, &names)).collect::<Vec<_>>();
+        for body in &bodies {
+            let nameless_body = body.remove_names(&terms, &["x", "y", "z", "x"]).unwrap();
+            for to in &tos {
+                let nameless_to = terms.alloc(to.remove_names(&terms, &names).unwrap());
+                let expected = nameless_body.substitute_top(&terms, nameless_to);
+                let actual = body
+                    .substitute(&nameds, "x", to)
+                    .remove_names(&terms, &names)
+                    .unwrap();
+                assert_eq!(Alpha(&actual), Alpha(&expected), "[x ↦ {}] {}", to, body);
+            }
+        }
+    }
+
+    #[test]
+    fn capture() {
+        let arena = Arena::new();
+        let y = arena.alloc(Named::Var { name: String::from("y") });
+        let x = arena.alloc(Named::Var { name: String::from("x") });
+        let term = arena.alloc(Named::Abs { name: String::from("y"), term: x });
+        assert_eq!(term.substitute(&arena, "x", y).to_string(), "(λy'. y)");
+    }
+
+    /// Evaluating a closed term in either representation takes the same
+    /// steps, up to α-equivalence, and the same number of them up to a bound.
+    #[test]
+    fn differential() {
+        let terms = Arena::new();
+        let nameds = Arena::new();
+        for term in Term::generate(&terms, 9) {
+            let named = &*nameds.alloc(term.restore_names(&nameds, &mut Context::default()));
+            let nameless = iter::successors(Some(&*terms.alloc(term)), |term| {
+                term.step(&terms).map(|next| &*terms.alloc(next))
+            });
+            let nameless = nameless.take(32).collect::<Vec<_>>();
+            let named = iter::successors(Some(named), |named| named.step(&nameds))
+                .take(32)
+                .collect::<Vec<_>>();
+            assert_eq!(nameless.len(), named.len(), "{}", named[0]);
+            for (nameless, named) in nameless.into_iter().zip(named) {
+                assert_eq!(Alpha(&named.remove_names(&terms, &[]).unwrap()), Alpha(nameless));
+            }
+        }
+    }
+}
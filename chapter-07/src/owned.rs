@@ -0,0 +1,177 @@
+//! Terms that own their subterms, for embedding the interpreter in
+//! applications that cannot thread an arena's lifetime through their own
+//! types.
+//!
+//! Subterms are reference counted, so owned terms are `Send + Sync` and
+//! cheap to clone. Each operation converts into a scratch arena, runs there,
+//! and converts the result back. Conversions in both directions preserve
+//! sharing, so a term whose arena form is a small DAG stays small.
+
+use std::collections::HashMap;
+use std::io;
+use std::sync::Arc;
+
+use typed_arena::Arena;
+
+use crate::term;
+use crate::term::Context;
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Term {
+    Var {
+        /// de Bruijn index
+        index: i64,
+    },
+    Abs {
+        /// Hint for the name of the bound variable
+        hint: String,
+        term: Arc<Term>,
+    },
+    App {
+        fun: Arc<Term>,
+        arg: Arc<Term>,
+    },
+}
+
+impl<'a> From<&term::Term<'a>> for Term {
+    fn from(term: &term::Term<'a>) -> Self {
+        Term::_from(term, &mut HashMap::new())
+    }
+}
+
+impl Term {
+    fn _from<'a>(term: &term::Term<'a>, cache: &mut HashMap<*const term::Term<'a>, Arc<Term>>) -> Self {
+        let mut share = |term: &'a term::Term<'a>| -> Arc<Term> {
+            if let Some(owned) = cache.get(&(term as *const _)) {
+                return Arc::clone(owned);
+            }
+            let owned = Arc::new(Term::_from(term, cache));
+            cache.insert(term, Arc::clone(&owned));
+            owned
+        };
+        match term {
+        | term::Term::Var { index } => Term::Var { index: *index },
+        | term::Term::Abs { hint, term } => {
+            Term::Abs {
+                hint: hint.clone(),
+                term: share(term),
+            }
+        }
+        | term::Term::App { fun, arg } => {
+            Term::App {
+                fun: share(fun),
+                arg: share(arg),
+            }
+        }
+        }
+    }
+
+    /// Allocates this term and its subterms in `arena`.
+    pub fn to_arena<'a>(&self, arena: &'a Arena<term::Term<'a>>) -> &'a term::Term<'a> {
+        self._to_arena(arena, &mut HashMap::new())
+    }
+
+    fn _to_arena<'a>(&self, arena: &'a Arena<term::Term<'a>>, cache: &mut HashMap<*const Term, &'a term::Term<'a>>) -> &'a term::Term<'a> {
+        let mut share = |term: &Arc<Term>| -> &'a term::Term<'a> {
+            if let Some(term) = cache.get(&Arc::as_ptr(term)) {
+                return term;
+            }
+            let allocated = term._to_arena(arena, cache);
+            cache.insert(Arc::as_ptr(term), allocated);
+            allocated
+        };
+        let term = match self {
+        | Term::Var { index } => term::Term::Var { index: *index },
+        | Term::Abs { hint, term } => {
+            term::Term::Abs {
+                hint: hint.clone(),
+                term: share(term),
+            }
+        }
+        | Term::App { fun, arg } => {
+            term::Term::App {
+                fun: share(fun),
+                arg: share(arg),
+            }
+        }
+        };
+        arena.alloc(term)
+    }
+
+    pub fn eval(&self) -> Self {
+        let arena = Arena::new();
+        Term::from(self.to_arena(&arena).eval(&arena))
+    }
+
+    pub fn is_value(&self) -> bool {
+        match self {
+        | Term::Var { .. }
+        | Term::Abs { .. } => true,
+        | Term::App { .. } => false,
+        }
+    }
+
+    pub fn write<W: io::Write>(&self, context: &mut Context, writer: &mut W) -> anyhow::Result<()> {
+        let arena = Arena::new();
+        self.to_arena(&arena).write(context, writer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use std::sync::Arc;
+    use std::thread;
+
+    use tapl_core::Sexp;
+    use typed_arena::Arena;
+
+    use crate::owned;
+    use crate::term::Context;
+    use crate::term::Term;
+
+    #[test]
+    fn send_sync() {
+        fn assert<T: Send + Sync>() {}
+        assert::<owned::Term>();
+    }
+
+    /// Converting to an owned term and back gives back the same term, and
+    /// evaluating either form gives the same result.
+    #[test]
+    fn round_trip() {
+        let arena = Arena::new();
+        for term in Term::generate(&arena, 8) {
+            let owned = owned::Term::from(&term);
+            assert_eq!(owned.to_arena(&arena), &term);
+            assert_eq!(owned.eval(), owned::Term::from(arena.alloc(term).eval(&arena)));
+        }
+    }
+
+    /// Subterms shared in the arena stay shared, so duplicating a term
+    /// through repeated application does not blow up its owned form.
+    #[test]
+    fn sharing() {
+        let arena = Arena::new();
+        let mut term = &*arena.alloc(Term::Var { index: 0 });
+        for _ in 0..64 {
+            term = arena.alloc(Term::App { fun: term, arg: term });
+        }
+        match owned::Term::from(term) {
+        | owned::Term::App { fun, arg } => assert!(Arc::ptr_eq(&fun, &arg)),
+        | _ => panic!("Expected application"),
+        }
+    }
+
+    #[test]
+    fn thread() {
+        let arena = Arena::new();
+        let sexp = "(app (abs x (app (var 0) (var 0))) (abs y (var 0)))".parse::<Sexp>().unwrap();
+        let owned = owned::Term::from(&Term::from_sexp(&arena, &sexp).unwrap());
+        let value = thread::spawn(move || owned.eval()).join().unwrap();
+
+        let mut buffer = Vec::new();
+        value.write(&mut Context::default(), &mut buffer).unwrap();
+        assert_eq!(String::from_utf8(buffer).unwrap(), "(λy. y)");
+    }
+}
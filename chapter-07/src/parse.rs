@@ -0,0 +1,251 @@
+use std::iter;
+use std::vec;
+
+use anyhow::anyhow;
+use anyhow::Context as _;
+use tapl_core::source;
+use tapl_core::DeBruijn as _;
+use typed_arena::Arena;
+
+use crate::term::Term;
+
+/// A command of a `.f` file for the book's `untyped` and `fulluntyped`
+/// implementations.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Command<'a> {
+    /// `t;`, a term to evaluate
+    Eval(Term<'a>),
+    /// `x/;`, which binds `x` as a free variable of the commands after it
+    Bind(String),
+    /// `x = t;`, which abbreviates `t` as `x` in the commands after it
+    Define(String, Term<'a>),
+}
+
+/// A binding made by a command, in scope for the commands after it.
+enum Global<'a> {
+    Free(String),
+    Abbreviation(String, &'a Term<'a>),
+}
+
+impl<'a> Term<'a> {
+    /// Parses the concrete syntax of closed terms.
+    ///
+    /// ```text
+    /// t ::= lambda x. t | t t | x | (t)
+    /// ```
+    pub fn parse(arena: &'a Arena<Term<'a>>, source: &str) -> anyhow::Result<Self> {
+        let tokens = source::lex(source)
+            .into_iter()
+            .map(|(_, token)| token)
+            .collect();
+        Parser::new(arena, tokens, &[]).parse_all()
+    }
+
+    /// Parses the `;`-terminated commands of a `.f` file. A variable bound by
+    /// `x/;` is free in the commands after it, with the most recent binding
+    /// at index 0, and an abbreviation is replaced by the term it stands for.
+    pub fn parse_file(arena: &'a Arena<Term<'a>>, source: &str) -> Vec<anyhow::Result<Command<'a>>> {
+        let mut globals = Vec::new();
+        source::commands(source)
+            .into_iter()
+            .map(|source::Command { line, tokens }| {
+                command(arena, &mut globals, tokens)
+                    .with_context(|| format!("Command on line {}", line))
+            })
+            .collect()
+    }
+}
+
+fn command<'a>(arena: &'a Arena<Term<'a>>, globals: &mut Vec<Global<'a>>, tokens: Vec<&str>) -> anyhow::Result<Command<'a>> {
+    match tokens.as_slice() {
+    | [name, "/"] if is_name(name) => {
+        globals.push(Global::Free(name.to_string()));
+        Ok(Command::Bind(name.to_string()))
+    }
+    | [name, "=", ..] if is_name(name) => {
+        let term = Parser::new(arena, tokens[2..].to_vec(), globals).parse_all()?;
+        globals.push(Global::Abbreviation(name.to_string(), arena.alloc(term.clone())));
+        Ok(Command::Define(name.to_string(), term))
+    }
+    | _ => Parser::new(arena, tokens, globals).parse_all().map(Command::Eval),
+    }
+}
+
+struct Parser<'a, 's, 'g> {
+    arena: &'a Arena<Term<'a>>,
+    tokens: iter::Peekable<vec::IntoIter<&'s str>>,
+    /// Names bound by enclosing abstractions, from the outermost in
+    bound: Vec<&'s str>,
+    globals: &'g [Global<'a>],
+}
+
+impl<'a, 's, 'g> Parser<'a, 's, 'g> {
+    fn new(arena: &'a Arena<Term<'a>>, tokens: Vec<&'s str>, globals: &'g [Global<'a>]) -> Self {
+        Parser {
+            arena,
+            tokens: tokens.into_iter().peekable(),
+            bound: Vec::new(),
+            globals,
+        }
+    }
+
+    fn parse_all(mut self) -> anyhow::Result<Term<'a>> {
+        let term = self.term()?;
+        match self.tokens.next() {
+        | None => Ok(term),
+        | Some(token) => Err(anyhow!("Unexpected token after term: {}", token)),
+        }
+    }
+
+    /// Application is left associative, and an abstraction extends as far
+    /// right as possible.
+    fn term(&mut self) -> anyhow::Result<Term<'a>> {
+        let mut fun = match self.tokens.peek() {
+        | Some(&"lambda") => return self.abs(),
+        | _ => self.atom()?,
+        };
+        loop {
+            let arg = match self.tokens.peek() {
+            | Some(&"lambda") => self.abs()?,
+            | Some(&token) if token == "(" || is_name(token) => self.atom()?,
+            | _ => return Ok(fun),
+            };
+            fun = Term::App {
+                fun: self.arena.alloc(fun),
+                arg: self.arena.alloc(arg),
+            };
+        }
+    }
+
+    fn abs(&mut self) -> anyhow::Result<Term<'a>> {
+        self.expect("lambda")?;
+        let name = self.name()?;
+        self.expect(".")?;
+        self.bound.push(name);
+        let term = self.term();
+        self.bound.pop();
+        Ok(Term::Abs {
+            hint: name.to_owned(),
+            term: self.arena.alloc(term?),
+        })
+    }
+
+    fn atom(&mut self) -> anyhow::Result<Term<'a>> {
+        if self.tokens.peek() == Some(&"(") {
+            self.expect("(")?;
+            let term = self.term()?;
+            self.expect(")")?;
+            return Ok(term);
+        }
+        let name = self.name()?;
+        self.var(name)
+    }
+
+    /// The variable `name`, bound either by an enclosing abstraction or by
+    /// an earlier command.
+    fn var(&self, name: &str) -> anyhow::Result<Term<'a>> {
+        if let Some(index) = self.bound.iter().rev().position(|bound| *bound == name) {
+            return Ok(Term::Var { index: index as i64 });
+        }
+
+        let depth = self.bound.len() as i64;
+        let mut free = 0;
+        for global in self.globals.iter().rev() {
+            match global {
+            | Global::Free(bound) if bound == name => return Ok(Term::Var { index: depth + free }),
+            | Global::Free(_) => free += 1,
+            | Global::Abbreviation(bound, term) if bound == name => return Ok(term.shift(self.arena, depth + free)),
+            | Global::Abbreviation(..) => (),
+            }
+        }
+
+        Err(anyhow!("Unbound variable: {}", name))
+    }
+
+    fn name(&mut self) -> anyhow::Result<&'s str> {
+        match self.next()? {
+        | token if is_name(token) => Ok(token),
+        | token => Err(anyhow!("Expected variable, but found {}", token)),
+        }
+    }
+
+    fn next(&mut self) -> anyhow::Result<&'s str> {
+        self.tokens
+            .next()
+            .ok_or_else(|| anyhow!("Unexpected end of input"))
+    }
+
+    fn expect(&mut self, expected: &str) -> anyhow::Result<()> {
+        match self.next()? {
+        | token if token == expected => Ok(()),
+        | token => Err(anyhow!("Expected {}, but found {}", expected, token)),
+        }
+    }
+}
+
+fn is_name(token: &str) -> bool {
+    token != "lambda" && token.starts_with(|char: char| char.is_alphabetic() || char == '_')
+}
+
+#[cfg(test)]
+mod tests {
+
+    use typed_arena::Arena;
+
+    use crate::parse::Command;
+    use crate::term::Alpha;
+    use crate::term::Context;
+    use crate::term::Term;
+
+    /// Printing and parsing a closed term gives back an α-equivalent term.
+    #[test]
+    fn round_trip() {
+        let arena = Arena::new();
+        for term in Term::generate(&arena, 7) {
+            let mut source = Vec::new();
+            term.write(&mut Context::default(), &mut source).unwrap();
+            let source = String::from_utf8(source).unwrap().replace('λ', "lambda ");
+            assert_eq!(Alpha(&Term::parse(&arena, &source).unwrap()), Alpha(&term), "{}", source);
+        }
+    }
+
+    /// The examples from the `test.f` file of the book's `untyped`
+    /// implementation, extended with an abbreviation used under a binder.
+    #[test]
+    fn file() {
+        let arena = Arena::new();
+        let source = "
+            /* Examples for testing */
+
+            x/;
+            x;
+
+            lambda x. x;
+            (lambda x. x) (lambda x. x x);
+
+            id = lambda y. y;
+            lambda z. id x z;
+            y;
+        ";
+        let commands = Term::parse_file(&arena, source);
+        let term = |source| Term::parse(&arena, source).unwrap();
+        let expected = [
+            Command::Bind(String::from("x")),
+            Command::Eval(Term::Var { index: 0 }),
+            Command::Eval(term("lambda x. x")),
+            Command::Eval(term("(lambda x. x) (lambda x. x x)")),
+            Command::Define(String::from("id"), term("lambda y. y")),
+        ];
+        for (actual, expected) in commands.iter().zip(&expected) {
+            assert_eq!(actual.as_ref().unwrap(), expected);
+        }
+
+        // `x` is free at index 0 at the top level, so at index 1 under `λz`
+        match &commands[5] {
+        | Ok(Command::Eval(Term::Abs { term: Term::App { fun: Term::App { arg: Term::Var { index: 1 }, .. }, .. }, .. })) => (),
+        | command => panic!("Expected abstraction, but found {:?}", command),
+        }
+
+        assert_eq!(format!("{:#}", commands[6].as_ref().unwrap_err()), "Command on line 12: Unbound variable: y");
+    }
+}
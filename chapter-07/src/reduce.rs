@@ -0,0 +1,442 @@
+//! Full β-reduction, under which any redex may be contracted, as opposed to
+//! the call-by-value strategy implemented by `Term::step`.
+
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::collections::VecDeque;
+use std::io;
+
+use tapl_core::DeBruijn;
+use typed_arena::Arena;
+
+use crate::term::Alpha;
+use crate::term::Context;
+use crate::term::Term;
+
+/// Direction from a term to one of its immediate subterms.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Child {
+    /// Body of an abstraction
+    Body,
+    /// Function of an application
+    Fun,
+    /// Argument of an application
+    Arg,
+}
+
+/// Path from the root of a term to one of its subterms.
+pub type Position = Vec<Child>;
+
+impl<'a> Term<'a> {
+    /// Positions of every β-redex `(λx. t₁₂) t₂` in this term, outermost and
+    /// leftmost first.
+    pub fn redexes(&self) -> Vec<Position> {
+        let mut redexes = Vec::new();
+        self._redexes(&mut Vec::new(), &mut redexes);
+        redexes
+    }
+
+    fn _redexes(&self, position: &mut Position, redexes: &mut Vec<Position>) {
+        match self {
+        | Term::Var { .. } => (),
+        | Term::Abs { term, .. } => {
+            position.push(Child::Body);
+            term._redexes(position, redexes);
+            position.pop();
+        }
+        | Term::App { fun, arg } => {
+            if let Term::Abs { .. } = fun {
+                redexes.push(position.clone());
+            }
+            position.push(Child::Fun);
+            fun._redexes(position, redexes);
+            position.pop();
+            position.push(Child::Arg);
+            arg._redexes(position, redexes);
+            position.pop();
+        }
+        }
+    }
+
+    /// Position of the β-redex contracted by the call-by-value `step`, or
+    /// `None` if this term doesn't step.
+    pub fn redex(&self) -> Option<Position> {
+        let (child, term) = match self {
+        | Term::App { fun: Term::Abs { .. }, arg } if arg.is_value() => return Some(Vec::new()),
+        | Term::App { fun, arg } if fun.is_value() => (Child::Arg, arg),
+        | Term::App { fun, .. } => (Child::Fun, fun),
+        | _ => return None,
+        };
+        let mut position = term.redex()?;
+        position.insert(0, child);
+        Some(position)
+    }
+
+    /// Contracts the β-redex at `position`, or returns `None` if there is no
+    /// redex there.
+    ///
+    /// ```text
+    ///
+    /// ---------------------------- (β)
+    /// (λx. t₁₂) t₂ -> [x ↦ t₂] t₁₂
+    /// ```
+    pub fn contract(&self, arena: &'a Arena<Term<'a>>, position: &[Child]) -> Option<Self> {
+        match (self, position) {
+        | (Term::App { fun: Term::Abs { term, .. }, arg }, []) => {
+            Some(term.substitute_top(arena, arg))
+        }
+        | (Term::Abs { hint, term }, [Child::Body, rest @ ..]) => {
+            Some(Term::Abs {
+                hint: hint.clone(),
+                term: arena.alloc(term.contract(arena, rest)?),
+            })
+        }
+        | (Term::App { fun, arg }, [Child::Fun, rest @ ..]) => {
+            Some(Term::App {
+                fun: arena.alloc(fun.contract(arena, rest)?),
+                arg,
+            })
+        }
+        | (Term::App { fun, arg }, [Child::Arg, rest @ ..]) => {
+            Some(Term::App {
+                fun,
+                arg: arena.alloc(arg.contract(arena, rest)?),
+            })
+        }
+        | _ => None,
+        }
+    }
+}
+
+/// A single β-step in a reduction graph.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Edge {
+    pub from: usize,
+    pub to: usize,
+    /// Position of the contracted redex in the source term
+    pub position: Position,
+}
+
+/// Outcome of checking a (possibly bounded) reduction graph for confluence.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Confluence {
+    /// Every peak in the graph is joinable.
+    Confluent,
+
+    /// Nodes `left` and `right` both reduce from `peak`, but have no common
+    /// reduct, and the graph below them has been explored completely.
+    Diverges {
+        peak: usize,
+        left: usize,
+        right: usize,
+    },
+
+    /// No counterexample was found, but the bound was reached before every
+    /// peak could be checked.
+    Unknown,
+}
+
+/// The graph of terms reachable from some term under full β-reduction, with
+/// nodes identified up to α-equivalence.
+#[derive(Clone, Debug)]
+pub struct Graph<'a> {
+    nodes: Vec<&'a Term<'a>>,
+    edges: Vec<Edge>,
+    /// Whether every outgoing edge of each node has been explored
+    expanded: Vec<bool>,
+}
+
+impl<'a> Graph<'a> {
+    /// Explores the terms reachable from `term` breadth-first, without
+    /// discovering more than `bound` distinct terms. Node `0` is always `term`.
+    pub fn explore(arena: &'a Arena<Term<'a>>, term: &'a Term<'a>, bound: usize) -> Self {
+        let mut graph = Graph {
+            nodes: vec![term],
+            edges: Vec::new(),
+            expanded: vec![false],
+        };
+
+        let mut index = HashMap::new();
+        let mut queue = VecDeque::new();
+        index.insert(Alpha(term), 0);
+        queue.push_back(0);
+
+        while let Some(from) = queue.pop_front() {
+            let reducts = graph.nodes[from]
+                .redexes()
+                .into_iter()
+                .map(|position| {
+                    let next = graph.nodes[from]
+                        .contract(arena, &position)
+                        .expect("[INTERNAL ERROR]: redex position should be contractible");
+                    (position, &*arena.alloc(next))
+                })
+                .collect::<Vec<_>>();
+
+            let fresh = reducts
+                .iter()
+                .map(|(_, next)| Alpha(next))
+                .filter(|next| !index.contains_key(next))
+                .collect::<HashSet<_>>();
+
+            // Leave this node unexpanded rather than record only some of its edges.
+            if graph.nodes.len() + fresh.len() > bound {
+                continue;
+            }
+
+            for (position, next) in reducts {
+                let to = *index.entry(Alpha(next)).or_insert_with(|| {
+                    graph.nodes.push(next);
+                    graph.expanded.push(false);
+                    queue.push_back(graph.nodes.len() - 1);
+                    graph.nodes.len() - 1
+                });
+                graph.edges.push(Edge { from, to, position });
+            }
+
+            graph.expanded[from] = true;
+        }
+
+        graph
+    }
+
+    pub fn nodes(&self) -> &[&'a Term<'a>] {
+        &self.nodes
+    }
+
+    pub fn edges(&self) -> &[Edge] {
+        &self.edges
+    }
+
+    /// Whether every term reachable from the root has been explored.
+    pub fn is_complete(&self) -> bool {
+        self.expanded.iter().all(|expanded| *expanded)
+    }
+
+    /// Nodes known to be in normal form.
+    pub fn normal_forms(&self) -> Vec<usize> {
+        (0..self.nodes.len())
+            .filter(|&node| self.expanded[node])
+            .filter(|&node| self.edges.iter().all(|edge| edge.from != node))
+            .collect()
+    }
+
+    /// Local confluence - Every one-step peak `t₁ <- t -> t₂` is
+    /// joinable, i.e. `t₁ ->* t₃ <-* t₂` for some `t₃`.
+    pub fn locally_confluent(&self) -> Confluence {
+        let successors = self.successors();
+        let peaks = (0..self.nodes.len()).map(|peak| (peak, successors[peak].clone()));
+        self.check(peaks, &successors)
+    }
+
+    /// Confluence, i.e. the Church–Rosser property - Every many-step peak
+    /// `t₁ <-* t ->* t₂` is joinable, i.e. `t₁ ->* t₃ <-* t₂` for some `t₃`.
+    pub fn confluent(&self) -> Confluence {
+        let successors = self.successors();
+        let peaks = (0..self.nodes.len()).map(|peak| {
+            let reachable = self.reachable(&successors, peak);
+            (peak, (0..self.nodes.len()).filter(|&node| reachable[node]).collect())
+        });
+        self.check(peaks, &successors)
+    }
+
+    fn check<I>(&self, peaks: I, successors: &[Vec<usize>]) -> Confluence
+    where
+        I: Iterator<Item = (usize, Vec<usize>)>,
+    {
+        let reachable = (0..self.nodes.len())
+            .map(|node| self.reachable(successors, node))
+            .collect::<Vec<_>>();
+
+        for (peak, reducts) in peaks {
+            for (i, &left) in reducts.iter().enumerate() {
+                for &right in &reducts[i + 1..] {
+                    let joinable = (0..self.nodes.len())
+                        .any(|node| reachable[left][node] && reachable[right][node]);
+
+                    let explored = (0..self.nodes.len())
+                        .filter(|&node| reachable[left][node] || reachable[right][node])
+                        .all(|node| self.expanded[node]);
+
+                    if !joinable && explored {
+                        return Confluence::Diverges { peak, left, right };
+                    }
+                }
+            }
+        }
+
+        if self.is_complete() {
+            Confluence::Confluent
+        } else {
+            Confluence::Unknown
+        }
+    }
+
+    fn successors(&self) -> Vec<Vec<usize>> {
+        let mut successors = vec![Vec::new(); self.nodes.len()];
+        for edge in &self.edges {
+            if !successors[edge.from].contains(&edge.to) {
+                successors[edge.from].push(edge.to);
+            }
+        }
+        successors
+    }
+
+    fn reachable(&self, successors: &[Vec<usize>], from: usize) -> Vec<bool> {
+        let mut reachable = vec![false; self.nodes.len()];
+        let mut stack = vec![from];
+        reachable[from] = true;
+        while let Some(node) = stack.pop() {
+            for &next in &successors[node] {
+                if !reachable[next] {
+                    reachable[next] = true;
+                    stack.push(next);
+                }
+            }
+        }
+        reachable
+    }
+
+    /// Writes this graph in the Graphviz DOT language. Normal forms are drawn
+    /// with a double border, and unexplored nodes with a dashed one.
+    pub fn write_dot<W: io::Write>(&self, writer: &mut W) -> anyhow::Result<()> {
+        let normal_forms = self.normal_forms();
+
+        writeln!(writer, "digraph reduction {{")?;
+
+        for (node, term) in self.nodes.iter().enumerate() {
+            let mut label = Vec::new();
+            term.write(&mut Context::default(), &mut label)?;
+            let label = String::from_utf8(label)?
+                .replace('\\', "\\\\")
+                .replace('"', "\\\"");
+
+            let style = if !self.expanded[node] {
+                ", style=dashed"
+            } else if normal_forms.contains(&node) {
+                ", peripheries=2"
+            } else {
+                ""
+            };
+
+            writeln!(writer, "    {} [label=\"{}\"{}];", node, label, style)?;
+        }
+
+        for edge in &self.edges {
+            let position = edge.position
+                .iter()
+                .map(|child| match child {
+                    | Child::Body => "body",
+                    | Child::Fun => "fun",
+                    | Child::Arg => "arg",
+                })
+                .collect::<Vec<_>>()
+                .join(".");
+
+            let position = if position.is_empty() { "ε" } else { &position };
+            writeln!(writer, "    {} -> {} [label=\"{}\"];", edge.from, edge.to, position)?;
+        }
+
+        writeln!(writer, "}}")?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    //! Note: as with chapter 3, these tests only check properties for closed
+    //! terms of bounded size, and reduction graphs of bounded size.
+
+    use typed_arena::Arena;
+
+    use crate::reduce::Confluence;
+    use crate::reduce::Graph;
+    use crate::term::Alpha;
+    use crate::term::Term;
+
+    /// Call-by-value evaluation only ever contracts a β-redex.
+    #[test]
+    fn step_contracts_redex() {
+        let arena = Arena::new();
+        for term in Term::generate(&arena, 9) {
+            if let Some(next) = term.step(&arena) {
+                assert!(term
+                    .redexes()
+                    .iter()
+                    .filter_map(|position| term.contract(&arena, position))
+                    .any(|reduct| Alpha(&reduct) == Alpha(&next)));
+            }
+        }
+    }
+
+    /// Call-by-value evaluation contracts the redex at `redex`.
+    #[test]
+    fn step_contracts_at_redex() {
+        let arena = Arena::new();
+        for term in Term::generate(&arena, 9) {
+            let reduct = term
+                .redex()
+                .and_then(|position| term.contract(&arena, &position));
+            assert_eq!(reduct, term.step(&arena));
+        }
+    }
+
+    /// Every redex position reported is contractible.
+    #[test]
+    fn redexes_contract() {
+        let arena = Arena::new();
+        for term in Term::generate(&arena, 9) {
+            for position in term.redexes() {
+                assert!(term.contract(&arena, &position).is_some());
+            }
+        }
+    }
+
+    #[test]
+    fn locally_confluent() {
+        let arena = Arena::new();
+        for term in Term::generate(&arena, 9) {
+            let graph = Graph::explore(&arena, arena.alloc(term), 32);
+            assert!(!matches!(graph.locally_confluent(), Confluence::Diverges { .. }));
+        }
+    }
+
+    /// The Church–Rosser property holds for full β-reduction.
+    #[test]
+    fn confluent() {
+        let arena = Arena::new();
+        for term in Term::generate(&arena, 9) {
+            let graph = Graph::explore(&arena, arena.alloc(term), 32);
+            assert!(!matches!(graph.confluent(), Confluence::Diverges { .. }));
+        }
+    }
+
+    /// `(λx. x) ((λy. y) (λz. z))` reduces to `λz. z` along two paths.
+    #[test]
+    fn diamond() {
+        let arena = Arena::new();
+        let id = |hint: &str| &*arena.alloc(Term::Abs {
+            hint: String::from(hint),
+            term: arena.alloc(Term::Var { index: 0 }),
+        });
+        let term = arena.alloc(Term::App {
+            fun: id("x"),
+            arg: arena.alloc(Term::App {
+                fun: id("y"),
+                arg: id("z"),
+            }),
+        });
+
+        let graph = Graph::explore(&arena, term, 32);
+        assert_eq!(term.redexes().len(), 2);
+        assert_eq!(graph.nodes().len(), 3);
+        assert_eq!(graph.edges().len(), 3);
+        assert_eq!(graph.normal_forms().len(), 1);
+        assert_eq!(graph.confluent(), Confluence::Confluent);
+
+        let mut dot = Vec::new();
+        graph.write_dot(&mut dot).unwrap();
+        assert!(String::from_utf8(dot).unwrap().starts_with("digraph reduction {"));
+    }
+}
@@ -0,0 +1,73 @@
+use anyhow::anyhow;
+use tapl_core::sexp;
+use tapl_core::Sexp;
+use typed_arena::Arena;
+
+use crate::term::Term;
+
+impl<'a> Term<'a> {
+    /// Writes this term as an S-expression.
+    ///
+    /// ```text
+    /// t ::= (var i) | (abs x t) | (app t t)
+    /// ```
+    pub fn to_sexp(&self) -> Sexp {
+        match self {
+        | Term::Var { index } => Sexp::form("var", vec![Sexp::atom(index.to_string())]),
+        | Term::Abs { hint, term } => Sexp::form("abs", vec![Sexp::atom(hint.as_str()), term.to_sexp()]),
+        | Term::App { fun, arg } => Sexp::form("app", vec![fun.to_sexp(), arg.to_sexp()]),
+        }
+    }
+
+    /// Reads a term written by `to_sexp`, allocating it in `arena`.
+    pub fn from_sexp(arena: &'a Arena<Term<'a>>, sexp: &Sexp) -> anyhow::Result<Self> {
+        let subterm = |sexp: &Sexp| -> anyhow::Result<&'a Term<'a>> {
+            Ok(arena.alloc(Self::from_sexp(arena, sexp)?))
+        };
+        match sexp.as_form()? {
+        | ("var", tail) => {
+            let [index] = sexp::arguments("var", tail)?;
+            Ok(Term::Var { index: index.parse_index()? })
+        }
+        | ("abs", tail) => {
+            let [hint, term] = sexp::arguments("abs", tail)?;
+            Ok(Term::Abs {
+                hint: hint.as_atom()?.to_owned(),
+                term: subterm(term)?,
+            })
+        }
+        | ("app", tail) => {
+            let [fun, arg] = sexp::arguments("app", tail)?;
+            Ok(Term::App {
+                fun: subterm(fun)?,
+                arg: subterm(arg)?,
+            })
+        }
+        | (head, _) => Err(anyhow!("Unknown term form: {}", head)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use tapl_core::Sexp;
+    use typed_arena::Arena;
+
+    use crate::term::Term;
+
+    /// Writing and reading a term gives back the same term.
+    #[test]
+    fn round_trip() {
+        let arena = Arena::new();
+        for term in Term::generate(&arena, 7) {
+            let sexp = term.to_sexp().to_string().parse::<Sexp>().unwrap();
+            assert_eq!(Term::from_sexp(&arena, &sexp).unwrap(), term);
+        }
+        let sexp = "(app (abs x (var 0)) (abs y (var 0)))".parse::<Sexp>().unwrap();
+        assert_eq!(Term::from_sexp(&arena, &sexp).unwrap().to_sexp(), sexp);
+        assert!(Term::from_sexp(&arena, &"(var x)".parse().unwrap()).is_err());
+        assert!(Term::from_sexp(&arena, &"(var -1)".parse().unwrap()).is_err());
+        assert!(Term::from_sexp(&arena, &"(app (var 0))".parse().unwrap()).is_err());
+    }
+}
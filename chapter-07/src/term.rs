@@ -1,72 +1,213 @@
+use std::hash::Hash;
+use std::hash::Hasher;
 use std::io;
 use std::iter;
+use std::mem;
+use std::ptr;
 
+use serde::Serialize;
+use tapl_core::Alloc;
+use tapl_core::DeBruijn;
+use tapl_core::intern::HashCons;
 use typed_arena::Arena;
 
-#[derive(Clone, Debug, Default)]
-pub struct Context(Vec<String>);
+pub use tapl_core::Context;
 
-impl Context {
-    pub fn len(&self) -> i64 {
-        self.0.len() as i64
-    }
-
-    pub fn push<'c>(&'c mut self, mut var: String) -> &str {
-        while let Some(_) = self.0.iter().find(|entry| **entry == var) {
-            var.push('\'');
-        }
-        self.0.push(var);
-        self.0.last().unwrap()
-    }
-
-    pub fn pop(&mut self) {
-        self.0.pop();
-    }
-
-    pub fn name(&self, index: i64) -> &str {
-        assert!(index >= 0);
-        if index >= self.len() {
-            match index - self.len() {
-            | 0 => "α",
-            | 1 => "β",
-            | 2 => "ξ",
-            | _ => todo!(),
-            }
-        } else {
-            let index = self.0.len() - 1 - index as usize;
-            &self.0[index]
-        }
-    }
-}
-
-#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize)]
 pub enum Term<'a> {
+    /// Serialized as `{"Var": {"index": i}}`.
     Var {
         /// de Bruijn index
         index: i64,
     },
+    /// Serialized as `{"Abs": {"hint": x, "term": t}}`.
     Abs {
         /// Hint for the name of the bound variable
         hint: String,
         term: &'a Term<'a>,
     },
+    /// Serialized as `{"App": {"fun": t₁, "arg": t₂}}`.
     App {
         fun: &'a Term<'a>,
         arg: &'a Term<'a>,
     },
 }
 
+/// Compares terms up to α-equivalence, i.e. ignoring the names hinted at
+/// binders. Since variables are de Bruijn indices, two terms are α-equivalent
+/// exactly when they agree everywhere except in their hints.
+#[derive(Copy, Clone, Debug)]
+pub struct Alpha<'a>(pub &'a Term<'a>);
+
+impl<'a> PartialEq for Alpha<'a> {
+    fn eq(&self, other: &Self) -> bool {
+        match (self.0, other.0) {
+        | (Term::Var { index: l }, Term::Var { index: r }) => l == r,
+        | (Term::Abs { term: l, .. }, Term::Abs { term: r, .. }) => Alpha(l) == Alpha(r),
+        | (Term::App { fun: lf, arg: la }, Term::App { fun: rf, arg: ra }) => {
+            Alpha(lf) == Alpha(rf) && Alpha(la) == Alpha(ra)
+        }
+        | _ => false,
+        }
+    }
+}
+
+impl<'a> Eq for Alpha<'a> {}
+
+impl<'a> Hash for Alpha<'a> {
+    fn hash<H: Hasher>(&self, hasher: &mut H) {
+        match self.0 {
+        | Term::Var { index } => {
+            0u8.hash(hasher);
+            index.hash(hasher);
+        }
+        | Term::Abs { term, .. } => {
+            1u8.hash(hasher);
+            Alpha(term).hash(hasher);
+        }
+        | Term::App { fun, arg } => {
+            2u8.hash(hasher);
+            Alpha(fun).hash(hasher);
+            Alpha(arg).hash(hasher);
+        }
+        }
+    }
+}
+
+impl<'a> DeBruijn<'a> for Term<'a> {
+    fn var(index: i64) -> Self {
+        Term::Var { index }
+    }
+
+    fn index(&self) -> Option<i64> {
+        match self {
+        | Term::Var { index } => Some(*index),
+        | _ => None,
+        }
+    }
+
+    fn map<A, F>(&self, arena: &'a A, mut map: F) -> Option<Self>
+    where
+        A: Alloc<'a, Self>,
+        F: FnMut(&'a Self, i64) -> Option<Self>,
+    {
+        match self {
+        | Term::Var { .. } => None,
+        | Term::Abs { hint, term } => {
+            Some(Term::Abs {
+                hint: hint.to_owned(),
+                term: arena.alloc(map(term, 1)?),
+            })
+        }
+        | Term::App { fun, arg } => {
+            match (map(fun, 0), map(arg, 0)) {
+            | (None, None) => None,
+            | (fun_, arg_) => {
+                Some(Term::App {
+                    fun: fun_.map_or(*fun, |fun| arena.alloc(fun)),
+                    arg: arg_.map_or(*arg, |arg| arena.alloc(arg)),
+                })
+            }
+            }
+        }
+        }
+    }
+
+    fn fold<B, F>(&self, init: B, mut fold: F) -> B
+    where
+        F: FnMut(B, &'a Self, i64) -> B,
+    {
+        match self {
+        | Term::Var { .. } => init,
+        | Term::Abs { term, .. } => fold(init, term, 1),
+        | Term::App { fun, arg } => {
+            let init = fold(init, fun, 0);
+            fold(init, arg, 0)
+        }
+        }
+    }
+}
+
+impl<'a> HashCons for Term<'a> {
+    fn shallow_eq(&self, other: &Self) -> bool {
+        match (self, other) {
+        | (Term::Var { index: l }, Term::Var { index: r }) => l == r,
+        | (Term::Abs { hint: lh, term: l }, Term::Abs { hint: rh, term: r }) => lh == rh && ptr::eq(*l, *r),
+        | (Term::App { fun: lf, arg: la }, Term::App { fun: rf, arg: ra }) => {
+            ptr::eq(*lf, *rf) && ptr::eq(*la, *ra)
+        }
+        | _ => false,
+        }
+    }
+
+    fn shallow_hash<H: Hasher>(&self, hasher: &mut H) {
+        mem::discriminant(self).hash(hasher);
+        match self {
+        | Term::Var { index } => index.hash(hasher),
+        | Term::Abs { hint, term } => {
+            hint.hash(hasher);
+            ptr::hash(*term, hasher);
+        }
+        | Term::App { fun, arg } => {
+            ptr::hash(*fun, hasher);
+            ptr::hash(*arg, hasher);
+        }
+        }
+    }
+}
+
 impl<'a> Term<'a> {
-    pub fn eval(&'a self, arena: &'a Arena<Term<'a>>) -> &'a Self {
+    /// Every closed term with at most `size` nodes in its abstract syntax tree,
+    /// with every binder hinted as `x`.
+    pub fn generate(arena: &'a Arena<Term<'a>>, size: usize) -> Vec<Self> {
+        (1..=size)
+            .flat_map(|size| Self::_generate(arena, size, 0))
+            .collect()
+    }
+
+    /// Every term with exactly `size` nodes whose free variables are bound by
+    /// the `depth` enclosing binders.
+    fn _generate(arena: &'a Arena<Term<'a>>, size: usize, depth: i64) -> Vec<Self> {
+        match size {
+        | 0 => Vec::new(),
+        | 1 => (0..depth).map(|index| Term::Var { index }).collect(),
+        | _ => {
+            let mut terms = Self::_generate(arena, size - 1, depth + 1)
+                .into_iter()
+                .map(|term| Term::Abs {
+                    hint: String::from("x"),
+                    term: arena.alloc(term),
+                })
+                .collect::<Vec<_>>();
+
+            for fun_size in 1..size - 1 {
+                let funs = Self::_generate(arena, fun_size, depth);
+                let args = Self::_generate(arena, size - 1 - fun_size, depth);
+                for fun in &funs {
+                    for arg in &args {
+                        terms.push(Term::App {
+                            fun: arena.alloc(fun.clone()),
+                            arg: arena.alloc(arg.clone()),
+                        });
+                    }
+                }
+            }
+
+            terms
+        }
+        }
+    }
+
+    pub fn eval<A: Alloc<'a, Term<'a>>>(&'a self, arena: &'a A) -> &'a Self {
         iter::successors(Some(self), |term| {
                 term.step(arena)
-                    .map(|next| &*arena.alloc(next))
+                    .map(|next| arena.alloc(next))
             })
             .last()
             .unwrap_or(self)
     }
 
-    pub fn step(&self, arena: &'a Arena<Term<'a>>) -> Option<Self> {
+    pub fn step<A: Alloc<'a, Term<'a>>>(&self, arena: &'a A) -> Option<Self> {
         match self {
         | Term::App { fun: Term::Abs { term, .. }, arg } if arg.is_value() => {
             Some(term.substitute_top(arena, arg))
@@ -95,82 +236,130 @@ impl<'a> Term<'a> {
         }
     }
 
-    fn substitute_top(&self, arena: &'a Arena<Term<'a>>, to: &'a Term<'a>) -> Self {
-        self.substitute(arena, 0, arena.alloc(to.shift(arena, 1))).shift(arena, -1)
-    }
-
-    fn substitute(&self, arena: &'a Arena<Term<'a>>, from: i64, to: &'a Term<'a>) -> Self {
-        self._substitute(arena, from, to, 0)
-    }
-
-    fn _substitute(
-        &self,
-        arena: &'a Arena<Term<'a>>,
-        from: i64,
-        to: &'a Term<'a>,
-        depth: i64,
-    ) -> Self {
+    pub fn write<W: io::Write>(&self, context: &mut Context, writer: &mut W) -> anyhow::Result<()> {
         match self {
-        | Term::Var { index } if *index == from + depth => to.shift(arena, depth),
-        | Term::Var { index } => Term::Var { index: *index },
+        | Term::Var { index } => {
+            write!(writer, "{}", context.name(*index))?;
+        }
         | Term::Abs { hint, term } => {
-            Term::Abs {
-                hint: hint.to_owned(),
-                term: arena.alloc(term._substitute(arena, from, to, depth + 1)),
-            }
+            context.bind(hint, |context, name| -> anyhow::Result<()> {
+                write!(writer, "(λ{}. ", name)?;
+                term.write(context, writer)?;
+                write!(writer, ")")?;
+                Ok(())
+            })?;
         }
         | Term::App { fun, arg } => {
-            Term::App {
-                fun: arena.alloc(fun._substitute(arena, from, to, depth)),
-                arg: arena.alloc(arg._substitute(arena, from, to, depth)),
-            }
+            write!(writer, "(")?;
+            fun.write(context, writer)?;
+            write!(writer, " ")?;
+            arg.write(context, writer)?;
+            write!(writer, ")")?;
         }
         }
+        Ok(())
     }
+}
 
-    fn shift(&self, arena: &'a Arena<Term<'a>>, max_depth: i64) -> Self {
-        self._shift(arena, max_depth, 0)
-    }
+#[cfg(test)]
+mod tests {
 
-    fn _shift(&self, arena: &'a Arena<Term<'a>>, max_depth: i64, depth: i64) -> Self {
-        match self {
-        | Term::Var { index } if *index >= depth => Term::Var { index: index + max_depth },
-        | Term::Var { index } => Term::Var { index: *index },
-        | Term::Abs { hint, term } => {
-            Term::Abs {
-                hint: hint.to_owned(),
-                term: arena.alloc(term._shift(arena, max_depth, depth + 1)),
+    use std::collections::HashMap;
+    use std::iter;
+
+    use tapl_core::intern::Id;
+    use tapl_core::DeBruijn;
+    use tapl_core::Interner;
+    use typed_arena::Arena;
+
+    use crate::term::Term;
+
+    /// The fused substitution agrees with shifting, substituting, and shifting
+    /// back, including on open terms.
+    #[test]
+    fn substitute_top_fused() {
+        let arena = Arena::new();
+        let bodies = (1..=6).flat_map(|size| Term::_generate(&arena, size, 2));
+        let tos = (1..=4)
+            .flat_map(|size| Term::_generate(&arena, size, 1))
+            .map(|to| &*arena.alloc(to))
+            .collect::<Vec<_>>();
+
+        for body in bodies {
+            for to in &tos {
+                let unfused = body
+                    .substitute(&arena, 0, arena.alloc(to.shift(&arena, 1)))
+                    .shift(&arena, -1);
+                assert_eq!(body.substitute_top(&arena, to), unfused);
             }
         }
-        | Term::App { fun, arg } => {
-            Term::App {
-                fun: arena.alloc(fun._shift(arena, max_depth, depth)),
-                arg: arena.alloc(arg._shift(arena, max_depth, depth)),
-            }
+    }
+
+    /// Closed terms have no free variables, and shifting or substituting into
+    /// a term moves its free variables accordingly.
+    #[test]
+    fn free() {
+        let arena = Arena::new();
+        for term in Term::generate(&arena, 6) {
+            assert!(term.is_closed());
         }
+        for term in (1..=6).flat_map(|size| Term::_generate(&arena, size, 2)) {
+            let free = term.free();
+            assert!(free.iter().all(|index| *index < 2));
+            assert_eq!(term.shift(&arena, 3).free(), free.iter().map(|index| index + 3).collect());
+            if free.contains(&0) {
+                let to = arena.alloc(Term::Var { index: 5 });
+                assert!(term.substitute_top(&arena, to).free().contains(&5));
+            }
         }
     }
 
-    pub fn write<W: io::Write>(&self, context: &mut Context, writer: &mut W) -> anyhow::Result<()> {
-        match self {
-        | Term::Var { index } => {
-            write!(writer, "{}", context.name(*index))?;
-        }
-        | Term::Abs { hint, term } => {
-            let name = context.push(hint.to_owned());
-            write!(writer, "(λ{}. ", name)?;
-            term.write(context, writer)?;
-            write!(writer, ")")?;
-            context.pop();
+    /// Interned terms are the same allocation exactly when they are equal.
+    #[test]
+    fn intern() {
+        let arena = Arena::new();
+        let interner = Interner::default();
+        let terms = Term::generate(&arena, 6);
+        let ids = terms
+            .iter()
+            .map(|term| Id(interner.intern(term)))
+            .collect::<Vec<_>>();
+
+        for (l, l_id) in terms.iter().zip(&ids) {
+            assert_eq!(Id(interner.intern(l)), *l_id);
+            for (r, r_id) in terms.iter().zip(&ids) {
+                assert_eq!(l == r, l_id == r_id);
+            }
         }
-        | Term::App { fun, arg } => {
-            write!(writer, "(")?;
-            fun.write(context, writer)?;
-            write!(writer, " ")?;
-            arg.write(context, writer)?;
-            write!(writer, ")")?;
+
+        let counts = ids.iter().fold(HashMap::new(), |mut counts, id| {
+            *counts.entry(*id).or_insert(0) += 1;
+            counts
+        });
+        assert_eq!(counts.len(), terms.len());
+
+        // Subterms shared between terms are only allocated once.
+        fn size(term: &Term) -> usize {
+            term.fold(1, |sum, term, _| sum + size(term))
         }
+        assert!(interner.len() < terms.iter().map(size).sum());
+    }
+
+    /// Evaluating in an interner gives the same results as in an arena.
+    #[test]
+    fn eval_interned() {
+        let arena = Arena::new();
+        let interner = Interner::default();
+        for term in Term::generate(&arena, 9) {
+            let terminates = iter::successors(Some(term.clone()), |term| term.step(&arena))
+                .nth(64)
+                .is_none();
+            if terminates {
+                let expected = arena.alloc(term.clone()).eval(&arena);
+                let actual = interner.intern(&term).eval(&interner);
+                assert_eq!(actual, expected);
+                assert_eq!(Id(actual), Id(interner.intern(expected)));
+            }
         }
-        Ok(())
     }
 }
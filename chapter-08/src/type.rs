@@ -0,0 +1,182 @@
+use std::fmt;
+
+use anyhow::anyhow;
+
+use chapter_03::term::T;
+
+/// The types of arithmetic expressions.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Ty {
+    Bool,
+    Nat,
+}
+
+impl fmt::Display for Ty {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+        | Ty::Bool => write!(fmt, "Bool"),
+        | Ty::Nat => write!(fmt, "Nat"),
+        }
+    }
+}
+
+/// The typing relation `t : T`.
+pub fn check(term: &T) -> anyhow::Result<Ty> {
+    use T::*;
+    match term {
+    //
+    // ----------- (T-True)
+    // true : Bool
+    | True => Ok(Ty::Bool),
+
+    //
+    // ------------ (T-False)
+    // false : Bool
+    | False => Ok(Ty::Bool),
+
+    // t₁ : Bool    t₂ : T    t₃ : T
+    // ----------------------------- (T-If)
+    //   if t₁ then t₂ else t₃ : T
+    | IfElse(t_1, t_2, t_3) => {
+        if check(t_1)? != Ty::Bool {
+            return Err(anyhow!("Guard of conditional not a boolean"));
+        }
+        let t_2 = check(t_2)?;
+        let t_3 = check(t_3)?;
+        if t_2 != t_3 {
+            return Err(anyhow!("Arms of conditional have different types"));
+        }
+        Ok(t_2)
+    }
+
+    //
+    // ------- (T-Zero)
+    // 0 : Nat
+    | Zero => Ok(Ty::Nat),
+
+    //    t₁ : Nat
+    // ------------- (T-Succ)
+    // succ t₁ : Nat
+    | Succ(t_1) => expect(t_1, Ty::Nat, "succ").map(|_| Ty::Nat),
+
+    //    t₁ : Nat
+    // ------------- (T-Pred)
+    // pred t₁ : Nat
+    | Pred(t_1) => expect(t_1, Ty::Nat, "pred").map(|_| Ty::Nat),
+
+    //     t₁ : Nat
+    // ---------------- (T-IsZero)
+    // iszero t₁ : Bool
+    | IsZero(t_1) => expect(t_1, Ty::Nat, "iszero").map(|_| Ty::Bool),
+
+    // t₁ : Nat    t₂ : Nat
+    // -------------------- (T-Plus)
+    //   plus t₁ t₂ : Nat
+    | Plus(t_1, t_2) => {
+        expect(t_1, Ty::Nat, "plus")?;
+        expect(t_2, Ty::Nat, "plus")?;
+        Ok(Ty::Nat)
+    }
+
+    // t₁ : Nat    t₂ : Nat
+    // -------------------- (T-Times)
+    //  times t₁ t₂ : Nat
+    | Times(t_1, t_2) => {
+        expect(t_1, Ty::Nat, "times")?;
+        expect(t_2, Ty::Nat, "times")?;
+        Ok(Ty::Nat)
+    }
+
+    // t₁ : Nat    t₂ : Nat
+    // -------------------- (T-Eq)
+    //   eq t₁ t₂ : Bool
+    | Eq(t_1, t_2) => {
+        expect(t_1, Ty::Nat, "eq")?;
+        expect(t_2, Ty::Nat, "eq")?;
+        Ok(Ty::Bool)
+    }
+
+    // t₁ : Nat    t₂ : Nat
+    // -------------------- (T-Lt)
+    //   lt t₁ t₂ : Bool
+    | Lt(t_1, t_2) => {
+        expect(t_1, Ty::Nat, "lt")?;
+        expect(t_2, Ty::Nat, "lt")?;
+        Ok(Ty::Bool)
+    }
+
+    // Exercise 8.3.8 - wrong is not well typed.
+    | Wrong => Err(anyhow!("Term wrong has no type")),
+    }
+}
+
+fn expect(term: &T, expected: Ty, operator: &str) -> anyhow::Result<()> {
+    let actual = check(term)?;
+    if actual == expected {
+        Ok(())
+    } else {
+        Err(anyhow!("Argument of {} has type {}, but expected {}", operator, actual, expected))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    //! Note: as in chapter 3, these tests verify properties *only* for the set
+    //! of terms in S₃.
+
+    use typed_arena::Arena;
+
+    use chapter_03::term::T;
+
+    use crate::r#type::check;
+
+    /// Exercise 8.2.3 - Every subterm of a well-typed term is well typed.
+    #[test]
+    fn subterms_well_typed() {
+        use T::*;
+        let arena = Arena::new();
+        for term in T::generate(&arena, 3) {
+            if check(&term).is_err() {
+                continue;
+            }
+            match term {
+            | True | False | Zero | Wrong => (),
+            | Succ(t_1) | Pred(t_1) | IsZero(t_1) => assert!(check(t_1).is_ok()),
+            | Plus(t_1, t_2) | Times(t_1, t_2) | Eq(t_1, t_2) | Lt(t_1, t_2) => {
+                assert!(check(t_1).is_ok());
+                assert!(check(t_2).is_ok());
+            }
+            | IfElse(t_1, t_2, t_3) => {
+                assert!(check(t_1).is_ok());
+                assert!(check(t_2).is_ok());
+                assert!(check(t_3).is_ok());
+            }
+            }
+        }
+    }
+
+    /// Theorem 8.3.2 (Progress) - Suppose `t` is a well-typed term (i.e.
+    /// `t : T` for some `T`). Then either `t` is a value or else there is some
+    /// `t'` with `t -> t'`.
+    #[test]
+    fn progress() {
+        let arena = Arena::new();
+        for term in T::generate(&arena, 3) {
+            if check(&term).is_ok() {
+                assert!(term.is_value() || term.step(&arena).is_some());
+            }
+        }
+    }
+
+    /// Theorem 8.3.3 (Preservation) - If `t : T` and `t -> t'`, then `t' : T`.
+    #[test]
+    fn preservation() {
+        let arena = Arena::new();
+        for term in T::generate(&arena, 3) {
+            if let (Ok(r#type), Some(next)) = (check(&term), term.step(&arena)) {
+                assert_eq!(check(&next).unwrap(), r#type);
+            }
+        }
+    }
+}
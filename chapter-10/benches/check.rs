@@ -0,0 +1,62 @@
+use criterion::black_box;
+use criterion::criterion_group;
+use criterion::criterion_main;
+use criterion::BenchmarkId;
+use criterion::Criterion;
+use typed_arena::Arena;
+
+use chapter_10::r#type::Context;
+use chapter_10::r#type::Type;
+use chapter_10::term::Term;
+
+/// `λb: bool. if b then b else if b then b else ... else b`, with `depth`
+/// conditionals chained through their else branches.
+fn chain<'a>(arena: &'a Arena<Term<'a>>, depth: usize) -> &'a Term<'a> {
+    let var = &*arena.alloc(Term::Var { index: 0 });
+    let body = (0..depth).fold(Term::Var { index: 0 }, |r#else, _| Term::If {
+        r#if: var,
+        then: var,
+        r#else: arena.alloc(r#else),
+    });
+    arena.alloc(Term::Abs {
+        hint: String::from("b"),
+        r#type: Type::Bool,
+        term: arena.alloc(body),
+    })
+}
+
+/// `(λb: bool. if b then b else ... else b) false`, which takes every else
+/// branch of the chain before evaluating to `false`.
+fn applied_chain<'a>(arena: &'a Arena<Term<'a>>, depth: usize) -> &'a Term<'a> {
+    arena.alloc(Term::App {
+        fun: chain(arena, depth),
+        arg: arena.alloc(Term::Bool(false)),
+    })
+}
+
+fn if_chain(criterion: &mut Criterion) {
+    let mut group = criterion.benchmark_group("check_if_chain");
+
+    for depth in [10, 100, 1000] {
+        let input: &'static Arena<Term<'static>> = Box::leak(Box::new(Arena::new()));
+        let term = chain(input, depth);
+
+        group.bench_with_input(BenchmarkId::new("check", depth), &depth, |bencher, _| {
+            bencher.iter(|| black_box(term.check(&mut Context::default(), 0).unwrap()))
+        });
+
+        let applied = applied_chain(input, depth);
+        assert_eq!(applied.check(&mut Context::default(), 0).unwrap(), Type::Bool);
+        assert_eq!(applied.eval(input), &Term::Bool(false));
+
+        group.bench_with_input(BenchmarkId::new("eval_applied", depth), &depth, |bencher, _| {
+            let arena = Arena::new();
+            bencher.iter(|| black_box(applied.eval(&arena)))
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, if_chain);
+criterion_main!(benches);
@@ -0,0 +1,75 @@
+use anyhow::anyhow;
+use serde_json::Value;
+use tapl_core::json;
+use typed_arena::Arena;
+
+use crate::term::Term;
+
+impl<'a> Term<'a> {
+    /// Reads back a term serialized as JSON, allocating it in `arena`. See
+    /// the variants of `Term` and `Type` for the schema.
+    pub fn from_json(arena: &'a Arena<Term<'a>>, json: &str) -> anyhow::Result<Self> {
+        json::read(json, |value| Self::from_value(arena, value))
+    }
+
+    pub fn from_value(arena: &'a Arena<Term<'a>>, value: &Value) -> anyhow::Result<Self> {
+        let subterm = |value: &Value| -> anyhow::Result<&'a Term<'a>> {
+            Ok(arena.alloc(json::grow(|| Self::from_value(arena, value))?))
+        };
+        match json::variant(value)? {
+        | ("Bool", content) => Ok(Term::Bool(json::leaf(content)?)),
+        | ("If", content) => {
+            Ok(Term::If {
+                r#if: subterm(json::field(content, "if")?)?,
+                then: subterm(json::field(content, "then")?)?,
+                r#else: subterm(json::field(content, "else")?)?,
+            })
+        }
+        | ("Var", content) => {
+            Ok(Term::Var {
+                index: json::leaf(json::field(content, "index")?)?,
+            })
+        }
+        | ("Abs", content) => {
+            Ok(Term::Abs {
+                hint: json::leaf(json::field(content, "hint")?)?,
+                r#type: json::leaf(json::field(content, "type")?)?,
+                term: subterm(json::field(content, "term")?)?,
+            })
+        }
+        | ("App", content) => {
+            Ok(Term::App {
+                fun: subterm(json::field(content, "fun")?)?,
+                arg: subterm(json::field(content, "arg")?)?,
+            })
+        }
+        | (tag, _) => Err(anyhow!("Unknown term variant: {}", tag)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use typed_arena::Arena;
+
+    use crate::r#type::Type;
+    use crate::term::Term;
+
+    /// Serializing and deserializing a term gives back the same term,
+    /// including its hints and types.
+    #[test]
+    fn round_trip() {
+        let arena = Arena::new();
+        let json = r#"{"App":{"fun":{"Abs":{"hint":"f","type":{"Fun":["Bool","Bool"]},"term":{"If":{"if":{"Bool":true},"then":{"Var":{"index":0}},"else":{"Var":{"index":0}}}}}},"arg":{"Abs":{"hint":"x","type":"Bool","term":{"Var":{"index":0}}}}}}"#;
+        let term = Term::from_json(&arena, json).unwrap();
+        match &term {
+        | Term::App { fun: Term::Abs { r#type, .. }, .. } => {
+            assert_eq!(*r#type, Type::Fun(Box::new(Type::Bool), Box::new(Type::Bool)));
+        }
+        | _ => panic!("Expected application of abstraction"),
+        }
+        assert_eq!(serde_json::to_string(&term).unwrap(), json);
+        assert!(Term::from_json(&arena, r#"{"Abs":{"hint":"x","term":{"Bool":true}}}"#).is_err());
+    }
+}
@@ -1,2 +1,34 @@
+pub mod json;
+pub mod owned;
+pub mod sexp;
 pub mod term;
 pub mod r#type;
+
+#[doc(hidden)]
+pub use tapl_macros;
+
+/// Builds a `Term` from its concrete syntax, resolving variables to de Bruijn
+/// indices at compile time, and allocating its subterms in the given arena.
+///
+/// ```
+/// use typed_arena::Arena;
+/// use chapter_10::term::Term;
+///
+/// let arena = Arena::new();
+/// let term = chapter_10::term!(arena, (λf: bool -> bool. f false) (λb: bool. if b then false else true));
+/// let sexp = "(app (abs f (fun bool bool) (app (var 0) (bool false))) (abs b bool (if (var 0) (bool false) (bool true))))";
+/// assert_eq!(term, Term::from_sexp(&arena, &sexp.parse().unwrap()).unwrap());
+/// ```
+///
+/// Unbound variables are rejected at compile time:
+///
+/// ```compile_fail
+/// let arena = typed_arena::Arena::new();
+/// chapter_10::term!(arena, λx: bool. y);
+/// ```
+#[macro_export]
+macro_rules! term {
+    ($arena:expr, $($term:tt)*) => {
+        $crate::tapl_macros::term!(simple, $crate, $arena, $($term)*)
+    };
+}
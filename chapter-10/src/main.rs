@@ -1,42 +1,103 @@
+use std::env;
 use std::io;
+use std::io::Read as _;
 use std::io::Write as _;
 
+use anyhow::anyhow;
+use tapl_core::Sexp;
 use typed_arena::Arena;
 
 use chapter_10::term::Context;
 use chapter_10::term::Term;
 
+/// Evaluates example terms, printing every step.
+///
+/// With `--read-sexp`, the terms are instead read as S-expressions from
+/// standard input, and with `--write-sexp`, every step is printed as an
+/// S-expression.
 pub fn main() -> anyhow::Result<()> {
 
+    let mut read_sexp = false;
+    let mut write_sexp = false;
+
+    for flag in env::args().skip(1) {
+        match flag.as_str() {
+        | "--read-sexp" => read_sexp = true,
+        | "--write-sexp" => write_sexp = true,
+        | flag => return Err(anyhow!("Unknown flag: {}", flag)),
+        }
+    }
+
     let arena = Arena::new();
-    
-    let terms = vec![];
+
+    let terms = if read_sexp {
+        read(&arena)?
+    } else {
+        examples(&arena)?
+    };
 
     for term in terms {
-        step(term, &arena)?;
+        step(term, &arena, write_sexp)?;
     }
 
     Ok(())
 }
 
+fn examples<'a>(arena: &'a Arena<Term<'a>>) -> anyhow::Result<Vec<Term<'a>>> {
+    let sources = [
+        // (λx: bool. x) true -->* true
+        "(app (abs x bool (var 0)) (bool true))",
+
+        // (λf: bool -> bool. f false) (λb: bool. b) -->* false
+        "(app (abs f (fun bool bool) (app (var 0) (bool false))) (abs b bool (var 0)))",
+    ];
+    sources
+        .iter()
+        .map(|source| Term::from_sexp(arena, &source.parse()?))
+        .collect()
+}
+
+fn read<'a>(arena: &'a Arena<Term<'a>>) -> anyhow::Result<Vec<Term<'a>>> {
+    let mut source = String::new();
+    io::stdin().read_to_string(&mut source)?;
+    Sexp::parse_all(&source)?
+        .iter()
+        .map(|sexp| Term::from_sexp(arena, sexp))
+        .collect()
+}
+
 fn step<'a>(
     mut term: Term<'a>,
     arena: &'a Arena<Term<'a>>,
+    write_sexp: bool,
 ) -> anyhow::Result<()> {
 
     let stdout = io::stdout();
     let mut stdout = stdout.lock();
     let mut context = Context::default();
 
-    term.write(&mut context, &mut stdout)?;
-    write!(&mut stdout, "\n")?;
+    write(&term, &mut context, &mut stdout, write_sexp)?;
 
-    while let Some(next) = term.step(&arena) {
+    while let Some(next) = term.step(arena) {
         term = next;
-        term.write(&mut context, &mut stdout)?;
-        write!(&mut stdout, "\n")?;
+        write(&term, &mut context, &mut stdout, write_sexp)?;
     }
 
-    write!(&mut stdout, "\n")?;
+    writeln!(&mut stdout)?;
+    Ok(())
+}
+
+fn write<W: io::Write>(
+    term: &Term,
+    context: &mut Context,
+    writer: &mut W,
+    write_sexp: bool,
+) -> anyhow::Result<()> {
+    if write_sexp {
+        write!(writer, "{}", term.to_sexp())?;
+    } else {
+        term.write(context, writer)?;
+    }
+    writeln!(writer)?;
     Ok(())
 }
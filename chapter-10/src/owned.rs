@@ -0,0 +1,190 @@
+//! Terms that own their subterms, for embedding the interpreter in
+//! applications that cannot thread an arena's lifetime through their own
+//! types.
+//!
+//! As in chapter 7, each operation converts into a scratch arena, runs
+//! there, and converts the result back, preserving sharing both ways.
+
+use std::collections::HashMap;
+use std::io;
+use std::sync::Arc;
+
+use typed_arena::Arena;
+
+use crate::r#type;
+use crate::r#type::Type;
+use crate::term;
+use crate::term::Context;
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Term {
+    Bool(bool),
+    If {
+        r#if: Arc<Term>,
+        then: Arc<Term>,
+        r#else: Arc<Term>,
+    },
+    Var {
+        /// de Bruijn index
+        index: i64,
+    },
+    Abs {
+        /// Hint for the name of the bound variable
+        hint: String,
+        r#type: Type,
+        term: Arc<Term>,
+    },
+    App {
+        fun: Arc<Term>,
+        arg: Arc<Term>,
+    },
+}
+
+impl<'a> From<&term::Term<'a>> for Term {
+    fn from(term: &term::Term<'a>) -> Self {
+        Term::_from(term, &mut HashMap::new())
+    }
+}
+
+impl Term {
+    fn _from<'a>(term: &term::Term<'a>, cache: &mut HashMap<*const term::Term<'a>, Arc<Term>>) -> Self {
+        let mut share = |term: &'a term::Term<'a>| -> Arc<Term> {
+            if let Some(owned) = cache.get(&(term as *const _)) {
+                return Arc::clone(owned);
+            }
+            let owned = Arc::new(Term::_from(term, cache));
+            cache.insert(term, Arc::clone(&owned));
+            owned
+        };
+        match term {
+        | term::Term::Bool(bool) => Term::Bool(*bool),
+        | term::Term::If { r#if, then, r#else } => {
+            Term::If {
+                r#if: share(r#if),
+                then: share(then),
+                r#else: share(r#else),
+            }
+        }
+        | term::Term::Var { index } => Term::Var { index: *index },
+        | term::Term::Abs { hint, r#type, term } => {
+            Term::Abs {
+                hint: hint.clone(),
+                r#type: r#type.clone(),
+                term: share(term),
+            }
+        }
+        | term::Term::App { fun, arg } => {
+            Term::App {
+                fun: share(fun),
+                arg: share(arg),
+            }
+        }
+        }
+    }
+
+    /// Allocates this term and its subterms in `arena`.
+    pub fn to_arena<'a>(&self, arena: &'a Arena<term::Term<'a>>) -> &'a term::Term<'a> {
+        self._to_arena(arena, &mut HashMap::new())
+    }
+
+    fn _to_arena<'a>(&self, arena: &'a Arena<term::Term<'a>>, cache: &mut HashMap<*const Term, &'a term::Term<'a>>) -> &'a term::Term<'a> {
+        let mut share = |term: &Arc<Term>| -> &'a term::Term<'a> {
+            if let Some(term) = cache.get(&Arc::as_ptr(term)) {
+                return term;
+            }
+            let allocated = term._to_arena(arena, cache);
+            cache.insert(Arc::as_ptr(term), allocated);
+            allocated
+        };
+        let term = match self {
+        | Term::Bool(bool) => term::Term::Bool(*bool),
+        | Term::If { r#if, then, r#else } => {
+            term::Term::If {
+                r#if: share(r#if),
+                then: share(then),
+                r#else: share(r#else),
+            }
+        }
+        | Term::Var { index } => term::Term::Var { index: *index },
+        | Term::Abs { hint, r#type, term } => {
+            term::Term::Abs {
+                hint: hint.clone(),
+                r#type: r#type.clone(),
+                term: share(term),
+            }
+        }
+        | Term::App { fun, arg } => {
+            term::Term::App {
+                fun: share(fun),
+                arg: share(arg),
+            }
+        }
+        };
+        arena.alloc(term)
+    }
+
+    pub fn check(&self, context: &mut r#type::Context, depth: i64) -> anyhow::Result<Type> {
+        let arena = Arena::new();
+        self.to_arena(&arena).check(context, depth)
+    }
+
+    pub fn eval(&self) -> Self {
+        let arena = Arena::new();
+        Term::from(self.to_arena(&arena).eval(&arena))
+    }
+
+    pub fn is_value(&self) -> bool {
+        match self {
+        | Term::Bool(_)
+        | Term::Var { .. }
+        | Term::Abs { .. } => true,
+        | Term::If { .. }
+        | Term::App { .. } => false,
+        }
+    }
+
+    pub fn write<W: io::Write>(&self, context: &mut Context, writer: &mut W) -> anyhow::Result<()> {
+        let arena = Arena::new();
+        self.to_arena(&arena).write(context, writer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use std::thread;
+
+    use tapl_core::Sexp;
+    use typed_arena::Arena;
+
+    use crate::owned;
+    use crate::r#type;
+    use crate::r#type::Type;
+    use crate::term::Context;
+    use crate::term::Term;
+
+    #[test]
+    fn send_sync() {
+        fn assert<T: Send + Sync>() {}
+        assert::<owned::Term>();
+    }
+
+    /// The owned form round-trips, and checks and evaluates like the arena
+    /// form, including on another thread.
+    #[test]
+    fn round_trip() {
+        let arena = Arena::new();
+        let sexp = "(app (abs f (fun bool bool) (app (var 0) (bool false))) (abs b bool (var 0)))".parse::<Sexp>().unwrap();
+        let term = &*arena.alloc(Term::from_sexp(&arena, &sexp).unwrap());
+        let owned = owned::Term::from(term);
+        assert_eq!(owned.to_arena(&arena), term);
+        assert_eq!(owned.check(&mut r#type::Context::default(), 0).unwrap(), Type::Bool);
+
+        let value = thread::spawn(move || owned.eval()).join().unwrap();
+        assert_eq!(value, owned::Term::from(term.eval(&arena)));
+
+        let mut buffer = Vec::new();
+        value.write(&mut Context::default(), &mut buffer).unwrap();
+        assert_eq!(String::from_utf8(buffer).unwrap(), "false");
+    }
+}
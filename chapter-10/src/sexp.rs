@@ -0,0 +1,121 @@
+use anyhow::anyhow;
+use tapl_core::sexp;
+use tapl_core::Sexp;
+use typed_arena::Arena;
+
+use crate::r#type::Type;
+use crate::term::Term;
+
+impl Type {
+    /// Writes this type as an S-expression.
+    ///
+    /// ```text
+    /// T ::= bool | (fun T T)
+    /// ```
+    pub fn to_sexp(&self) -> Sexp {
+        match self {
+        | Type::Bool => Sexp::atom("bool"),
+        | Type::Fun(from, to) => Sexp::form("fun", vec![from.to_sexp(), to.to_sexp()]),
+        }
+    }
+
+    /// Reads a type written by `to_sexp`.
+    pub fn from_sexp(sexp: &Sexp) -> anyhow::Result<Self> {
+        if let Sexp::Atom(atom) = sexp {
+            return match atom.as_str() {
+            | "bool" => Ok(Type::Bool),
+            | _ => Err(anyhow!("Unknown type: {}", atom)),
+            };
+        }
+        match sexp.as_form()? {
+        | ("fun", tail) => {
+            let [from, to] = sexp::arguments("fun", tail)?;
+            Ok(Type::Fun(Box::new(Type::from_sexp(from)?), Box::new(Type::from_sexp(to)?)))
+        }
+        | (head, _) => Err(anyhow!("Unknown type form: {}", head)),
+        }
+    }
+}
+
+impl<'a> Term<'a> {
+    /// Writes this term as an S-expression.
+    ///
+    /// ```text
+    /// t ::= (bool true) | (bool false) | (if t t t)
+    ///     | (var i) | (abs x T t) | (app t t)
+    /// ```
+    pub fn to_sexp(&self) -> Sexp {
+        match self {
+        | Term::Bool(bool) => Sexp::form("bool", vec![Sexp::atom(bool.to_string())]),
+        | Term::If { r#if, then, r#else } => Sexp::form("if", vec![r#if.to_sexp(), then.to_sexp(), r#else.to_sexp()]),
+        | Term::Var { index } => Sexp::form("var", vec![Sexp::atom(index.to_string())]),
+        | Term::Abs { hint, r#type, term } => {
+            Sexp::form("abs", vec![Sexp::atom(hint.as_str()), r#type.to_sexp(), term.to_sexp()])
+        }
+        | Term::App { fun, arg } => Sexp::form("app", vec![fun.to_sexp(), arg.to_sexp()]),
+        }
+    }
+
+    /// Reads a term written by `to_sexp`, allocating it in `arena`.
+    pub fn from_sexp(arena: &'a Arena<Term<'a>>, sexp: &Sexp) -> anyhow::Result<Self> {
+        let subterm = |sexp: &Sexp| -> anyhow::Result<&'a Term<'a>> {
+            Ok(arena.alloc(Self::from_sexp(arena, sexp)?))
+        };
+        match sexp.as_form()? {
+        | ("bool", tail) => {
+            let [bool] = sexp::arguments("bool", tail)?;
+            Ok(Term::Bool(bool.parse_atom()?))
+        }
+        | ("if", tail) => {
+            let [r#if, then, r#else] = sexp::arguments("if", tail)?;
+            Ok(Term::If {
+                r#if: subterm(r#if)?,
+                then: subterm(then)?,
+                r#else: subterm(r#else)?,
+            })
+        }
+        | ("var", tail) => {
+            let [index] = sexp::arguments("var", tail)?;
+            Ok(Term::Var { index: index.parse_index()? })
+        }
+        | ("abs", tail) => {
+            let [hint, r#type, term] = sexp::arguments("abs", tail)?;
+            Ok(Term::Abs {
+                hint: hint.as_atom()?.to_owned(),
+                r#type: Type::from_sexp(r#type)?,
+                term: subterm(term)?,
+            })
+        }
+        | ("app", tail) => {
+            let [fun, arg] = sexp::arguments("app", tail)?;
+            Ok(Term::App {
+                fun: subterm(fun)?,
+                arg: subterm(arg)?,
+            })
+        }
+        | (head, _) => Err(anyhow!("Unknown term form: {}", head)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use tapl_core::Sexp;
+    use typed_arena::Arena;
+
+    use crate::term::Term;
+
+    /// Reading and writing a term gives back the same S-expression.
+    #[test]
+    fn round_trip() {
+        let arena = Arena::new();
+        let sexp = "(app (abs f (fun bool bool) (if (bool true) (var 0) (var 0))) (abs x bool (var 0)))"
+            .parse::<Sexp>()
+            .unwrap();
+        assert_eq!(Term::from_sexp(&arena, &sexp).unwrap().to_sexp(), sexp);
+        assert!(Term::from_sexp(&arena, &"(abs x nat (var 0))".parse().unwrap()).is_err());
+        assert!(Term::from_sexp(&arena, &"(bool yes)".parse().unwrap()).is_err());
+        assert!(Term::from_sexp(&arena, &"(var -1)".parse().unwrap()).is_err());
+    }
+}
@@ -1,70 +1,116 @@
 use std::io;
 use std::iter;
 
+use serde::Serialize;
+use tapl_core::Alloc;
+use tapl_core::DeBruijn;
 use typed_arena::Arena;
 
 use crate::r#type::Type;
 
-#[derive(Clone, Debug, Default)]
-pub struct Context(Vec<String>);
+pub use tapl_core::Context;
 
-impl Context {
-    pub fn len(&self) -> i64 {
-        self.0.len() as i64
-    }
-
-    pub fn push<'c>(&'c mut self, mut var: String) -> &str {
-        while let Some(_) = self.0.iter().find(|entry| **entry == var) {
-            var.push('\'');
-        }
-        self.0.push(var);
-        self.0.last().unwrap()
-    }
-
-    pub fn pop(&mut self) {
-        self.0.pop();
-    }
-
-    pub fn name(&self, index: i64) -> &str {
-        assert!(index >= 0);
-        if index >= self.len() {
-            match index - self.len() {
-            | 0 => "α",
-            | 1 => "β",
-            | 2 => "ξ",
-            | _ => todo!(),
-            }
-        } else {
-            let index = self.0.len() - 1 - index as usize;
-            &self.0[index]
-        }
-    }
-}
-
-#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize)]
 pub enum Term<'a> {
+    /// Serialized as `{"Bool": b}`.
     Bool(bool),
+    /// Serialized as `{"If": {"if": t₁, "then": t₂, "else": t₃}}`.
     If {
         r#if: &'a Term<'a>,
         then: &'a Term<'a>,
         r#else: &'a Term<'a>,
     },
+    /// Serialized as `{"Var": {"index": i}}`.
     Var {
         /// de Bruijn index
         index: i64,
     },
+    /// Serialized as `{"Abs": {"hint": x, "type": T, "term": t}}`.
     Abs {
         /// Hint for the name of the bound variable
         hint: String,
         r#type: Type,
         term: &'a Term<'a>,
     },
+    /// Serialized as `{"App": {"fun": t₁, "arg": t₂}}`.
     App {
         fun: &'a Term<'a>,
         arg: &'a Term<'a>,
     },
 }
 
+impl<'a> DeBruijn<'a> for Term<'a> {
+    fn var(index: i64) -> Self {
+        Term::Var { index }
+    }
+
+    fn index(&self) -> Option<i64> {
+        match self {
+        | Term::Var { index } => Some(*index),
+        | _ => None,
+        }
+    }
+
+    fn map<A, F>(&self, arena: &'a A, mut map: F) -> Option<Self>
+    where
+        A: Alloc<'a, Self>,
+        F: FnMut(&'a Self, i64) -> Option<Self>,
+    {
+        match self {
+        | Term::Bool(_) | Term::Var { .. } => None,
+        | Term::If { r#if, then, r#else } => {
+            match (map(r#if, 0), map(then, 0), map(r#else, 0)) {
+            | (None, None, None) => None,
+            | (if_, then_, else_) => {
+                Some(Term::If {
+                    r#if: if_.map_or(*r#if, |r#if| arena.alloc(r#if)),
+                    then: then_.map_or(*then, |then| arena.alloc(then)),
+                    r#else: else_.map_or(*r#else, |r#else| arena.alloc(r#else)),
+                })
+            }
+            }
+        }
+        | Term::Abs { hint, r#type, term } => {
+            Some(Term::Abs {
+                hint: hint.clone(),
+                r#type: r#type.clone(),
+                term: arena.alloc(map(term, 1)?),
+            })
+        }
+        | Term::App { fun, arg } => {
+            match (map(fun, 0), map(arg, 0)) {
+            | (None, None) => None,
+            | (fun_, arg_) => {
+                Some(Term::App {
+                    fun: fun_.map_or(*fun, |fun| arena.alloc(fun)),
+                    arg: arg_.map_or(*arg, |arg| arena.alloc(arg)),
+                })
+            }
+            }
+        }
+        }
+    }
+
+    fn fold<B, F>(&self, init: B, mut fold: F) -> B
+    where
+        F: FnMut(B, &'a Self, i64) -> B,
+    {
+        match self {
+        | Term::Bool(_) | Term::Var { .. } => init,
+        | Term::If { r#if, then, r#else } => {
+            let init = fold(init, r#if, 0);
+            let init = fold(init, then, 0);
+            fold(init, r#else, 0)
+        }
+        | Term::Abs { term, .. } => fold(init, term, 1),
+        | Term::App { fun, arg } => {
+            let init = fold(init, fun, 0);
+            fold(init, arg, 0)
+        }
+        }
+    }
+}
+
 impl<'a> Term<'a> {
     pub fn eval(&'a self, arena: &'a Arena<Term<'a>>) -> &'a Self {
         iter::successors(Some(self), |term| {
@@ -92,6 +138,15 @@ impl<'a> Term<'a> {
                 arg,
             })
         }
+        | Term::If { r#if: Term::Bool(true), then, .. } => Some(Clone::clone(*then)),
+        | Term::If { r#if: Term::Bool(false), r#else, .. } => Some(Clone::clone(*r#else)),
+        | Term::If { r#if, then, r#else } => {
+            Some(Term::If {
+                r#if: arena.alloc(r#if.step(arena)?),
+                then,
+                r#else,
+            })
+        }
         | _ => None,
         }
     }
@@ -106,80 +161,6 @@ impl<'a> Term<'a> {
         }
     }
 
-    fn substitute_top(&self, arena: &'a Arena<Term<'a>>, to: &'a Term<'a>) -> Self {
-        self.substitute(arena, 0, arena.alloc(to.shift(arena, 1))).shift(arena, -1)
-    }
-
-    fn substitute(&self, arena: &'a Arena<Term<'a>>, from: i64, to: &'a Term<'a>) -> Self {
-        self._substitute(arena, from, to, 0)
-    }
-
-    fn _substitute(
-        &self,
-        arena: &'a Arena<Term<'a>>,
-        from: i64,
-        to: &'a Term<'a>,
-        depth: i64,
-    ) -> Self {
-        match self {
-        | Term::Bool(bool) => Term::Bool(*bool),
-        | Term::If { r#if, then, r#else } => {
-            Term::If {
-                r#if: arena.alloc(r#if._substitute(arena, from, to, depth)),
-                then: arena.alloc(then._substitute(arena, from, to, depth)),
-                r#else: arena.alloc(r#else._substitute(arena, from, to, depth)),
-            }
-        }
-        | Term::Var { index } if *index == from + depth => to.shift(arena, depth),
-        | Term::Var { index } => Term::Var { index: *index },
-        | Term::Abs { hint, r#type, term } => {
-            Term::Abs {
-                hint: hint.clone(),
-                r#type: r#type.clone(),
-                term: arena.alloc(term._substitute(arena, from, to, depth + 1)),
-            }
-        }
-        | Term::App { fun, arg } => {
-            Term::App {
-                fun: arena.alloc(fun._substitute(arena, from, to, depth)),
-                arg: arena.alloc(arg._substitute(arena, from, to, depth)),
-            }
-        }
-        }
-    }
-
-    fn shift(&self, arena: &'a Arena<Term<'a>>, max_depth: i64) -> Self {
-        self._shift(arena, max_depth, 0)
-    }
-
-    fn _shift(&self, arena: &'a Arena<Term<'a>>, max_depth: i64, depth: i64) -> Self {
-        match self {
-        | Term::Bool(bool) => Term::Bool(*bool),
-        | Term::If { r#if, then, r#else } => {
-            Term::If {
-                r#if: arena.alloc(r#if._shift(arena, max_depth, depth)),
-                then: arena.alloc(then._shift(arena, max_depth, depth)),
-                r#else: arena.alloc(r#else._shift(arena, max_depth, depth)),
-            }
-        }
-        | Term::Var { index } if *index >= depth => Term::Var { index: index + max_depth },
-        | Term::Var { index } => Term::Var { index: *index },
-        | Term::Abs { hint, r#type, term } => {
-            Term::Abs {
-                hint: hint.clone(),
-                r#type: r#type.clone(),
-                term: arena.alloc(term._shift(arena, max_depth, depth + 1)),
-            }
-        }
-        | Term::App { fun, arg } => {
-            Term::App {
-                fun: arena.alloc(fun._shift(arena, max_depth, depth)),
-                arg: arena.alloc(arg._shift(arena, max_depth, depth)),
-            }
-        }
-        }
-    }
-
     pub fn write<W: io::Write>(&self, context: &mut Context, writer: &mut W) -> anyhow::Result<()> {
         match self {
         | Term::Bool(bool) => {
@@ -197,11 +178,12 @@ impl<'a> Term<'a> {
             write!(writer, "{}", context.name(*index))?;
         }
         | Term::Abs { hint, r#type, term } => {
-            let name = context.push(hint.to_owned());
-            write!(writer, "(λ{}: {}. ", r#type, name)?;
-            term.write(context, writer)?;
-            write!(writer, ")")?;
-            context.pop();
+            context.bind(hint, |context, name| -> anyhow::Result<()> {
+                write!(writer, "(λ{}: {}. ", name, r#type)?;
+                term.write(context, writer)?;
+                write!(writer, ")")?;
+                Ok(())
+            })?;
         }
         | Term::App { fun, arg } => {
             write!(writer, "(")?;
@@ -214,3 +196,112 @@ impl<'a> Term<'a> {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+
+    use tapl_core::DeBruijn as _;
+    use tapl_core::Sexp;
+    use typed_arena::Arena;
+
+    use crate::r#type::Type;
+    use crate::term::Context;
+    use crate::term::Term;
+
+    /// Every term with exactly `size` nodes whose free variables are bound by
+    /// the `depth` enclosing binders, ignoring types.
+    fn generate<'a>(arena: &'a Arena<Term<'a>>, size: usize, depth: i64) -> Vec<Term<'a>> {
+        match size {
+        | 0 => Vec::new(),
+        | 1 => (0..depth)
+            .map(|index| Term::Var { index })
+            .chain(vec![Term::Bool(true), Term::Bool(false)])
+            .collect(),
+        | _ => {
+            let mut terms = generate(arena, size - 1, depth + 1)
+                .into_iter()
+                .map(|term| Term::Abs { hint: String::from("x"), r#type: Type::Bool, term: arena.alloc(term) })
+                .collect::<Vec<_>>();
+            for fun_size in 1..size - 1 {
+                for fun in generate(arena, fun_size, depth) {
+                    for arg in generate(arena, size - 1 - fun_size, depth) {
+                        terms.push(Term::App { fun: arena.alloc(fun.clone()), arg: arena.alloc(arg) });
+                    }
+                }
+            }
+            for if_size in 1..size - 2 {
+                for then_size in 1..size - 1 - if_size {
+                    let else_size = size - 1 - if_size - then_size;
+                    for r#if in generate(arena, if_size, depth) {
+                        for then in generate(arena, then_size, depth) {
+                            for r#else in generate(arena, else_size, depth) {
+                                terms.push(Term::If {
+                                    r#if: arena.alloc(r#if.clone()),
+                                    then: arena.alloc(then.clone()),
+                                    r#else: arena.alloc(r#else),
+                                });
+                            }
+                        }
+                    }
+                }
+            }
+            terms
+        }
+        }
+    }
+
+    /// Binders are written name first, primed when they shadow a name in
+    /// scope, and free variables are named by the context.
+    #[test]
+    fn write() {
+        let arena = Arena::new();
+        let sexp = "(abs x bool (abs x (fun bool bool) (app (var 0) (app (var 1) (app (var 2) (var 3))))))";
+        let term = Term::from_sexp(&arena, &sexp.parse::<Sexp>().unwrap()).unwrap();
+        let mut buffer = Vec::new();
+        term.write(&mut Context::default(), &mut buffer).unwrap();
+        assert_eq!(String::from_utf8(buffer).unwrap(), "(λx: bool. (λx': bool -> bool. (x' (x (α β)))))");
+    }
+
+    /// The fused substitution agrees with shifting, substituting, and shifting
+    /// back, including on open terms.
+    #[test]
+    fn substitute_top_fused() {
+        let arena = Arena::new();
+        let tos = (1..=3)
+            .flat_map(|size| generate(&arena, size, 1))
+            .map(|to| &*arena.alloc(to))
+            .collect::<Vec<_>>();
+        for body in (1..=5).flat_map(|size| generate(&arena, size, 2)) {
+            for to in &tos {
+                let unfused = body
+                    .substitute(&arena, 0, arena.alloc(to.shift(&arena, 1)))
+                    .shift(&arena, -1);
+                assert_eq!(body.substitute_top(&arena, to), unfused);
+            }
+        }
+    }
+
+    /// E-IfTrue and E-IfFalse contract a conditional on a boolean, and E-If
+    /// steps its guard until it is one.
+    #[test]
+    fn step() {
+        let arena = Arena::new();
+        let term = crate::term!(arena, if (λx: bool. x) false then true else false);
+        let next = term.step(&arena).unwrap();
+        assert_eq!(next, crate::term!(arena, if false then true else false));
+        assert_eq!(next.step(&arena), Some(Term::Bool(false)));
+        assert_eq!(crate::term!(arena, if true then false else true).step(&arena), Some(Term::Bool(false)));
+        assert_eq!(crate::term!(arena, λb: bool. if b then b else false).step(&arena), None);
+    }
+
+    #[test]
+    fn eval() {
+        let arena = Arena::new();
+        let not = &*arena.alloc(crate::term!(arena, λb: bool. if b then false else true));
+        let term = arena.alloc(Term::App { fun: not, arg: arena.alloc(Term::App { fun: not, arg: arena.alloc(Term::Bool(false)) }) });
+        assert_eq!(term.eval(&arena), &Term::Bool(false));
+
+        let chain = arena.alloc(crate::term!(arena, (λb: bool. if b then true else if b then true else b) false));
+        assert_eq!(chain.eval(&arena), &Term::Bool(false));
+    }
+}
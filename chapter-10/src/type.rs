@@ -2,12 +2,16 @@ use std::collections::HashMap;
 use std::fmt;
 
 use anyhow::anyhow;
+use serde::Deserialize;
+use serde::Serialize;
 
 use crate::term::Term;
 
-#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum Type {
+    /// Serialized as `"Bool"`.
     Bool,
+    /// Serialized as `{"Fun": [T₁, T₂]}`.
     Fun(Box<Type>, Box<Type>),
 }
 
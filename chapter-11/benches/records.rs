@@ -0,0 +1,71 @@
+use criterion::black_box;
+use criterion::criterion_group;
+use criterion::criterion_main;
+use criterion::BenchmarkId;
+use criterion::Criterion;
+use indexmap::IndexMap;
+use typed_arena::Arena;
+
+use chapter_11::r#type::Context;
+use chapter_11::r#type::Type;
+use chapter_11::term::Term;
+
+/// `(λx: bool. x) true`, which takes one step to evaluate.
+fn field<'a>(arena: &'a Arena<Term<'a>>) -> &'a Term<'a> {
+    arena.alloc(Term::App {
+        fun: arena.alloc(Term::Abs {
+            hint: String::from("x"),
+            r#type: Type::Bool,
+            body: arena.alloc(Term::Var(0)),
+        }),
+        arg: arena.alloc(Term::Bool(true)),
+    })
+}
+
+/// `{l0 = t, ..., ln = t}.ln`, with each field `t` a redex.
+fn record<'a>(arena: &'a Arena<Term<'a>>, n: usize) -> &'a Term<'a> {
+    let fields = (0..n)
+        .map(|index| (format!("l{}", index), field(arena)))
+        .collect::<IndexMap<_, _>>();
+    arena.alloc(Term::RecordProject {
+        record: arena.alloc(Term::Record(fields)),
+        label: format!("l{}", n - 1),
+    })
+}
+
+/// `(t, ..., t).n`, with each element `t` a redex.
+fn tuple<'a>(arena: &'a Arena<Term<'a>>, n: usize) -> &'a Term<'a> {
+    arena.alloc(Term::TupleProject {
+        tuple: arena.alloc(Term::Tuple((0..n).map(|_| field(arena)).collect())),
+        index: n - 1,
+    })
+}
+
+type Build = for<'a> fn(&'a Arena<Term<'a>>, usize) -> &'a Term<'a>;
+
+fn records(criterion: &mut Criterion) {
+    for (name, build) in [("record", record as Build), ("tuple", tuple)] {
+        let mut group = criterion.benchmark_group(name);
+
+        for n in [10, 100, 1000] {
+            let input: &'static Arena<Term<'static>> = Box::leak(Box::new(Arena::new()));
+            let term = build(input, n);
+
+            group.bench_with_input(BenchmarkId::new("check", n), &n, |bencher, _| {
+                bencher.iter(|| black_box(term.check(&mut Context::default(), 0).unwrap()))
+            });
+
+            group.bench_with_input(BenchmarkId::new("eval", n), &n, |bencher, _| {
+                bencher.iter(|| {
+                    let arena = Arena::new();
+                    black_box(term.eval(&arena).unwrap().clone());
+                })
+            });
+        }
+
+        group.finish();
+    }
+}
+
+criterion_group!(benches, records);
+criterion_main!(benches);
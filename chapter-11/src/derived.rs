@@ -0,0 +1,138 @@
+//! Derived forms (§11.3), which abbreviate terms of the internal language
+//! instead of extending it with evaluation and typing rules of their own.
+//!
+//! `desugar` is the elaboration function `e` of Theorem 11.3.1, which
+//! replaces each derived form by its expansion.
+
+use tapl_core::Alloc;
+use tapl_core::DeBruijn as _;
+
+use crate::r#type::Type;
+use crate::term::Term;
+
+impl<'a> Term<'a> {
+    /// The sequence `first; second` as `(λ_: Unit. second) first`, where
+    /// `second` is shifted past the new binder, which it never refers to.
+    pub fn derived_seq<A: Alloc<'a, Term<'a>>>(arena: &'a A, first: &'a Term<'a>, second: &'a Term<'a>) -> Self {
+        Term::App {
+            fun: arena.alloc(Term::Abs {
+                hint: String::from("_"),
+                r#type: Type::Unit,
+                body: arena.alloc(second.shift(arena, 1)),
+            }),
+            arg: first,
+        }
+    }
+
+    /// `letrec x: T₁ = arg in body` as `let x = fix (λx: T₁. arg) in body`,
+    /// where `x` is bound in both `arg` and `body`. Unlike sequencing, the
+    /// expansion is the only representation of `letrec`.
+    pub fn derived_letrec<A: Alloc<'a, Term<'a>>>(arena: &'a A, hint: &str, r#type: Type, arg: &'a Term<'a>, body: &'a Term<'a>) -> Self {
+        Term::Let {
+            hint: hint.to_owned(),
+            arg: arena.alloc(Term::Fix(arena.alloc(Term::Abs {
+                hint: hint.to_owned(),
+                r#type,
+                body: arg,
+            }))),
+            body,
+        }
+    }
+
+    /// Replaces every derived form in this term by its expansion.
+    pub fn desugar<A: Alloc<'a, Term<'a>>>(&self, arena: &'a A) -> Self {
+        self._desugar(arena)
+            .unwrap_or_else(|| self.clone())
+    }
+
+    /// Returns `None` if this term has no derived forms.
+    fn _desugar<A: Alloc<'a, Term<'a>>>(&self, arena: &'a A) -> Option<Self> {
+        match self {
+        | Term::Seq { first, second } => {
+            let first = first._desugar(arena).map_or(*first, |first| arena.alloc(first));
+            let second = second._desugar(arena).map_or(*second, |second| arena.alloc(second));
+            Some(Term::derived_seq(arena, first, second))
+        }
+        | _ => self.map(arena, |term, _| term._desugar(arena)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use typed_arena::Arena;
+
+    use crate::parse::Command;
+    use crate::r#type;
+    use crate::r#type::Type;
+    use crate::term::Term;
+
+    /// Theorem 11.3.1 - Sequencing and its derived form have the same types,
+    /// and each step of a sequence is matched by a step of its expansion.
+    #[test]
+    fn seq() {
+        let arena = Arena::new();
+        let sources = [
+            "((lambda x: Unit. x) unit; unit; true)",
+            "(lambda u: Unit. (u; (lambda v: Unit. v) u; if true then u else unit)) ((lambda x: Unit. x) unit)",
+            "let f = lambda b: Bool. (unit; b) in {f true, (f false; unit)}",
+            "let f = lambda u: Unit. (u; u) in {f unit, (f unit; true)}",
+            "(true; false)",
+        ];
+        for source in &sources {
+            let mut term = Term::parse(&arena, source).unwrap();
+            let mut derived = term.desugar(&arena);
+            let checked = |term: &Term| term.check(&mut r#type::Context::default(), 0).map_err(|_| ());
+            assert_eq!(checked(&term), checked(&derived), "{}", source);
+            if checked(&term).is_err() {
+                continue;
+            }
+
+            loop {
+                assert_eq!(term.desugar(&arena), derived, "{}", source);
+                match (term.step(&arena), derived.step(&arena)) {
+                | (Some(next), Some(derived_next)) => {
+                    term = next;
+                    derived = derived_next;
+                }
+                | (None, None) => break,
+                | (next, derived_next) => panic!("{}: {:?} and {:?}", source, next, derived_next),
+                }
+            }
+            assert!(term.is_value());
+        }
+    }
+
+    /// `letrec` reads as its expansion, and a recursive function over a
+    /// variant type, computing the parity of the days left until wednesday,
+    /// runs to completion.
+    #[test]
+    fn letrec() {
+        let arena = Arena::new();
+        let letrec = Term::parse(&arena, "letrec f: Bool -> Bool = lambda b: Bool. f b in f true").unwrap();
+        let expanded = Term::parse(&arena, "let f = fix (lambda f: Bool -> Bool. lambda b: Bool. f b) in f true").unwrap();
+        assert_eq!(letrec, expanded);
+
+        let source = "
+            Weekday = <monday: Unit, tuesday: Unit, wednesday: Unit>;
+            next = lambda w: Weekday.
+                case w of <monday = x> ==> <tuesday = unit> as Weekday
+                        | <tuesday = x> ==> <wednesday = unit> as Weekday
+                        | <wednesday = x> ==> <monday = unit> as Weekday;
+            letrec iseven: Weekday -> Bool =
+                lambda w: Weekday.
+                    case w of <monday = x> ==> if iseven (next w) then false else true
+                            | <tuesday = x> ==> if iseven (next w) then false else true
+                            | <wednesday = x> ==> true
+            in {iseven (<monday = unit> as Weekday), iseven (<tuesday = unit> as Weekday)};
+        ";
+        let commands = Term::parse_file(&arena, source);
+        let term = match commands[2].as_ref().unwrap() {
+        | Command::Eval(term) => arena.alloc(term.clone()),
+        | command => panic!("Expected term, but found {:?}", command),
+        };
+        assert_eq!(term.check(&mut r#type::Context::default(), 0).unwrap(), Type::Tuple(vec![Type::Bool, Type::Bool]));
+        assert_eq!(term.eval(&arena).unwrap(), &Term::Tuple(vec![&Term::Bool(true), &Term::Bool(false)]));
+    }
+}
@@ -0,0 +1,183 @@
+use std::io;
+use std::iter;
+
+use anyhow::anyhow;
+use tapl_core::dot;
+use tapl_core::dot::Tree;
+use tapl_core::DeBruijn;
+use typed_arena::Arena;
+
+use crate::term::Term;
+use crate::term::FUEL;
+
+impl<'a> Term<'a> {
+    /// The abstract syntax tree of this term, with the subterm at `redex`
+    /// highlighted. Variables are labeled with their de Bruijn index, and
+    /// bound variables point back to their abstraction or `let`.
+    pub fn to_dot(&self, redex: Option<&[usize]>) -> Tree {
+        let mut tree = Tree::default();
+        self._to_dot(&mut tree, &mut Vec::new(), redex);
+        tree
+    }
+
+    fn _to_dot(&self, tree: &mut Tree, binders: &mut Vec<usize>, redex: Option<&[usize]>) -> usize {
+        let (label, edges) = match self {
+        | Term::Bool(bool) => (bool.to_string(), vec![]),
+        | Term::If { .. } => (String::from("if"), vec![String::from("if"), String::from("then"), String::from("else")]),
+        | Term::Var(index) => (index.to_string(), vec![]),
+        | Term::Abs { hint, r#type, .. } => (format!("λ{}: {}", hint, r#type), vec![String::from("body")]),
+        | Term::App { .. } => (String::from("app"), vec![String::from("fun"), String::from("arg")]),
+        | Term::Asc { r#type, .. } => (format!("as {}", r#type), vec![String::from("term")]),
+        | Term::Let { hint, .. } => (format!("let {}", hint), vec![String::from("arg"), String::from("body")]),
+        | Term::Tuple(terms) => (String::from("tuple"), (0..terms.len()).map(|index| index.to_string()).collect()),
+        | Term::TupleProject { index, .. } => (format!(".{}", index), vec![String::from("tuple")]),
+        | Term::Record(terms) => (String::from("record"), terms.keys().cloned().collect()),
+        | Term::RecordProject { label, .. } => (format!(".{}", label), vec![String::from("record")]),
+        | Term::Unit => (String::from("unit"), vec![]),
+        | Term::Seq { .. } => (String::from(";"), vec![String::from("first"), String::from("second")]),
+        | Term::Inl { r#type, .. } => (format!("inl as {}", r#type), vec![String::from("term")]),
+        | Term::Inr { r#type, .. } => (format!("inr as {}", r#type), vec![String::from("term")]),
+        | Term::Case { inl_hint, inr_hint, .. } => {
+            (String::from("case"), vec![String::from("sum"), format!("inl {}", inl_hint), format!("inr {}", inr_hint)])
+        }
+        | Term::Variant { label, r#type, .. } => (format!("<{}> as {}", label, r#type), vec![String::from("term")]),
+        | Term::VariantCase { arms, .. } => {
+            let arms = arms.iter().map(|arm| format!("<{} = {}>", arm.label, arm.hint));
+            (String::from("case"), iter::once(String::from("variant")).chain(arms).collect())
+        }
+        | Term::Fix(_) => (String::from("fix"), vec![String::from("term")]),
+        };
+
+        let node = tree.node(label);
+
+        if let Term::Var(index) = self {
+            if let Some(&binder) = binders.iter().rev().nth(*index as usize) {
+                tree.binder(node, binder);
+            }
+        }
+
+        if let Some([]) = redex {
+            tree.highlight(node);
+        }
+
+        let children = self.fold(Vec::new(), |mut children, child, bound| {
+            children.push((child, bound));
+            children
+        });
+
+        for (index, ((child, bound), edge)) in children.into_iter().zip(edges).enumerate() {
+            binders.extend(iter::repeat_n(node, bound as usize));
+            let child = child._to_dot(tree, binders, dot::descend(redex, index));
+            binders.truncate(binders.len() - bound as usize);
+            tree.child(node, &edge, child);
+        }
+
+        node
+    }
+
+    /// Writes the abstract syntax tree of this term in the Graphviz DOT
+    /// language.
+    pub fn write_dot<W: io::Write>(&self, writer: &mut W) -> anyhow::Result<()> {
+        self.to_dot(None).write("term", writer)
+    }
+
+    /// Writes every term in the evaluation sequence of this term as its own
+    /// graph, highlighting the redex contracted by the following step, and
+    /// failing after `FUEL` steps.
+    pub fn write_trace<W: io::Write>(&'a self, arena: &'a Arena<Term<'a>>, writer: &mut W) -> anyhow::Result<()> {
+        self.write_trace_with_fuel(arena, FUEL, writer)
+    }
+
+    /// Writes the evaluation sequence of this term as `write_trace` does,
+    /// failing if it takes more than `fuel` steps.
+    pub fn write_trace_with_fuel<W: io::Write>(&'a self, arena: &'a Arena<Term<'a>>, fuel: usize, writer: &mut W) -> anyhow::Result<()> {
+        let mut term = self;
+        for step in 0..=fuel {
+            term.to_dot(term.redex().as_deref()).write(&format!("step{}", step), writer)?;
+            match term.step(arena) {
+            | None => return Ok(()),
+            | Some(next) => term = arena.alloc(next),
+            }
+        }
+        Err(anyhow!("Evaluation ran out of fuel after {} steps", fuel))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use tapl_core::Sexp;
+    use typed_arena::Arena;
+
+    use crate::term::Term;
+
+    #[test]
+    fn trace() {
+        let arena = Arena::new();
+        let sexp = "(let x (bool true) (tuple-project (tuple (var 0) (abs y bool (var 1))) 1))".parse::<Sexp>().unwrap();
+        let term = Term::from_sexp(&arena, &sexp).unwrap();
+        let mut dot = Vec::new();
+        term.write_trace(&arena, &mut dot).unwrap();
+        assert_eq!(String::from_utf8(dot).unwrap(), "\
+digraph step0 {
+    node [shape=box];
+    0 [label=\"let x\", style=filled, fillcolor=yellow];
+    1 [label=\"true\", style=filled, fillcolor=yellow];
+    2 [label=\".1\", style=filled, fillcolor=yellow];
+    3 [label=\"tuple\", style=filled, fillcolor=yellow];
+    4 [label=\"0\", style=filled, fillcolor=yellow];
+    5 [label=\"λy: bool\", style=filled, fillcolor=yellow];
+    6 [label=\"1\", style=filled, fillcolor=yellow];
+    0 -> 1 [label=\"arg\"];
+    3 -> 4 [label=\"0\"];
+    5 -> 6 [label=\"body\"];
+    3 -> 5 [label=\"1\"];
+    2 -> 3 [label=\"tuple\"];
+    0 -> 2 [label=\"body\"];
+    4 -> 0 [style=dashed, constraint=false];
+    6 -> 0 [style=dashed, constraint=false];
+}
+digraph step1 {
+    node [shape=box];
+    0 [label=\".1\", style=filled, fillcolor=yellow];
+    1 [label=\"tuple\", style=filled, fillcolor=yellow];
+    2 [label=\"true\", style=filled, fillcolor=yellow];
+    3 [label=\"λy: bool\", style=filled, fillcolor=yellow];
+    4 [label=\"true\", style=filled, fillcolor=yellow];
+    1 -> 2 [label=\"0\"];
+    3 -> 4 [label=\"body\"];
+    1 -> 3 [label=\"1\"];
+    0 -> 1 [label=\"tuple\"];
+}
+digraph step2 {
+    node [shape=box];
+    0 [label=\"λy: bool\"];
+    1 [label=\"true\"];
+    0 -> 1 [label=\"body\"];
+}
+");
+    }
+
+    /// A diverging term is drawn for as many steps as there is fuel.
+    #[test]
+    fn fuel() {
+        let arena = Arena::new();
+        let term = arena.alloc(Term::parse(&arena, "fix (lambda x: Bool. x)").unwrap());
+        let mut dot = Vec::new();
+        let error = term.write_trace_with_fuel(&arena, 3, &mut dot).unwrap_err();
+        assert_eq!(error.to_string(), "Evaluation ran out of fuel after 3 steps");
+        assert_eq!(String::from_utf8(dot).unwrap().matches("digraph").count(), 4);
+    }
+
+    /// The redex is inside a record, after the fields that are values.
+    #[test]
+    fn redex() {
+        let arena = Arena::new();
+        let sexp = "(record-project (record (a (bool true)) (b (if (app (abs x bool (var 0)) (bool false)) (bool true) (bool false)))) b)"
+            .parse::<Sexp>()
+            .unwrap();
+        let term = Term::from_sexp(&arena, &sexp).unwrap();
+        assert_eq!(term.redex(), Some(vec![0, 1, 0]));
+        assert_eq!(Term::from_sexp(&arena, &"(abs x bool (var 0))".parse().unwrap()).unwrap().redex(), None);
+    }
+}
@@ -0,0 +1,173 @@
+use anyhow::anyhow;
+use serde_json::Value;
+use tapl_core::json;
+use typed_arena::Arena;
+
+use crate::term::Arm;
+use crate::term::Term;
+
+impl<'a> Term<'a> {
+    /// Reads back a term serialized as JSON, allocating it in `arena`. See
+    /// the variants of `Term` and `Type` for the schema.
+    pub fn from_json(arena: &'a Arena<Term<'a>>, json: &str) -> anyhow::Result<Self> {
+        json::read(json, |value| Self::from_value(arena, value))
+    }
+
+    pub fn from_value(arena: &'a Arena<Term<'a>>, value: &Value) -> anyhow::Result<Self> {
+        let subterm = |value: &Value| -> anyhow::Result<&'a Term<'a>> {
+            Ok(arena.alloc(json::grow(|| Self::from_value(arena, value))?))
+        };
+        match json::variant(value)? {
+        | ("Bool", content) => Ok(Term::Bool(json::leaf(content)?)),
+        | ("If", content) => {
+            Ok(Term::If {
+                r#if: subterm(json::field(content, "if")?)?,
+                then: subterm(json::field(content, "then")?)?,
+                r#else: subterm(json::field(content, "else")?)?,
+            })
+        }
+        | ("Var", content) => Ok(Term::Var(json::leaf(content)?)),
+        | ("Abs", content) => {
+            Ok(Term::Abs {
+                hint: json::leaf(json::field(content, "hint")?)?,
+                r#type: json::leaf(json::field(content, "type")?)?,
+                body: subterm(json::field(content, "body")?)?,
+            })
+        }
+        | ("App", content) => {
+            Ok(Term::App {
+                fun: subterm(json::field(content, "fun")?)?,
+                arg: subterm(json::field(content, "arg")?)?,
+            })
+        }
+        | ("Asc", content) => {
+            Ok(Term::Asc {
+                term: subterm(json::field(content, "term")?)?,
+                r#type: json::leaf(json::field(content, "type")?)?,
+            })
+        }
+        | ("Let", content) => {
+            Ok(Term::Let {
+                hint: json::leaf(json::field(content, "hint")?)?,
+                arg: subterm(json::field(content, "arg")?)?,
+                body: subterm(json::field(content, "body")?)?,
+            })
+        }
+        | ("Tuple", content) => {
+            json::array(content)?
+                .iter()
+                .map(subterm)
+                .collect::<anyhow::Result<_>>()
+                .map(Term::Tuple)
+        }
+        | ("TupleProject", content) => {
+            Ok(Term::TupleProject {
+                tuple: subterm(json::field(content, "tuple")?)?,
+                index: json::leaf(json::field(content, "index")?)?,
+            })
+        }
+        | ("Record", Value::Object(terms)) => {
+            terms
+                .iter()
+                .map(|(label, term)| Ok((label.to_owned(), subterm(term)?)))
+                .collect::<anyhow::Result<_>>()
+                .map(Term::Record)
+        }
+        | ("RecordProject", content) => {
+            Ok(Term::RecordProject {
+                record: subterm(json::field(content, "record")?)?,
+                label: json::leaf(json::field(content, "label")?)?,
+            })
+        }
+        | ("Unit", _) => Ok(Term::Unit),
+        | ("Seq", content) => {
+            Ok(Term::Seq {
+                first: subterm(json::field(content, "first")?)?,
+                second: subterm(json::field(content, "second")?)?,
+            })
+        }
+        | ("Inl", content) => {
+            Ok(Term::Inl {
+                term: subterm(json::field(content, "term")?)?,
+                r#type: json::leaf(json::field(content, "type")?)?,
+            })
+        }
+        | ("Inr", content) => {
+            Ok(Term::Inr {
+                term: subterm(json::field(content, "term")?)?,
+                r#type: json::leaf(json::field(content, "type")?)?,
+            })
+        }
+        | ("Case", content) => {
+            Ok(Term::Case {
+                sum: subterm(json::field(content, "sum")?)?,
+                inl_hint: json::leaf(json::field(content, "inl_hint")?)?,
+                inl: subterm(json::field(content, "inl")?)?,
+                inr_hint: json::leaf(json::field(content, "inr_hint")?)?,
+                inr: subterm(json::field(content, "inr")?)?,
+            })
+        }
+        | ("Variant", content) => {
+            Ok(Term::Variant {
+                label: json::leaf(json::field(content, "label")?)?,
+                term: subterm(json::field(content, "term")?)?,
+                r#type: json::leaf(json::field(content, "type")?)?,
+            })
+        }
+        | ("VariantCase", content) => {
+            Ok(Term::VariantCase {
+                variant: subterm(json::field(content, "variant")?)?,
+                arms: json::array(json::field(content, "arms")?)?
+                    .iter()
+                    .map(|arm| {
+                        Ok(Arm {
+                            label: json::leaf(json::field(arm, "label")?)?,
+                            hint: json::leaf(json::field(arm, "hint")?)?,
+                            body: subterm(json::field(arm, "body")?)?,
+                        })
+                    })
+                    .collect::<anyhow::Result<_>>()?,
+            })
+        }
+        | ("Fix", content) => Ok(Term::Fix(subterm(content)?)),
+        | (tag, content) => Err(anyhow!("Unknown term variant: {} {}", tag, content)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use tapl_core::json;
+    use typed_arena::Arena;
+
+    use crate::term::Term;
+
+    /// Serializing and deserializing a term gives back the same term,
+    /// including its hints, types, and the order of record fields.
+    #[test]
+    fn round_trip() {
+        let arena = Arena::new();
+        let json = r#"{"Let":{"hint":"r","arg":{"Record":{"y":{"Tuple":[{"Bool":true},{"Var":0}]},"x":{"Asc":{"term":{"Abs":{"hint":"x","type":{"Record":{"b":"Bool","a":{"Tuple":["Bool",{"Fun":["Bool","Bool"]}]}}},"body":{"Var":0}}},"type":{"Fun":[{"Record":{"b":"Bool","a":{"Tuple":["Bool",{"Fun":["Bool","Bool"]}]}}},{"Record":{"b":"Bool","a":{"Tuple":["Bool",{"Fun":["Bool","Bool"]}]}}}]}}}}},"body":{"If":{"if":{"TupleProject":{"tuple":{"RecordProject":{"record":{"Var":0},"label":"y"}},"index":0}},"then":{"App":{"fun":{"Var":1},"arg":{"Var":0}}},"else":{"Var":0}}}}}"#;
+        let term = Term::from_json(&arena, json).unwrap();
+        assert_eq!(serde_json::to_string(&term).unwrap(), json);
+        assert!(Term::from_json(&arena, r#"{"Record":[]}"#).is_err());
+    }
+
+    /// JSON nested up to `MAX_DEPTH` levels is read back, and deeper JSON is
+    /// rejected rather than overflowing the stack.
+    #[test]
+    fn deep() {
+        let arena = Arena::new();
+        let fix = |n| r#"{"Fix":"#.repeat(n) + r#""Unit""# + &"}".repeat(n);
+        let mut term = &Term::from_json(&arena, &fix(json::MAX_DEPTH)).unwrap();
+        let mut depth = 0;
+        while let Term::Fix(body) = term {
+            term = body;
+            depth += 1;
+        }
+        assert_eq!((term, depth), (&Term::Unit, json::MAX_DEPTH));
+        assert!(Term::from_json(&arena, &fix(json::MAX_DEPTH + 1)).is_err());
+        assert!(Term::from_json(&arena, &fix(100_000)).is_err());
+    }
+}
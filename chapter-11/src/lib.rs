@@ -1,2 +1,47 @@
+pub mod derived;
+pub mod dot;
+pub mod json;
+pub mod owned;
+pub mod parse;
+pub mod sexp;
 pub mod term;
 pub mod r#type;
+
+#[doc(hidden)]
+pub use tapl_macros;
+
+/// Builds a `Term` from its concrete syntax, resolving variables to de Bruijn
+/// indices at compile time, and allocating its subterms in the given arena.
+/// Tuples are written `(t₁, t₂)` and projected from `0`, records are written
+/// `{l = t}`, with types `{l: T}`, and sequences are written `(t₁; t₂)`.
+/// `Bool` may also be written `bool`, and capitalized type names other than
+/// `Bool` and `Unit` are base types. The arms of a case on a sum are written
+/// `inl x => t₁ | inr y => t₂`, and those of a case on a variant
+/// `<l = x> => t | ...`. `letrec` is read as its expansion into `let` and
+/// `fix`.
+///
+/// ```
+/// use typed_arena::Arena;
+/// use chapter_11::term::Term;
+///
+/// let arena = Arena::new();
+/// let term = chapter_11::term!(arena, let r = {a = (true, λx: bool. x)} in r.a.1 as bool -> bool);
+/// let sexp = "
+///     (let r (record (a (tuple (bool true) (abs x bool (var 0)))))
+///         (as (tuple-project (record-project (var 0) a) 1) (fun bool bool)))
+/// ";
+/// assert_eq!(term, Term::from_sexp(&arena, &sexp.parse().unwrap()).unwrap());
+/// ```
+///
+/// Unbound variables are rejected at compile time:
+///
+/// ```compile_fail
+/// let arena = typed_arena::Arena::new();
+/// chapter_11::term!(arena, let x = true in y);
+/// ```
+#[macro_export]
+macro_rules! term {
+    ($arena:expr, $($term:tt)*) => {
+        $crate::tapl_macros::term!(extended, $crate, $arena, $($term)*)
+    };
+}
@@ -1,42 +1,183 @@
+use std::env;
 use std::io;
+use std::io::Read as _;
 use std::io::Write as _;
 
+use anyhow::anyhow;
+use tapl_core::Sexp;
 use typed_arena::Arena;
 
+use chapter_11::parse::Command;
+use chapter_11::r#type;
+use chapter_11::r#type::Type;
 use chapter_11::term::Context;
 use chapter_11::term::Term;
+use chapter_11::term::FUEL;
 
+/// Type-checks example terms, printing the type of each and then every
+/// step of its evaluation, or the type error instead. Evaluation fails after
+/// `FUEL` steps.
+///
+/// With `--read-sexp`, the terms are instead read as S-expressions from
+/// standard input, and with `--write-sexp`, every step is printed as an
+/// S-expression. With `--read-tapl`, the terms are instead read from the
+/// commands of a `.f` file for the book's reference implementation on
+/// standard input, and commands that fail to parse are reported. With
+/// `--write-dot`, every step is instead drawn as a Graphviz DOT graph, with
+/// the redex contracted by the next step highlighted.
 pub fn main() -> anyhow::Result<()> {
 
+    let mut read_sexp = false;
+    let mut read_tapl = false;
+    let mut write_sexp = false;
+    let mut write_dot = false;
+
+    for flag in env::args().skip(1) {
+        match flag.as_str() {
+        | "--read-sexp" => read_sexp = true,
+        | "--read-tapl" => read_tapl = true,
+        | "--write-sexp" => write_sexp = true,
+        | "--write-dot" => write_dot = true,
+        | flag => return Err(anyhow!("Unknown flag: {}", flag)),
+        }
+    }
+
     let arena = Arena::new();
+    let mut context = Context::default();
+    let mut types = r#type::Context::default();
 
-    let terms = vec![];
+    let terms = if read_tapl {
+        read_file(&arena, &mut context, &mut types)?
+    } else if read_sexp {
+        read(&arena)?
+    } else {
+        examples(&arena)?
+    };
 
     for term in terms {
-        step(term, &arena)?;
+        let depth = types.depth();
+        let r#type = match term.check(&mut types, depth) {
+        | Ok(r#type) => r#type,
+        | Err(error) => {
+            eprintln!("{:#}", error);
+            continue;
+        }
+        };
+        if write_dot {
+            trace(term, &arena)?;
+        } else {
+            step(term, &r#type, &arena, &mut context, write_sexp)?;
+        }
     }
 
     Ok(())
 }
 
+fn examples<'a>(arena: &'a Arena<Term<'a>>) -> anyhow::Result<Vec<Term<'a>>> {
+    let sources = [
+        // let x = true in (x, λy: bool. x).1 false -->* true
+        "(let x (bool true) (app (tuple-project (tuple (var 0) (abs y bool (var 1))) 1) (bool false)))",
+
+        // {a = (λx: bool. x) false, b = true}.a as bool -->* false
+        "(as (record-project (record (a (app (abs x bool (var 0)) (bool false))) (b (bool true))) a) bool)",
+
+        // ((λx: unit. x) unit; true) -->* true
+        "(seq (app (abs x unit (var 0)) unit) (bool true))",
+
+        // λf: A -> A. λx: A. f (f x), a value over the base type A
+        "(abs f (fun (base A) (base A)) (abs x (base A) (app (var 1) (app (var 1) (var 0)))))",
+
+        // case inr unit as bool + unit of inl x ⇒ x | inr y ⇒ false -->* false
+        "(case (inr unit (sum bool unit)) (inl x (var 0)) (inr y (bool false)))",
+    ];
+    sources
+        .iter()
+        .map(|source| Term::from_sexp(arena, &source.parse()?))
+        .collect()
+}
+
+fn read<'a>(arena: &'a Arena<Term<'a>>) -> anyhow::Result<Vec<Term<'a>>> {
+    let mut source = String::new();
+    io::stdin().read_to_string(&mut source)?;
+    Sexp::parse_all(&source)?
+        .iter()
+        .map(|sexp| Term::from_sexp(arena, sexp))
+        .collect()
+}
+
+/// Reads the terms to evaluate from a `.f` file, naming the free variables it
+/// binds in `context` and declaring their types in `types`.
+fn read_file<'a>(
+    arena: &'a Arena<Term<'a>>,
+    context: &mut Context,
+    types: &mut r#type::Context,
+) -> anyhow::Result<Vec<Term<'a>>> {
+    let mut source = String::new();
+    io::stdin().read_to_string(&mut source)?;
+    let mut terms = Vec::new();
+    for command in Term::parse_file(arena, &source) {
+        match command {
+        | Ok(Command::Eval(term)) => terms.push(term),
+        | Ok(Command::Bind(name, r#type)) => {
+            context.push(name);
+            types.declare(r#type);
+        }
+        | Ok(_) => (),
+        | Err(error) => eprintln!("{:#}", error),
+        }
+    }
+    Ok(terms)
+}
+
 fn step<'a>(
     mut term: Term<'a>,
+    r#type: &Type,
     arena: &'a Arena<Term<'a>>,
+    context: &mut Context,
+    write_sexp: bool,
 ) -> anyhow::Result<()> {
 
     let stdout = io::stdout();
     let mut stdout = stdout.lock();
-    let mut context = Context::default();
 
-    term.write(&mut context, &mut stdout)?;
-    write!(&mut stdout, "\n")?;
+    if write_sexp {
+        writeln!(&mut stdout, ": {}", r#type.to_sexp())?;
+    } else {
+        writeln!(&mut stdout, ": {}", r#type)?;
+    }
+    write(&term, context, &mut stdout, write_sexp)?;
 
-    while let Some(next) = term.step(&arena) {
+    let mut fuel = FUEL;
+    while let Some(next) = term.step(arena) {
+        if fuel == 0 {
+            return Err(anyhow!("Evaluation ran out of fuel after {} steps", FUEL));
+        }
+        fuel -= 1;
         term = next;
-        term.write(&mut context, &mut stdout)?;
-        write!(&mut stdout, "\n")?;
+        write(&term, context, &mut stdout, write_sexp)?;
     }
 
-    write!(&mut stdout, "\n")?;
+    writeln!(&mut stdout)?;
+    Ok(())
+}
+
+fn trace<'a>(term: Term<'a>, arena: &'a Arena<Term<'a>>) -> anyhow::Result<()> {
+    let stdout = io::stdout();
+    let mut stdout = stdout.lock();
+    arena.alloc(term).write_trace(arena, &mut stdout)
+}
+
+fn write<W: io::Write>(
+    term: &Term,
+    context: &mut Context,
+    writer: &mut W,
+    write_sexp: bool,
+) -> anyhow::Result<()> {
+    if write_sexp {
+        write!(writer, "{}", term.to_sexp())?;
+    } else {
+        term.write(context, writer)?;
+    }
+    writeln!(writer)?;
     Ok(())
 }
@@ -0,0 +1,424 @@
+//! Terms that own their subterms, for embedding the interpreter in
+//! applications that cannot thread an arena's lifetime through their own
+//! types.
+//!
+//! As in chapter 7, each operation converts into a scratch arena, runs
+//! there, and converts the result back, preserving sharing both ways.
+
+use std::collections::HashMap;
+use std::io;
+use std::sync::Arc;
+
+use indexmap::IndexMap;
+use typed_arena::Arena;
+
+use crate::r#type;
+use crate::r#type::Type;
+use crate::term;
+use crate::term::Context;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Term {
+    Bool(bool),
+    If {
+        r#if: Arc<Term>,
+        then: Arc<Term>,
+        r#else: Arc<Term>,
+    },
+    /// de Bruijn index
+    Var(i64),
+    Abs {
+        /// Hint for the name of the bound variable
+        hint: String,
+        r#type: Type,
+        body: Arc<Term>,
+    },
+    App {
+        fun: Arc<Term>,
+        arg: Arc<Term>,
+    },
+    Asc {
+        term: Arc<Term>,
+        r#type: Type,
+    },
+    Let {
+        hint: String,
+        arg: Arc<Term>,
+        body: Arc<Term>,
+    },
+    Tuple(Vec<Arc<Term>>),
+    TupleProject {
+        tuple: Arc<Term>,
+        index: usize,
+    },
+    Record(IndexMap<String, Arc<Term>>),
+    RecordProject {
+        record: Arc<Term>,
+        label: String,
+    },
+    Unit,
+    Seq {
+        first: Arc<Term>,
+        second: Arc<Term>,
+    },
+    Inl {
+        term: Arc<Term>,
+        r#type: Type,
+    },
+    Inr {
+        term: Arc<Term>,
+        r#type: Type,
+    },
+    Case {
+        sum: Arc<Term>,
+        inl_hint: String,
+        inl: Arc<Term>,
+        inr_hint: String,
+        inr: Arc<Term>,
+    },
+    Variant {
+        label: String,
+        term: Arc<Term>,
+        r#type: Type,
+    },
+    VariantCase {
+        variant: Arc<Term>,
+        arms: Vec<Arm>,
+    },
+    Fix(Arc<Term>),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Arm {
+    pub label: String,
+    pub hint: String,
+    pub body: Arc<Term>,
+}
+
+impl<'a> From<&term::Term<'a>> for Term {
+    fn from(term: &term::Term<'a>) -> Self {
+        Term::_from(term, &mut HashMap::new())
+    }
+}
+
+impl Term {
+    fn _from<'a>(term: &term::Term<'a>, cache: &mut HashMap<*const term::Term<'a>, Arc<Term>>) -> Self {
+        let mut share = |term: &'a term::Term<'a>| -> Arc<Term> {
+            if let Some(owned) = cache.get(&(term as *const _)) {
+                return Arc::clone(owned);
+            }
+            let owned = Arc::new(Term::_from(term, cache));
+            cache.insert(term, Arc::clone(&owned));
+            owned
+        };
+        match term {
+        | term::Term::Bool(bool) => Term::Bool(*bool),
+        | term::Term::If { r#if, then, r#else } => {
+            Term::If {
+                r#if: share(r#if),
+                then: share(then),
+                r#else: share(r#else),
+            }
+        }
+        | term::Term::Var(index) => Term::Var(*index),
+        | term::Term::Abs { hint, r#type, body } => {
+            Term::Abs {
+                hint: hint.clone(),
+                r#type: r#type.clone(),
+                body: share(body),
+            }
+        }
+        | term::Term::App { fun, arg } => {
+            Term::App {
+                fun: share(fun),
+                arg: share(arg),
+            }
+        }
+        | term::Term::Asc { term, r#type } => {
+            Term::Asc {
+                term: share(term),
+                r#type: r#type.clone(),
+            }
+        }
+        | term::Term::Let { hint, arg, body } => {
+            Term::Let {
+                hint: hint.clone(),
+                arg: share(arg),
+                body: share(body),
+            }
+        }
+        | term::Term::Tuple(terms) => Term::Tuple(terms.iter().map(|term| share(term)).collect()),
+        | term::Term::TupleProject { tuple, index } => {
+            Term::TupleProject {
+                tuple: share(tuple),
+                index: *index,
+            }
+        }
+        | term::Term::Record(terms) => {
+            Term::Record(terms
+                .iter()
+                .map(|(label, term)| (label.clone(), share(term)))
+                .collect())
+        }
+        | term::Term::RecordProject { record, label } => {
+            Term::RecordProject {
+                record: share(record),
+                label: label.clone(),
+            }
+        }
+        | term::Term::Unit => Term::Unit,
+        | term::Term::Seq { first, second } => {
+            Term::Seq {
+                first: share(first),
+                second: share(second),
+            }
+        }
+        | term::Term::Inl { term, r#type } => {
+            Term::Inl {
+                term: share(term),
+                r#type: r#type.clone(),
+            }
+        }
+        | term::Term::Inr { term, r#type } => {
+            Term::Inr {
+                term: share(term),
+                r#type: r#type.clone(),
+            }
+        }
+        | term::Term::Case { sum, inl_hint, inl, inr_hint, inr } => {
+            Term::Case {
+                sum: share(sum),
+                inl_hint: inl_hint.clone(),
+                inl: share(inl),
+                inr_hint: inr_hint.clone(),
+                inr: share(inr),
+            }
+        }
+        | term::Term::Variant { label, term, r#type } => {
+            Term::Variant {
+                label: label.clone(),
+                term: share(term),
+                r#type: r#type.clone(),
+            }
+        }
+        | term::Term::VariantCase { variant, arms } => {
+            Term::VariantCase {
+                variant: share(variant),
+                arms: arms
+                    .iter()
+                    .map(|arm| Arm {
+                        label: arm.label.clone(),
+                        hint: arm.hint.clone(),
+                        body: share(arm.body),
+                    })
+                    .collect(),
+            }
+        }
+        | term::Term::Fix(term) => Term::Fix(share(term)),
+        }
+    }
+
+    /// Allocates this term and its subterms in `arena`.
+    pub fn to_arena<'a>(&self, arena: &'a Arena<term::Term<'a>>) -> &'a term::Term<'a> {
+        self._to_arena(arena, &mut HashMap::new())
+    }
+
+    fn _to_arena<'a>(&self, arena: &'a Arena<term::Term<'a>>, cache: &mut HashMap<*const Term, &'a term::Term<'a>>) -> &'a term::Term<'a> {
+        let mut share = |term: &Arc<Term>| -> &'a term::Term<'a> {
+            if let Some(term) = cache.get(&Arc::as_ptr(term)) {
+                return term;
+            }
+            let allocated = term._to_arena(arena, cache);
+            cache.insert(Arc::as_ptr(term), allocated);
+            allocated
+        };
+        let term = match self {
+        | Term::Bool(bool) => term::Term::Bool(*bool),
+        | Term::If { r#if, then, r#else } => {
+            term::Term::If {
+                r#if: share(r#if),
+                then: share(then),
+                r#else: share(r#else),
+            }
+        }
+        | Term::Var(index) => term::Term::Var(*index),
+        | Term::Abs { hint, r#type, body } => {
+            term::Term::Abs {
+                hint: hint.clone(),
+                r#type: r#type.clone(),
+                body: share(body),
+            }
+        }
+        | Term::App { fun, arg } => {
+            term::Term::App {
+                fun: share(fun),
+                arg: share(arg),
+            }
+        }
+        | Term::Asc { term, r#type } => {
+            term::Term::Asc {
+                term: share(term),
+                r#type: r#type.clone(),
+            }
+        }
+        | Term::Let { hint, arg, body } => {
+            term::Term::Let {
+                hint: hint.clone(),
+                arg: share(arg),
+                body: share(body),
+            }
+        }
+        | Term::Tuple(terms) => term::Term::Tuple(terms.iter().map(&mut share).collect()),
+        | Term::TupleProject { tuple, index } => {
+            term::Term::TupleProject {
+                tuple: share(tuple),
+                index: *index,
+            }
+        }
+        | Term::Record(terms) => {
+            term::Term::Record(terms
+                .iter()
+                .map(|(label, term)| (label.clone(), share(term)))
+                .collect())
+        }
+        | Term::RecordProject { record, label } => {
+            term::Term::RecordProject {
+                record: share(record),
+                label: label.clone(),
+            }
+        }
+        | Term::Unit => term::Term::Unit,
+        | Term::Seq { first, second } => {
+            term::Term::Seq {
+                first: share(first),
+                second: share(second),
+            }
+        }
+        | Term::Inl { term, r#type } => {
+            term::Term::Inl {
+                term: share(term),
+                r#type: r#type.clone(),
+            }
+        }
+        | Term::Inr { term, r#type } => {
+            term::Term::Inr {
+                term: share(term),
+                r#type: r#type.clone(),
+            }
+        }
+        | Term::Case { sum, inl_hint, inl, inr_hint, inr } => {
+            term::Term::Case {
+                sum: share(sum),
+                inl_hint: inl_hint.clone(),
+                inl: share(inl),
+                inr_hint: inr_hint.clone(),
+                inr: share(inr),
+            }
+        }
+        | Term::Variant { label, term, r#type } => {
+            term::Term::Variant {
+                label: label.clone(),
+                term: share(term),
+                r#type: r#type.clone(),
+            }
+        }
+        | Term::VariantCase { variant, arms } => {
+            term::Term::VariantCase {
+                variant: share(variant),
+                arms: arms
+                    .iter()
+                    .map(|arm| term::Arm {
+                        label: arm.label.clone(),
+                        hint: arm.hint.clone(),
+                        body: share(&arm.body),
+                    })
+                    .collect(),
+            }
+        }
+        | Term::Fix(term) => term::Term::Fix(share(term)),
+        };
+        arena.alloc(term)
+    }
+
+    pub fn check(&self, context: &mut r#type::Context, depth: i64) -> anyhow::Result<Type> {
+        let arena = Arena::new();
+        self.to_arena(&arena).check(context, depth)
+    }
+
+    pub fn eval(&self) -> anyhow::Result<Self> {
+        let arena = Arena::new();
+        self.to_arena(&arena)
+            .eval(&arena)
+            .map(Term::from)
+    }
+
+    pub fn is_value(&self) -> bool {
+        match self {
+        | Term::Bool(_)
+        | Term::Var(_)
+        | Term::Abs { .. }
+        | Term::Unit => true,
+        | Term::Tuple(terms) => terms.iter().all(|term| term.is_value()),
+        | Term::Record(terms) => terms.values().all(|term| term.is_value()),
+        | Term::Inl { term, .. } | Term::Inr { term, .. } | Term::Variant { term, .. } => term.is_value(),
+        | Term::If { .. }
+        | Term::App { .. }
+        | Term::Asc { .. }
+        | Term::Let { .. }
+        | Term::TupleProject { .. }
+        | Term::RecordProject { .. }
+        | Term::Seq { .. }
+        | Term::Case { .. }
+        | Term::VariantCase { .. }
+        | Term::Fix(_) => false,
+        }
+    }
+
+    pub fn write<W: io::Write>(&self, context: &mut Context, writer: &mut W) -> anyhow::Result<()> {
+        let arena = Arena::new();
+        self.to_arena(&arena).write(context, writer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use std::thread;
+
+    use tapl_core::Sexp;
+    use typed_arena::Arena;
+
+    use crate::owned;
+    use crate::r#type;
+    use crate::r#type::Type;
+    use crate::term::Context;
+    use crate::term::Term;
+
+    #[test]
+    fn send_sync() {
+        fn assert<T: Send + Sync>() {}
+        assert::<owned::Term>();
+    }
+
+    /// The owned form round-trips, and checks and evaluates like the arena
+    /// form, including on another thread.
+    #[test]
+    fn round_trip() {
+        let arena = Arena::new();
+        let sexp = "(let x (bool true) (record-project (record (a (tuple (var 0) (bool false))) (b (app (abs y bool (var 0)) (var 0)))) b))"
+            .parse::<Sexp>()
+            .unwrap();
+        let term = &*arena.alloc(Term::from_sexp(&arena, &sexp).unwrap());
+        let owned = owned::Term::from(term);
+        assert_eq!(owned.to_arena(&arena), term);
+        assert_eq!(owned.check(&mut r#type::Context::default(), 0).unwrap(), Type::Bool);
+
+        let value = thread::spawn(move || owned.eval()).join().unwrap().unwrap();
+        assert_eq!(value, owned::Term::from(term.eval(&arena).unwrap()));
+        assert!(value.is_value());
+
+        let mut buffer = Vec::new();
+        value.write(&mut Context::default(), &mut buffer).unwrap();
+        assert_eq!(String::from_utf8(buffer).unwrap(), "true");
+    }
+}
@@ -0,0 +1,836 @@
+use std::iter;
+use std::vec;
+
+use anyhow::anyhow;
+use anyhow::Context as _;
+use indexmap::IndexMap;
+use tapl_core::source;
+use tapl_core::DeBruijn as _;
+use typed_arena::Arena;
+
+use crate::r#type::Type;
+use crate::term::Arm;
+use crate::term::Term;
+
+/// A command of a `.f` file for the book's `fullsimple` implementation.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Command<'a> {
+    /// `t;`, a term to check and evaluate
+    Eval(Term<'a>),
+    /// `x = t;`, which abbreviates `t` as `x` in the commands after it
+    Define(String, Term<'a>),
+    /// `X = T;`, which abbreviates `T` as `X` in the commands after it
+    DefineType(String, Type),
+    /// `x : T;`, which binds `x` as a free variable of type `T` in the
+    /// commands after it
+    Bind(String, Type),
+    /// `X;`, which declares `X` as a base type in the commands after it
+    BindType(String),
+}
+
+/// A binding made by a command, in scope for the commands after it.
+enum Global<'a> {
+    Free(String),
+    Abbreviation(String, &'a Term<'a>),
+    BaseType(String),
+    TypeAbbreviation(String, Type),
+}
+
+const KEYWORDS: [&str; 18] = [
+    "lambda", "if", "then", "else", "let", "in", "as", "true", "false", "Bool", "unit", "Unit", "inl", "inr", "case", "of",
+    "fix", "letrec",
+];
+
+impl<'a> Term<'a> {
+    /// Parses the concrete syntax of closed terms, in which tuples are
+    /// projected from `1`, as in the book. Since `;` ends a command in a
+    /// file, sequences are only written inside parentheses, and `letrec` is
+    /// read as its derived form.
+    ///
+    /// ```text
+    /// t ::= lambda x: T. t | if t then t else t | let x = t in t
+    ///     | letrec x: T = t in t | fix t
+    ///     | t t | t as T | t.i | t.l
+    ///     | {t, ..., t} | {l = t, ...} | (t; ...; t) | x | true | false | unit
+    ///     | inl t as T | inr t as T | case t of inl x ==> t | inr x ==> t
+    ///     | <l = t> as T | case t of <l = x> ==> t | ...
+    ///
+    /// T ::= Bool | Unit | T -> T | T + T | {T, ..., T} | {l: T, ...} | <l: T, ...> | (T) | X
+    /// ```
+    pub fn parse(arena: &'a Arena<Term<'a>>, source: &str) -> anyhow::Result<Self> {
+        let tokens = source::lex(source)
+            .into_iter()
+            .map(|(_, token)| token)
+            .collect();
+        Parser::new(arena, tokens, &[]).parse_all()
+    }
+
+    /// Parses the `;`-terminated commands of a `.f` file. A variable bound by
+    /// `x : T;` is free in the commands after it, with the most recent
+    /// binding at index 0, a type `X` declared by `X;` is a base type, and
+    /// abbreviations of terms and types are replaced by what they stand for.
+    pub fn parse_file(arena: &'a Arena<Term<'a>>, source: &str) -> Vec<anyhow::Result<Command<'a>>> {
+        let mut globals = Vec::new();
+        source::commands(source)
+            .into_iter()
+            .map(|source::Command { line, tokens }| {
+                command(arena, &mut globals, tokens)
+                    .with_context(|| format!("Command on line {}", line))
+            })
+            .collect()
+    }
+}
+
+fn command<'a>(arena: &'a Arena<Term<'a>>, globals: &mut Vec<Global<'a>>, tokens: Vec<&str>) -> anyhow::Result<Command<'a>> {
+    match tokens.as_slice() {
+    | [name, "=", ..] if is_name(name) => {
+        let term = Parser::new(arena, tokens[2..].to_vec(), globals).parse_all()?;
+        globals.push(Global::Abbreviation(name.to_string(), arena.alloc(term.clone())));
+        Ok(Command::Define(name.to_string(), term))
+    }
+    | [name, "=", ..] if is_type_name(name) => {
+        let mut parser = Parser::new(arena, tokens[2..].to_vec(), globals);
+        let r#type = parser.r#type()?;
+        parser.end()?;
+        globals.push(Global::TypeAbbreviation(name.to_string(), r#type.clone()));
+        Ok(Command::DefineType(name.to_string(), r#type))
+    }
+    | [name, ":", ..] if is_name(name) => {
+        let mut parser = Parser::new(arena, tokens[2..].to_vec(), globals);
+        let r#type = parser.r#type()?;
+        parser.end()?;
+        globals.push(Global::Free(name.to_string()));
+        Ok(Command::Bind(name.to_string(), r#type))
+    }
+    | [name] if is_type_name(name) => {
+        globals.push(Global::BaseType(name.to_string()));
+        Ok(Command::BindType(name.to_string()))
+    }
+    | _ => Parser::new(arena, tokens, globals).parse_all().map(Command::Eval),
+    }
+}
+
+struct Parser<'a, 's, 'g> {
+    arena: &'a Arena<Term<'a>>,
+    tokens: iter::Peekable<vec::IntoIter<&'s str>>,
+    /// Names bound by enclosing binders, from the outermost in
+    bound: Vec<&'s str>,
+    globals: &'g [Global<'a>],
+}
+
+impl<'a, 's, 'g> Parser<'a, 's, 'g> {
+    fn new(arena: &'a Arena<Term<'a>>, tokens: Vec<&'s str>, globals: &'g [Global<'a>]) -> Self {
+        Parser {
+            arena,
+            tokens: tokens.into_iter().peekable(),
+            bound: Vec::new(),
+            globals,
+        }
+    }
+
+    fn parse_all(mut self) -> anyhow::Result<Term<'a>> {
+        let term = self.term()?;
+        self.end()?;
+        Ok(term)
+    }
+
+    fn end(&mut self) -> anyhow::Result<()> {
+        match self.tokens.next() {
+        | None => Ok(()),
+        | Some(token) => Err(anyhow!("Unexpected token after term: {}", token)),
+        }
+    }
+
+    fn term(&mut self) -> anyhow::Result<Term<'a>> {
+        match self.tokens.peek() {
+        | Some(&"lambda") => self.abs(),
+        | Some(&"if") => self.r#if(),
+        | Some(&"let") => self.r#let(),
+        | Some(&"letrec") => self.letrec(),
+        | Some(&"inl") | Some(&"inr") => self.inject(),
+        | Some(&"<") => self.variant(),
+        | Some(&"case") => self.case(),
+        | _ => self.app(),
+        }
+    }
+
+    fn abs(&mut self) -> anyhow::Result<Term<'a>> {
+        self.expect("lambda")?;
+        let name = self.name()?;
+        self.expect(":")?;
+        let r#type = self.r#type()?;
+        self.expect(".")?;
+        let body = self.bind(name)?;
+        Ok(Term::Abs {
+            hint: name.to_owned(),
+            r#type,
+            body: self.arena.alloc(body),
+        })
+    }
+
+    fn r#if(&mut self) -> anyhow::Result<Term<'a>> {
+        self.expect("if")?;
+        let r#if = self.term()?;
+        self.expect("then")?;
+        let then = self.term()?;
+        self.expect("else")?;
+        let r#else = self.term()?;
+        Ok(Term::If {
+            r#if: self.arena.alloc(r#if),
+            then: self.arena.alloc(then),
+            r#else: self.arena.alloc(r#else),
+        })
+    }
+
+    fn r#let(&mut self) -> anyhow::Result<Term<'a>> {
+        self.expect("let")?;
+        let name = self.name()?;
+        self.expect("=")?;
+        let arg = self.term()?;
+        self.expect("in")?;
+        let body = self.bind(name)?;
+        Ok(Term::Let {
+            hint: name.to_owned(),
+            arg: self.arena.alloc(arg),
+            body: self.arena.alloc(body),
+        })
+    }
+
+    /// `letrec x: T = t₁ in t₂`, with `x` in scope in both `t₁` and `t₂`.
+    fn letrec(&mut self) -> anyhow::Result<Term<'a>> {
+        self.expect("letrec")?;
+        let name = self.name()?;
+        self.expect(":")?;
+        let r#type = self.r#type()?;
+        self.expect("=")?;
+        let arg = self.bind(name)?;
+        self.expect("in")?;
+        let body = self.bind(name)?;
+        Ok(Term::derived_letrec(self.arena, name, r#type, self.arena.alloc(arg), self.arena.alloc(body)))
+    }
+
+    /// An injection, whose ascription is part of its syntax.
+    fn inject(&mut self) -> anyhow::Result<Term<'a>> {
+        let inject = self.next()?;
+        let term = self.path()?;
+        self.expect("as")?;
+        let r#type = self.r#type()?;
+        let term = self.arena.alloc(term);
+        match inject {
+        | "inl" => Ok(Term::Inl { term, r#type }),
+        | _ => Ok(Term::Inr { term, r#type }),
+        }
+    }
+
+    /// A variant, whose ascription is part of its syntax.
+    fn variant(&mut self) -> anyhow::Result<Term<'a>> {
+        self.expect("<")?;
+        let label = self.name()?;
+        self.expect("=")?;
+        let term = self.term()?;
+        self.expect(">")?;
+        self.expect("as")?;
+        Ok(Term::Variant {
+            label: label.to_owned(),
+            term: self.arena.alloc(term),
+            r#type: self.r#type()?,
+        })
+    }
+
+    /// A case on a sum or a variant, whose arms other than the last extend
+    /// as far right as possible, so a case nested in them must be
+    /// parenthesized.
+    fn case(&mut self) -> anyhow::Result<Term<'a>> {
+        self.expect("case")?;
+        let sum = self.term()?;
+        self.expect("of")?;
+        if self.tokens.peek() == Some(&"<") {
+            return self.variant_case(sum);
+        }
+        self.expect("inl")?;
+        let inl_hint = self.name()?;
+        self.expect("==>")?;
+        let inl = self.bind(inl_hint)?;
+        self.expect("|")?;
+        self.expect("inr")?;
+        let inr_hint = self.name()?;
+        self.expect("==>")?;
+        let inr = self.bind(inr_hint)?;
+        Ok(Term::Case {
+            sum: self.arena.alloc(sum),
+            inl_hint: inl_hint.to_owned(),
+            inl: self.arena.alloc(inl),
+            inr_hint: inr_hint.to_owned(),
+            inr: self.arena.alloc(inr),
+        })
+    }
+
+    /// The arms `<l = x> ==> t | ...` of a case on `variant`, in order.
+    fn variant_case(&mut self, variant: Term<'a>) -> anyhow::Result<Term<'a>> {
+        let mut arms = Vec::new();
+        loop {
+            self.expect("<")?;
+            let label = self.name()?;
+            self.expect("=")?;
+            let hint = self.name()?;
+            self.expect(">")?;
+            self.expect("==>")?;
+            let body = self.bind(hint)?;
+            arms.push(Arm {
+                label: label.to_owned(),
+                hint: hint.to_owned(),
+                body: self.arena.alloc(body),
+            });
+            if self.tokens.peek() != Some(&"|") {
+                return Ok(Term::VariantCase {
+                    variant: self.arena.alloc(variant),
+                    arms,
+                });
+            }
+            self.expect("|")?;
+        }
+    }
+
+    /// Parses the rest of a term with `name` in scope.
+    fn bind(&mut self, name: &'s str) -> anyhow::Result<Term<'a>> {
+        self.bound.push(name);
+        let term = self.term();
+        self.bound.pop();
+        term
+    }
+
+    /// Sequencing is right associative, and binds more loosely than any
+    /// other term.
+    fn seq(&mut self) -> anyhow::Result<Term<'a>> {
+        let first = self.term()?;
+        if self.tokens.peek() == Some(&";") {
+            self.expect(";")?;
+            return Ok(Term::Seq {
+                first: self.arena.alloc(first),
+                second: self.arena.alloc(self.seq()?),
+            });
+        }
+        Ok(first)
+    }
+
+    /// Application is left associative, and an abstraction, conditional,
+    /// let binding, injection, variant, or case in argument position extends
+    /// as far right as possible.
+    fn app(&mut self) -> anyhow::Result<Term<'a>> {
+        let mut fun = self.fix()?;
+        loop {
+            let arg = match self.tokens.peek() {
+            | Some(&"lambda") | Some(&"if") | Some(&"let") | Some(&"inl") | Some(&"inr") | Some(&"<") | Some(&"case") => self.term()?,
+            | Some(&token) if ["(", "{", "true", "false", "unit"].contains(&token) || is_name(token) => self.asc()?,
+            | _ => return Ok(fun),
+            };
+            fun = Term::App {
+                fun: self.arena.alloc(fun),
+                arg: self.arena.alloc(arg),
+            };
+        }
+    }
+
+    /// `fix` takes a single argument, so only appears in function position.
+    fn fix(&mut self) -> anyhow::Result<Term<'a>> {
+        if self.tokens.peek() == Some(&"fix") {
+            self.expect("fix")?;
+            return Ok(Term::Fix(self.arena.alloc(self.asc()?)));
+        }
+        self.asc()
+    }
+
+    fn asc(&mut self) -> anyhow::Result<Term<'a>> {
+        let term = self.path()?;
+        if self.tokens.peek() == Some(&"as") {
+            self.expect("as")?;
+            return Ok(Term::Asc {
+                term: self.arena.alloc(term),
+                r#type: self.r#type()?,
+            });
+        }
+        Ok(term)
+    }
+
+    fn path(&mut self) -> anyhow::Result<Term<'a>> {
+        let mut term = self.atom()?;
+        while self.tokens.peek() == Some(&".") {
+            self.expect(".")?;
+            term = match self.next()? {
+            | label if is_name(label) => {
+                Term::RecordProject {
+                    record: self.arena.alloc(term),
+                    label: label.to_owned(),
+                }
+            }
+            | index => {
+                match index.parse::<usize>() {
+                | Ok(index) if index > 0 => {
+                    Term::TupleProject {
+                        tuple: self.arena.alloc(term),
+                        index: index - 1,
+                    }
+                }
+                | _ => return Err(anyhow!("Expected label or tuple index from 1, but found {}", index)),
+                }
+            }
+            };
+        }
+        Ok(term)
+    }
+
+    fn atom(&mut self) -> anyhow::Result<Term<'a>> {
+        match self.next()? {
+        | "true" => Ok(Term::Bool(true)),
+        | "false" => Ok(Term::Bool(false)),
+        | "unit" => Ok(Term::Unit),
+        | "(" => {
+            let term = self.seq()?;
+            self.expect(")")?;
+            Ok(term)
+        }
+        | "{" => {
+            if self.labeled()? {
+                let terms = self.fields("=", "}", |parser| {
+                    let term = parser.term()?;
+                    Ok(&*parser.arena.alloc(term))
+                })?;
+                Ok(Term::Record(terms))
+            } else {
+                let terms = self.sequence("}", |parser| {
+                    let term = parser.term()?;
+                    Ok(&*parser.arena.alloc(term))
+                })?;
+                Ok(Term::Tuple(terms))
+            }
+        }
+        | name if is_name(name) => self.var(name),
+        | token => Err(anyhow!("Unexpected token: {}", token)),
+        }
+    }
+
+    /// The variable `name`, bound either by an enclosing binder or by an
+    /// earlier command.
+    fn var(&self, name: &str) -> anyhow::Result<Term<'a>> {
+        if let Some(index) = self.bound.iter().rev().position(|bound| *bound == name) {
+            return Ok(Term::Var(index as i64));
+        }
+
+        let depth = self.bound.len() as i64;
+        let mut free = 0;
+        for global in self.globals.iter().rev() {
+            match global {
+            | Global::Free(bound) if bound == name => return Ok(Term::Var(depth + free)),
+            | Global::Free(_) => free += 1,
+            | Global::Abbreviation(bound, term) if bound == name => return Ok(term.shift(self.arena, depth + free)),
+            | _ => (),
+            }
+        }
+
+        Err(anyhow!("Unbound variable: {}", name))
+    }
+
+    /// Function types, which are right associative.
+    fn r#type(&mut self) -> anyhow::Result<Type> {
+        let from = self.sum_type()?;
+        if self.tokens.peek() == Some(&"->") {
+            self.expect("->")?;
+            return Ok(Type::Fun(Box::new(from), Box::new(self.r#type()?)));
+        }
+        Ok(from)
+    }
+
+    /// Sum types, which are left associative, and bind more tightly than
+    /// function types.
+    fn sum_type(&mut self) -> anyhow::Result<Type> {
+        let mut left = self.atom_type()?;
+        while self.tokens.peek() == Some(&"+") {
+            self.expect("+")?;
+            left = Type::Sum(Box::new(left), Box::new(self.atom_type()?));
+        }
+        Ok(left)
+    }
+
+    fn atom_type(&mut self) -> anyhow::Result<Type> {
+        match self.next()? {
+        | "Bool" => Ok(Type::Bool),
+        | "Unit" => Ok(Type::Unit),
+        | "(" => {
+            let r#type = self.r#type()?;
+            self.expect(")")?;
+            Ok(r#type)
+        }
+        | "{" => {
+            if self.labeled()? {
+                self.fields(":", "}", Self::r#type).map(Type::Record)
+            } else {
+                self.sequence("}", Self::r#type).map(Type::Tuple)
+            }
+        }
+        | "<" => self.fields(":", ">", Self::r#type).map(Type::Variant),
+        | name if is_type_name(name) => {
+            self.globals
+                .iter()
+                .rev()
+                .find_map(|global| match global {
+                | Global::BaseType(bound) if bound == name => Some(Type::Base(name.to_owned())),
+                | Global::TypeAbbreviation(bound, r#type) if bound == name => Some(r#type.clone()),
+                | _ => None,
+                })
+                .ok_or_else(|| anyhow!("Unknown type: {}", name))
+        }
+        | token => Err(anyhow!("Expected type, but found {}", token)),
+        }
+    }
+
+    /// Whether the braces just opened hold labeled fields rather than the
+    /// elements of a tuple. Empty braces are an empty record.
+    fn labeled(&mut self) -> anyhow::Result<bool> {
+        let mut lookahead = self.tokens.clone();
+        match (lookahead.next(), lookahead.next()) {
+        | (Some("}"), _) => Ok(true),
+        | (Some(label), Some("=")) | (Some(label), Some(":")) => Ok(is_name(label)),
+        | (Some(_), _) => Ok(false),
+        | (None, _) => Err(anyhow!("Unexpected end of input")),
+        }
+    }
+
+    /// The fields `l <separator> x, ...` up to `close`, in order.
+    fn fields<T, F>(&mut self, separator: &str, close: &str, mut item: F) -> anyhow::Result<IndexMap<String, T>>
+    where
+        F: FnMut(&mut Self) -> anyhow::Result<T>,
+    {
+        let mut fields = IndexMap::new();
+        let items = self.sequence(close, |parser| {
+            let label = parser.name()?;
+            parser.expect(separator)?;
+            Ok((label, item(parser)?))
+        })?;
+        for (label, item) in items {
+            if fields.insert(label.to_owned(), item).is_some() {
+                return Err(anyhow!("Duplicate label: {}", label));
+            }
+        }
+        Ok(fields)
+    }
+
+    /// Comma-separated items up to `close`.
+    fn sequence<T, F>(&mut self, close: &str, mut item: F) -> anyhow::Result<Vec<T>>
+    where
+        F: FnMut(&mut Self) -> anyhow::Result<T>,
+    {
+        let mut items = Vec::new();
+        if self.tokens.peek() == Some(&close) {
+            self.expect(close)?;
+            return Ok(items);
+        }
+        loop {
+            items.push(item(self)?);
+            match self.next()? {
+            | "," => (),
+            | token if token == close => return Ok(items),
+            | token => return Err(anyhow!("Expected , or {}, but found {}", close, token)),
+            }
+        }
+    }
+
+    fn name(&mut self) -> anyhow::Result<&'s str> {
+        match self.next()? {
+        | token if is_name(token) => Ok(token),
+        | token => Err(anyhow!("Expected variable, but found {}", token)),
+        }
+    }
+
+    fn next(&mut self) -> anyhow::Result<&'s str> {
+        self.tokens
+            .next()
+            .ok_or_else(|| anyhow!("Unexpected end of input"))
+    }
+
+    fn expect(&mut self, expected: &str) -> anyhow::Result<()> {
+        match self.next()? {
+        | token if token == expected => Ok(()),
+        | token => Err(anyhow!("Expected {}, but found {}", expected, token)),
+        }
+    }
+}
+
+/// Variables and labels start with a lowercase letter or an underscore.
+fn is_name(token: &str) -> bool {
+    !KEYWORDS.contains(&token) && token.starts_with(|char: char| char.is_lowercase() || char == '_')
+}
+
+/// Types start with an uppercase letter.
+fn is_type_name(token: &str) -> bool {
+    !KEYWORDS.contains(&token) && token.starts_with(char::is_uppercase)
+}
+
+#[cfg(test)]
+mod tests {
+
+    use tapl_core::Sexp;
+    use typed_arena::Arena;
+
+    use crate::parse::Command;
+    use crate::r#type;
+    use crate::r#type::Type;
+    use crate::term::Term;
+
+    #[test]
+    fn parse() {
+        let arena = Arena::new();
+        let term = Term::parse(&arena, "
+            let r = {x = {true, lambda b: Bool. b}, y = false} in
+            if r.y then false else r.x.2 true as Bool
+        ").unwrap();
+        let sexp = "
+            (let r (record (x (tuple (bool true) (abs b bool (var 0)))) (y (bool false)))
+                (if (record-project (var 0) y)
+                    (bool false)
+                    (app (tuple-project (record-project (var 0) x) 1) (as (bool true) bool))))
+        ".parse::<Sexp>().unwrap();
+        assert_eq!(term, Term::from_sexp(&arena, &sexp).unwrap());
+        assert!(Term::parse(&arena, "{true}.0").is_err());
+        assert!(Term::parse(&arena, "{x = true, x = false}").is_err());
+    }
+
+    /// The `term!` macro reads the same terms as the parser, in its own
+    /// concrete syntax, given the last command of each file.
+    #[test]
+    fn macros() {
+        let arena = Arena::new();
+        let pairs = [
+            (crate::term!(arena, λu: Unit. (u; unit; true)), "lambda u: Unit. (u; unit; true);"),
+            (crate::term!(arena, ((λu: Unit. u) unit, (unit; unit))), "{(lambda u: Unit. u) unit, (unit; unit)};"),
+            (crate::term!(arena, λf: A -> A. λx: A. f (f x)), "A; lambda f: A -> A. lambda x: A. f (f x);"),
+            (
+                crate::term!(arena, case inr unit as Bool + Unit of inl x => x | inr y => (y; false)),
+                "case inr unit as Bool + Unit of inl x ==> x | inr y ==> (y; false);",
+            ),
+            (
+                crate::term!(arena, case <some = true> as <none: Unit, some: Bool> of <none = u> => false | <some = b> => b),
+                "case <some = true> as <none: Unit, some: Bool> of <none = u> ==> false | <some = b> ==> b;",
+            ),
+            (
+                crate::term!(arena, letrec f: Bool -> bool = λb: Bool. f b in fix (λx: bool. f x) as Bool),
+                "letrec f: Bool -> Bool = lambda b: Bool. f b in fix (lambda x: Bool. f x) as Bool;",
+            ),
+        ];
+        for (term, source) in &pairs {
+            match Term::parse_file(&arena, source).pop() {
+            | Some(Ok(Command::Eval(expected))) => assert_eq!(term, &expected, "{}", source),
+            | command => panic!("Expected term, but found {:?}", command),
+            }
+        }
+    }
+
+    /// The examples from the `test.f` file of the book's `fullsimple`
+    /// implementation that only use the features of this chapter, together
+    /// with some that use other features and are reported without stopping
+    /// the rest of the file from being read.
+    #[test]
+    fn file() {
+        let arena = Arena::new();
+        let source = "
+            /* Examples for testing */
+
+            \"hello\";
+
+            let x=true in x;
+
+            {x=true, y=false};
+            {x=true, y=false}.x;
+            {true, false};
+            {true, false}.1;
+
+            if true then {x=true,y=false,a=false} else {y=false,x={},a=false};
+
+            T = Bool->Bool;
+            f = lambda f:T. lambda x:Bool. f (f x);
+            f (lambda b:Bool. if b then false else true) false;
+        ";
+        let commands = Term::parse_file(&arena, source);
+        assert_eq!(commands.len(), 10);
+        assert_eq!(format!("{:#}", commands[0].as_ref().unwrap_err()), "Command on line 4: Unexpected token: \"hello\"");
+
+        let types = commands[1..]
+            .iter()
+            .filter_map(|command| match command.as_ref().unwrap() {
+            | Command::Eval(term) => Some(term.check(&mut r#type::Context::default(), 0).map_err(|error| error.to_string())),
+            | _ => None,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(types[..5], [
+            Ok(Type::Bool),
+            Ok(Type::Record(vec![(String::from("x"), Type::Bool), (String::from("y"), Type::Bool)].into_iter().collect())),
+            Ok(Type::Bool),
+            Ok(Type::Tuple(vec![Type::Bool, Type::Bool])),
+            Ok(Type::Bool),
+        ]);
+        assert!(types[5].is_err());
+
+        match commands[9].as_ref().unwrap() {
+        | Command::Eval(term) => assert_eq!(arena.alloc(term.clone()).eval(&arena).unwrap(), &Term::Bool(false)),
+        | command => panic!("Expected term, but found {:?}", command),
+        }
+    }
+
+    /// Base types are opaque, so a term over them can only be applied to the
+    /// free variables declared in the initial typing context, which a failed
+    /// check under a binder leaves as it was.
+    #[test]
+    fn base() {
+        let arena = Arena::new();
+        let source = "
+            A;
+            twice = lambda f: A -> A. lambda x: A. f (f x);
+            twice;
+            a : A;
+            f : A -> A;
+            lambda x: Bool. x x;
+            twice f a;
+            f true;
+            let y = a in y y;
+            f a;
+            lambda b: B. b;
+        ";
+        let mut context = r#type::Context::default();
+        let mut terms = Vec::new();
+        let mut types = Vec::new();
+        for command in Term::parse_file(&arena, source) {
+            match command {
+            | Ok(Command::Eval(term)) => {
+                let depth = context.depth();
+                types.push(term.check(&mut context, depth).map_err(|error| error.to_string()));
+                terms.push(term);
+            }
+            | Ok(Command::Bind(_, r#type)) => context.declare(r#type),
+            | Ok(_) => (),
+            | Err(error) => types.push(Err(format!("{:#}", error))),
+            }
+        }
+
+        let a = || Box::new(Type::Base(String::from("A")));
+        assert_eq!(types, [
+            Ok(Type::Fun(Box::new(Type::Fun(a(), a())), Box::new(Type::Fun(a(), a())))),
+            Err(String::from("Function type expected")),
+            Ok(*a()),
+            Err(String::from("Parameter type mismatch")),
+            Err(String::from("Function type expected")),
+            Ok(*a()),
+            Err(String::from("Command on line 12: Unknown type: B")),
+        ]);
+
+        // `f (f a)` is stuck, with `f` at index 0 and `a` at index 1
+        let f = &*arena.alloc(Term::Var(0));
+        let expected = Term::App { fun: f, arg: arena.alloc(Term::App { fun: f, arg: arena.alloc(Term::Var(1)) }) };
+        assert_eq!(arena.alloc(terms[2].clone()).eval(&arena).unwrap(), &expected);
+    }
+
+    /// The address example of §11.9, over a base type of strings, and a
+    /// variable bound outside a case that is substituted into its arms.
+    #[test]
+    fn sum() {
+        let arena = Arena::new();
+        let source = "
+            String;
+            PhysicalAddr = {firstlast: String, addr: String};
+            VirtualAddr = {name: String, email: String};
+            Addr = PhysicalAddr + VirtualAddr;
+            getName = lambda a: Addr. case a of inl x ==> x.firstlast | inr y ==> y.name;
+            name : String;
+            email : String;
+            getName (inr {name = name, email = email} as Addr);
+            (lambda y: Bool. case inl y as Bool + Unit of inl x ==> y | inr x ==> false) true;
+            inl true as Bool;
+            lambda s: Bool + Unit. case s of inl x ==> x | inr x ==> x;
+        ";
+        let mut context = r#type::Context::default();
+        let mut types = Vec::new();
+        let mut values = Vec::new();
+        for command in Term::parse_file(&arena, source) {
+            match command.unwrap() {
+            | Command::Eval(term) => {
+                let depth = context.depth();
+                types.push(term.check(&mut context, depth).map_err(|error| error.to_string()));
+                values.push(arena.alloc(term).eval(&arena).unwrap());
+            }
+            | Command::Bind(_, r#type) => context.declare(r#type),
+            | _ => (),
+            }
+        }
+        assert_eq!(types, [
+            Ok(Type::Base(String::from("String"))),
+            Ok(Type::Bool),
+            Err(String::from("Annotation of inl not a sum type")),
+            Err(String::from("Arms of case have different types")),
+        ]);
+        assert_eq!(values[..2], [&Term::Var(1), &Term::Bool(true)]);
+    }
+
+    /// The examples of §11.10, with an optional boolean in place of an
+    /// optional number, and the arms the type checker rejects.
+    #[test]
+    fn variant() {
+        let arena = Arena::new();
+        let source = "
+            OptionalBool = <none: Unit, some: Bool>;
+            Table = Bool -> OptionalBool;
+            emptyTable = lambda n: Bool. <none = unit> as OptionalBool;
+            extendTable =
+                lambda t: Table. lambda m: Bool. lambda v: Bool. lambda n: Bool.
+                    if (if n then m else if m then false else true) then <some = v> as OptionalBool else t n;
+            t = extendTable emptyTable true false;
+            case t true of <none = u> ==> true | <some = v> ==> v;
+            case t false of <none = u> ==> true | <some = v> ==> v;
+
+            Weekday = <monday: Unit, tuesday: Unit, wednesday: Unit, thursday: Unit, friday: Unit>;
+            nextBusinessDay = lambda w: Weekday.
+                case w of <monday = x> ==> <tuesday = unit> as Weekday
+                        | <tuesday = x> ==> <wednesday = unit> as Weekday
+                        | <wednesday = x> ==> <thursday = unit> as Weekday
+                        | <thursday = x> ==> <friday = unit> as Weekday
+                        | <friday = x> ==> <monday = unit> as Weekday;
+            nextBusinessDay (<friday = unit> as Weekday);
+
+            <maybe = true> as OptionalBool;
+            case t true of <none = u> ==> true | <maybe = v> ==> v;
+            case t true of <none = u> ==> true | <some = v> ==> v | <none = w> ==> false;
+            case t true of <some = v> ==> v;
+        ";
+        let mut types = Vec::new();
+        let mut values = Vec::new();
+        for command in Term::parse_file(&arena, source) {
+            if let Command::Eval(term) = command.unwrap() {
+                let r#type = term.check(&mut r#type::Context::default(), 0).map_err(|error| error.to_string());
+                if r#type.is_ok() {
+                    values.push(arena.alloc(term).eval(&arena).unwrap());
+                }
+                types.push(r#type);
+            }
+        }
+
+        let weekday = Type::Variant(["monday", "tuesday", "wednesday", "thursday", "friday"]
+            .iter()
+            .map(|label| (label.to_string(), Type::Unit))
+            .collect());
+        assert_eq!(types, [
+            Ok(Type::Bool),
+            Ok(Type::Bool),
+            Ok(weekday.clone()),
+            Err(String::from("Label maybe not in variant type <none: unit, some: bool>")),
+            Err(String::from("Label maybe not in variant type <none: unit, some: bool>")),
+            Err(String::from("Duplicate case arm for label none")),
+            Err(String::from("Missing case arms for labels none")),
+        ]);
+        assert_eq!(values, [
+            &Term::Bool(false),
+            &Term::Bool(true),
+            &Term::Variant {
+                label: String::from("monday"),
+                term: arena.alloc(Term::Unit),
+                r#type: weekday,
+            },
+        ]);
+    }
+}
@@ -0,0 +1,342 @@
+use std::iter;
+
+use anyhow::anyhow;
+use indexmap::IndexMap;
+use tapl_core::sexp;
+use tapl_core::Sexp;
+use typed_arena::Arena;
+
+use crate::r#type::Type;
+use crate::term::Arm;
+use crate::term::Term;
+
+impl Type {
+    /// Writes this type as an S-expression.
+    ///
+    /// ```text
+    /// T ::= bool | (fun T T) | (tuple T...) | (record (l T)...) | unit
+    ///     | (base X) | (sum T T) | (variant (l T)...)
+    /// ```
+    pub fn to_sexp(&self) -> Sexp {
+        match self {
+        | Type::Bool => Sexp::atom("bool"),
+        | Type::Fun(from, to) => Sexp::form("fun", vec![from.to_sexp(), to.to_sexp()]),
+        | Type::Tuple(types) => Sexp::form("tuple", types.iter().map(Type::to_sexp)),
+        | Type::Record(types) => Sexp::form("record", types.iter().map(|(label, r#type)| field(label, r#type.to_sexp()))),
+        | Type::Unit => Sexp::atom("unit"),
+        | Type::Base(name) => Sexp::form("base", vec![Sexp::atom(name.as_str())]),
+        | Type::Sum(left, right) => Sexp::form("sum", vec![left.to_sexp(), right.to_sexp()]),
+        | Type::Variant(types) => Sexp::form("variant", types.iter().map(|(label, r#type)| field(label, r#type.to_sexp()))),
+        }
+    }
+
+    /// Reads a type written by `to_sexp`.
+    pub fn from_sexp(sexp: &Sexp) -> anyhow::Result<Self> {
+        if let Sexp::Atom(atom) = sexp {
+            return match atom.as_str() {
+            | "bool" => Ok(Type::Bool),
+            | "unit" => Ok(Type::Unit),
+            | _ => Err(anyhow!("Unknown type: {}", atom)),
+            };
+        }
+        match sexp.as_form()? {
+        | ("fun", tail) => {
+            let [from, to] = sexp::arguments("fun", tail)?;
+            Ok(Type::Fun(Box::new(Type::from_sexp(from)?), Box::new(Type::from_sexp(to)?)))
+        }
+        | ("tuple", tail) => {
+            tail.iter()
+                .map(Type::from_sexp)
+                .collect::<anyhow::Result<_>>()
+                .map(Type::Tuple)
+        }
+        | ("record", tail) => fields(tail, Type::from_sexp).map(Type::Record),
+        | ("base", tail) => {
+            let [name] = sexp::arguments("base", tail)?;
+            Ok(Type::Base(name.as_atom()?.to_owned()))
+        }
+        | ("sum", tail) => {
+            let [left, right] = sexp::arguments("sum", tail)?;
+            Ok(Type::Sum(Box::new(Type::from_sexp(left)?), Box::new(Type::from_sexp(right)?)))
+        }
+        | ("variant", tail) => fields(tail, Type::from_sexp).map(Type::Variant),
+        | (head, _) => Err(anyhow!("Unknown type form: {}", head)),
+        }
+    }
+}
+
+impl<'a> Term<'a> {
+    /// Writes this term as an S-expression.
+    ///
+    /// ```text
+    /// t ::= (bool true) | (bool false) | (if t t t)
+    ///     | (var i) | (abs x T t) | (app t t)
+    ///     | (as t T) | (let x t t)
+    ///     | (tuple t...) | (tuple-project t i)
+    ///     | (record (l t)...) | (record-project t l)
+    ///     | unit | (seq t t)
+    ///     | (inl t T) | (inr t T) | (case t (inl x t) (inr x t))
+    ///     | (variant l t T) | (variant-case t (l x t)...)
+    ///     | (fix t)
+    /// ```
+    pub fn to_sexp(&self) -> Sexp {
+        match self {
+        | Term::Bool(bool) => Sexp::form("bool", vec![Sexp::atom(bool.to_string())]),
+        | Term::If { r#if, then, r#else } => Sexp::form("if", vec![r#if.to_sexp(), then.to_sexp(), r#else.to_sexp()]),
+        | Term::Var(index) => Sexp::form("var", vec![Sexp::atom(index.to_string())]),
+        | Term::Abs { hint, r#type, body } => {
+            Sexp::form("abs", vec![Sexp::atom(hint.as_str()), r#type.to_sexp(), body.to_sexp()])
+        }
+        | Term::App { fun, arg } => Sexp::form("app", vec![fun.to_sexp(), arg.to_sexp()]),
+        | Term::Asc { term, r#type } => Sexp::form("as", vec![term.to_sexp(), r#type.to_sexp()]),
+        | Term::Let { hint, arg, body } => Sexp::form("let", vec![Sexp::atom(hint.as_str()), arg.to_sexp(), body.to_sexp()]),
+        | Term::Tuple(terms) => Sexp::form("tuple", terms.iter().map(|term| term.to_sexp())),
+        | Term::TupleProject { tuple, index } => {
+            Sexp::form("tuple-project", vec![tuple.to_sexp(), Sexp::atom(index.to_string())])
+        }
+        | Term::Record(terms) => Sexp::form("record", terms.iter().map(|(label, term)| field(label, term.to_sexp()))),
+        | Term::RecordProject { record, label } => {
+            Sexp::form("record-project", vec![record.to_sexp(), Sexp::atom(label.as_str())])
+        }
+        | Term::Unit => Sexp::atom("unit"),
+        | Term::Seq { first, second } => Sexp::form("seq", vec![first.to_sexp(), second.to_sexp()]),
+        | Term::Inl { term, r#type } => Sexp::form("inl", vec![term.to_sexp(), r#type.to_sexp()]),
+        | Term::Inr { term, r#type } => Sexp::form("inr", vec![term.to_sexp(), r#type.to_sexp()]),
+        | Term::Case { sum, inl_hint, inl, inr_hint, inr } => {
+            Sexp::form("case", vec![
+                sum.to_sexp(),
+                Sexp::form("inl", vec![Sexp::atom(inl_hint.as_str()), inl.to_sexp()]),
+                Sexp::form("inr", vec![Sexp::atom(inr_hint.as_str()), inr.to_sexp()]),
+            ])
+        }
+        | Term::Variant { label, term, r#type } => {
+            Sexp::form("variant", vec![Sexp::atom(label.as_str()), term.to_sexp(), r#type.to_sexp()])
+        }
+        | Term::VariantCase { variant, arms } => {
+            Sexp::form("variant-case", iter::once(variant.to_sexp()).chain(arms.iter().map(|arm| {
+                Sexp::form(&arm.label, vec![Sexp::atom(arm.hint.as_str()), arm.body.to_sexp()])
+            })))
+        }
+        | Term::Fix(term) => Sexp::form("fix", vec![term.to_sexp()]),
+        }
+    }
+
+    /// Reads a term written by `to_sexp`, allocating it in `arena`.
+    pub fn from_sexp(arena: &'a Arena<Term<'a>>, sexp: &Sexp) -> anyhow::Result<Self> {
+        let subterm = |sexp: &Sexp| -> anyhow::Result<&'a Term<'a>> {
+            Ok(arena.alloc(Self::from_sexp(arena, sexp)?))
+        };
+        if let Sexp::Atom(atom) = sexp {
+            return match atom.as_str() {
+            | "unit" => Ok(Term::Unit),
+            | _ => Err(anyhow!("Unknown term: {}", atom)),
+            };
+        }
+        match sexp.as_form()? {
+        | ("bool", tail) => {
+            let [bool] = sexp::arguments("bool", tail)?;
+            Ok(Term::Bool(bool.parse_atom()?))
+        }
+        | ("if", tail) => {
+            let [r#if, then, r#else] = sexp::arguments("if", tail)?;
+            Ok(Term::If {
+                r#if: subterm(r#if)?,
+                then: subterm(then)?,
+                r#else: subterm(r#else)?,
+            })
+        }
+        | ("var", tail) => {
+            let [index] = sexp::arguments("var", tail)?;
+            Ok(Term::Var(index.parse_index()?))
+        }
+        | ("abs", tail) => {
+            let [hint, r#type, body] = sexp::arguments("abs", tail)?;
+            Ok(Term::Abs {
+                hint: hint.as_atom()?.to_owned(),
+                r#type: Type::from_sexp(r#type)?,
+                body: subterm(body)?,
+            })
+        }
+        | ("app", tail) => {
+            let [fun, arg] = sexp::arguments("app", tail)?;
+            Ok(Term::App {
+                fun: subterm(fun)?,
+                arg: subterm(arg)?,
+            })
+        }
+        | ("as", tail) => {
+            let [term, r#type] = sexp::arguments("as", tail)?;
+            Ok(Term::Asc {
+                term: subterm(term)?,
+                r#type: Type::from_sexp(r#type)?,
+            })
+        }
+        | ("let", tail) => {
+            let [hint, arg, body] = sexp::arguments("let", tail)?;
+            Ok(Term::Let {
+                hint: hint.as_atom()?.to_owned(),
+                arg: subterm(arg)?,
+                body: subterm(body)?,
+            })
+        }
+        | ("tuple", tail) => {
+            tail.iter()
+                .map(subterm)
+                .collect::<anyhow::Result<_>>()
+                .map(Term::Tuple)
+        }
+        | ("tuple-project", tail) => {
+            let [tuple, index] = sexp::arguments("tuple-project", tail)?;
+            Ok(Term::TupleProject {
+                tuple: subterm(tuple)?,
+                index: index.parse_atom()?,
+            })
+        }
+        | ("record", tail) => fields(tail, subterm).map(Term::Record),
+        | ("record-project", tail) => {
+            let [record, label] = sexp::arguments("record-project", tail)?;
+            Ok(Term::RecordProject {
+                record: subterm(record)?,
+                label: label.as_atom()?.to_owned(),
+            })
+        }
+        | ("seq", tail) => {
+            let [first, second] = sexp::arguments("seq", tail)?;
+            Ok(Term::Seq {
+                first: subterm(first)?,
+                second: subterm(second)?,
+            })
+        }
+        | ("inl", tail) => {
+            let [term, r#type] = sexp::arguments("inl", tail)?;
+            Ok(Term::Inl {
+                term: subterm(term)?,
+                r#type: Type::from_sexp(r#type)?,
+            })
+        }
+        | ("inr", tail) => {
+            let [term, r#type] = sexp::arguments("inr", tail)?;
+            Ok(Term::Inr {
+                term: subterm(term)?,
+                r#type: Type::from_sexp(r#type)?,
+            })
+        }
+        | ("case", tail) => {
+            let [sum, inl, inr] = sexp::arguments("case", tail)?;
+            let (inl_hint, inl) = arm(inl, "inl", subterm)?;
+            let (inr_hint, inr) = arm(inr, "inr", subterm)?;
+            Ok(Term::Case {
+                sum: subterm(sum)?,
+                inl_hint,
+                inl,
+                inr_hint,
+                inr,
+            })
+        }
+        | ("variant", tail) => {
+            let [label, term, r#type] = sexp::arguments("variant", tail)?;
+            Ok(Term::Variant {
+                label: label.as_atom()?.to_owned(),
+                term: subterm(term)?,
+                r#type: Type::from_sexp(r#type)?,
+            })
+        }
+        | ("variant-case", [variant, arms @ ..]) => {
+            Ok(Term::VariantCase {
+                variant: subterm(variant)?,
+                arms: arms
+                    .iter()
+                    .map(|arm| match arm.as_list()? {
+                    | [label, hint, body] => {
+                        Ok(Arm {
+                            label: label.as_atom()?.to_owned(),
+                            hint: hint.as_atom()?.to_owned(),
+                            body: subterm(body)?,
+                        })
+                    }
+                    | _ => Err(anyhow!("Expected variant case arm, but found {}", arm)),
+                    })
+                    .collect::<anyhow::Result<_>>()?,
+            })
+        }
+        | ("fix", tail) => {
+            let [term] = sexp::arguments("fix", tail)?;
+            Ok(Term::Fix(subterm(term)?))
+        }
+        | (head, _) => Err(anyhow!("Unknown term form: {}", head)),
+        }
+    }
+}
+
+/// The field `(l x)` of a record term or type.
+fn field(label: &str, sexp: Sexp) -> Sexp {
+    Sexp::List(vec![Sexp::atom(label), sexp])
+}
+
+/// Reads the arm `(<head> x t)` of a case term.
+fn arm<'a, F>(sexp: &Sexp, head: &str, subterm: F) -> anyhow::Result<(String, &'a Term<'a>)>
+where
+    F: FnOnce(&Sexp) -> anyhow::Result<&'a Term<'a>>,
+{
+    match sexp.as_form()? {
+    | (actual, tail) if actual == head => {
+        let [hint, body] = sexp::arguments(head, tail)?;
+        Ok((hint.as_atom()?.to_owned(), subterm(body)?))
+    }
+    | (actual, _) => Err(anyhow!("Expected {} arm, but found {}", head, actual)),
+    }
+}
+
+/// Reads the fields `(l x)...` of a record term or type, in order.
+fn fields<T, F>(tail: &[Sexp], mut read: F) -> anyhow::Result<IndexMap<String, T>>
+where
+    F: FnMut(&Sexp) -> anyhow::Result<T>,
+{
+    let mut fields = IndexMap::new();
+    for field in tail {
+        match field.as_list()? {
+        | [label, sexp] => {
+            let label = label.as_atom()?.to_owned();
+            if fields.contains_key(&label) {
+                return Err(anyhow!("Duplicate record label: {}", label));
+            }
+            fields.insert(label, read(sexp)?);
+        }
+        | _ => return Err(anyhow!("Expected record field, but found {}", field)),
+        }
+    }
+    Ok(fields)
+}
+
+#[cfg(test)]
+mod tests {
+
+    use tapl_core::Sexp;
+    use typed_arena::Arena;
+
+    use crate::term::Term;
+
+    /// Reading and writing a term gives back the same S-expression.
+    #[test]
+    fn round_trip() {
+        let arena = Arena::new();
+        let sexp = "
+            (let r (record (y (tuple (bool true) (var 0)))
+                           (x (as (abs x (record (b bool) (a (tuple bool (fun bool bool)))) (var 0))
+                                  (fun (record (b bool) (a (tuple bool (fun bool bool))))
+                                       (record (b bool) (a (tuple bool (fun bool bool))))))))
+                 (if (tuple-project (record-project (var 0) y) 0)
+                     (app (var 1) (var 0))
+                     (var 0)))
+        ".parse::<Sexp>().unwrap();
+        assert_eq!(Term::from_sexp(&arena, &sexp).unwrap().to_sexp(), sexp);
+        let sexp = "(case (inl unit (sum unit (base A))) (inl x (var 0)) (inr y (seq unit (var 0))))".parse::<Sexp>().unwrap();
+        assert_eq!(Term::from_sexp(&arena, &sexp).unwrap().to_sexp(), sexp);
+        let sexp = "(variant-case (variant b (bool true) (variant (a unit) (b bool))) (b x (var 0)) (a y (bool false)))".parse::<Sexp>().unwrap();
+        assert_eq!(Term::from_sexp(&arena, &sexp).unwrap().to_sexp(), sexp);
+        assert!(Term::from_sexp(&arena, &"(record (x (bool true)) (x (bool false)))".parse().unwrap()).is_err());
+        assert!(Term::from_sexp(&arena, &"(case (var 0) (inr x (var 0)) (inl y (var 0)))".parse().unwrap()).is_err());
+        assert!(Term::from_sexp(&arena, &"(tuple-project (tuple) x)".parse().unwrap()).is_err());
+        assert!(Term::from_sexp(&arena, &"(var -1)".parse().unwrap()).is_err());
+    }
+}
@@ -1,104 +1,506 @@
+use std::hash::Hash;
+use std::hash::Hasher;
 use std::io;
-use std::iter;
+use std::mem;
+use std::ptr;
 
+use anyhow::anyhow;
 use indexmap::IndexMap;
-use typed_arena::Arena;
+use serde::Serialize;
+use tapl_core::Alloc;
+use tapl_core::DeBruijn;
+use tapl_core::intern::HashCons;
 
 use crate::r#type::Type;
 
-#[derive(Clone, Debug, Default)]
-pub struct Context(Vec<String>);
+pub use tapl_core::Context;
 
-impl Context {
-    pub fn len(&self) -> i64 {
-        self.0.len() as i64
-    }
-
-    pub fn push<'c>(&'c mut self, mut var: String) -> &str {
-        while let Some(_) = self.0.iter().find(|entry| **entry == var) {
-            var.push('\'');
-        }
-        self.0.push(var);
-        self.0.last().unwrap()
-    }
-
-    pub fn pop(&mut self) {
-        self.0.pop();
-    }
-
-    pub fn name(&self, index: i64) -> &str {
-        assert!(index >= 0);
-        if index >= self.len() {
-            match index - self.len() {
-            | 0 => "α",
-            | 1 => "β",
-            | 2 => "ξ",
-            | _ => todo!(),
-            }
-        } else {
-            let index = self.0.len() - 1 - index as usize;
-            &self.0[index]
-        }
-    }
-}
-
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
 pub enum Term<'a> {
+    /// Serialized as `{"Bool": b}`.
     Bool(bool),
+    /// Serialized as `{"If": {"if": t₁, "then": t₂, "else": t₃}}`.
     If {
         r#if: &'a Term<'a>,
         then: &'a Term<'a>,
         r#else: &'a Term<'a>,
     },
-    /// de Bruijn index
+    /// de Bruijn index, serialized as `{"Var": i}`.
     Var(i64),
+    /// Serialized as `{"Abs": {"hint": x, "type": T, "body": t}}`.
     Abs {
         /// Hint for the name of the bound variable
         hint: String,
         r#type: Type,
         body: &'a Term<'a>,
     },
+    /// Serialized as `{"App": {"fun": t₁, "arg": t₂}}`.
     App {
         fun: &'a Term<'a>,
         arg: &'a Term<'a>,
     },
+    /// Serialized as `{"Asc": {"term": t, "type": T}}`.
     Asc {
         term: &'a Term<'a>,
         r#type: Type,
     },
+    /// Serialized as `{"Let": {"hint": x, "arg": t₁, "body": t₂}}`.
     Let {
         hint: String,
         arg: &'a Term<'a>,
         body: &'a Term<'a>,
     },
+    /// Serialized as `{"Tuple": [t₁, ..., tₙ]}`.
     Tuple(Vec<&'a Term<'a>>),
+    /// Serialized as `{"TupleProject": {"tuple": t, "index": i}}`.
     TupleProject {
         tuple: &'a Term<'a>,
         index: usize,
     },
 
+    /// Serialized as `{"Record": {"l₁": t₁, ..., "lₙ": tₙ}}`, in order.
     Record(IndexMap<String, &'a Term<'a>>),
+    /// Serialized as `{"RecordProject": {"record": t, "label": l}}`.
     RecordProject {
         record: &'a Term<'a>,
         label: String,
     },
+
+    /// Serialized as `"Unit"`.
+    Unit,
+    /// Serialized as `{"Seq": {"first": t₁, "second": t₂}}`.
+    Seq {
+        first: &'a Term<'a>,
+        second: &'a Term<'a>,
+    },
+
+    /// Injection into the sum type `type`, serialized as
+    /// `{"Inl": {"term": t, "type": T}}`.
+    Inl {
+        term: &'a Term<'a>,
+        r#type: Type,
+    },
+    /// Serialized as `{"Inr": {"term": t, "type": T}}`.
+    Inr {
+        term: &'a Term<'a>,
+        r#type: Type,
+    },
+    /// Serialized as `{"Case": {"sum": t₀, "inl_hint": x₁, "inl": t₁,
+    /// "inr_hint": x₂, "inr": t₂}}`.
+    Case {
+        sum: &'a Term<'a>,
+        inl_hint: String,
+        inl: &'a Term<'a>,
+        inr_hint: String,
+        inr: &'a Term<'a>,
+    },
+
+    /// Injection into the variant type `type`, serialized as
+    /// `{"Variant": {"label": l, "term": t, "type": T}}`.
+    Variant {
+        label: String,
+        term: &'a Term<'a>,
+        r#type: Type,
+    },
+    /// Serialized as `{"VariantCase": {"variant": t, "arms": [a₁, ..., aₙ]}}`.
+    VariantCase {
+        variant: &'a Term<'a>,
+        arms: Vec<Arm<'a>>,
+    },
+
+    /// Serialized as `{"Fix": t}`.
+    Fix(&'a Term<'a>),
 }
 
-impl<'a> Term<'a> {
-    pub fn eval(&'a self, arena: &'a Arena<Term<'a>>) -> &'a Self {
-        iter::successors(Some(self), |term| {
-                term.step(arena)
-                    .map(|next| &*arena.alloc(next))
+/// The arm `<label = hint> ⇒ body` of a variant case, serialized as
+/// `{"label": l, "hint": x, "body": t}`. Arms are kept in the order they are
+/// written, so that duplicate arms are left for the type checker to reject.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct Arm<'a> {
+    pub label: String,
+    pub hint: String,
+    pub body: &'a Term<'a>,
+}
+
+impl<'a> DeBruijn<'a> for Term<'a> {
+    fn var(index: i64) -> Self {
+        Term::Var(index)
+    }
+
+    fn index(&self) -> Option<i64> {
+        match self {
+        | Term::Var(index) => Some(*index),
+        | _ => None,
+        }
+    }
+
+    fn map<A, F>(&self, arena: &'a A, mut map: F) -> Option<Self>
+    where
+        A: Alloc<'a, Self>,
+        F: FnMut(&'a Self, i64) -> Option<Self>,
+    {
+        match self {
+        | Term::Bool(_) | Term::Var(_) | Term::Unit => None,
+        | Term::If { r#if, then, r#else } => {
+            match (map(r#if, 0), map(then, 0), map(r#else, 0)) {
+            | (None, None, None) => None,
+            | (if_, then_, else_) => {
+                Some(Term::If {
+                    r#if: if_.map_or(*r#if, |r#if| arena.alloc(r#if)),
+                    then: then_.map_or(*then, |then| arena.alloc(then)),
+                    r#else: else_.map_or(*r#else, |r#else| arena.alloc(r#else)),
+                })
+            }
+            }
+        }
+        | Term::Abs { hint, r#type, body } => {
+            Some(Term::Abs {
+                hint: hint.clone(),
+                r#type: r#type.clone(),
+                body: arena.alloc(map(body, 1)?),
+            })
+        }
+        | Term::App { fun, arg } => {
+            match (map(fun, 0), map(arg, 0)) {
+            | (None, None) => None,
+            | (fun_, arg_) => {
+                Some(Term::App {
+                    fun: fun_.map_or(*fun, |fun| arena.alloc(fun)),
+                    arg: arg_.map_or(*arg, |arg| arena.alloc(arg)),
+                })
+            }
+            }
+        }
+        | Term::Asc { term, r#type } => {
+            Some(Term::Asc {
+                term: arena.alloc(map(term, 0)?),
+                r#type: r#type.clone(),
+            })
+        }
+        | Term::Let { hint, arg, body } => {
+            match (map(arg, 0), map(body, 1)) {
+            | (None, None) => None,
+            | (arg_, body_) => {
+                Some(Term::Let {
+                    hint: hint.clone(),
+                    arg: arg_.map_or(*arg, |arg| arena.alloc(arg)),
+                    body: body_.map_or(*body, |body| arena.alloc(body)),
+                })
+            }
+            }
+        }
+        | Term::Tuple(terms) => {
+            let terms_ = terms
+                .iter()
+                .map(|term| map(term, 0))
+                .collect::<Vec<_>>();
+            if terms_.iter().all(Option::is_none) {
+                return None;
+            }
+            Some(Term::Tuple(terms
+                .iter()
+                .zip(terms_)
+                .map(|(term, term_)| term_.map_or(*term, |term| arena.alloc(term)))
+                .collect()))
+        }
+        | Term::TupleProject { tuple, index } => {
+            Some(Term::TupleProject {
+                tuple: arena.alloc(map(tuple, 0)?),
+                index: *index,
             })
-            .last()
-            .unwrap_or(self)
+        }
+        | Term::Record(terms) => {
+            let terms_ = terms
+                .values()
+                .map(|term| map(term, 0))
+                .collect::<Vec<_>>();
+            if terms_.iter().all(Option::is_none) {
+                return None;
+            }
+            Some(Term::Record(terms
+                .iter()
+                .zip(terms_)
+                .map(|((label, term), term_)| (label.to_owned(), term_.map_or(*term, |term| arena.alloc(term))))
+                .collect()))
+        }
+        | Term::RecordProject { record, label } => {
+            Some(Term::RecordProject {
+                record: arena.alloc(map(record, 0)?),
+                label: label.to_owned(),
+            })
+        }
+        | Term::Seq { first, second } => {
+            match (map(first, 0), map(second, 0)) {
+            | (None, None) => None,
+            | (first_, second_) => {
+                Some(Term::Seq {
+                    first: first_.map_or(*first, |first| arena.alloc(first)),
+                    second: second_.map_or(*second, |second| arena.alloc(second)),
+                })
+            }
+            }
+        }
+        | Term::Inl { term, r#type } => {
+            Some(Term::Inl {
+                term: arena.alloc(map(term, 0)?),
+                r#type: r#type.clone(),
+            })
+        }
+        | Term::Inr { term, r#type } => {
+            Some(Term::Inr {
+                term: arena.alloc(map(term, 0)?),
+                r#type: r#type.clone(),
+            })
+        }
+        | Term::Case { sum, inl_hint, inl, inr_hint, inr } => {
+            match (map(sum, 0), map(inl, 1), map(inr, 1)) {
+            | (None, None, None) => None,
+            | (sum_, inl_, inr_) => {
+                Some(Term::Case {
+                    sum: sum_.map_or(*sum, |sum| arena.alloc(sum)),
+                    inl_hint: inl_hint.clone(),
+                    inl: inl_.map_or(*inl, |inl| arena.alloc(inl)),
+                    inr_hint: inr_hint.clone(),
+                    inr: inr_.map_or(*inr, |inr| arena.alloc(inr)),
+                })
+            }
+            }
+        }
+        | Term::Variant { label, term, r#type } => {
+            Some(Term::Variant {
+                label: label.clone(),
+                term: arena.alloc(map(term, 0)?),
+                r#type: r#type.clone(),
+            })
+        }
+        | Term::VariantCase { variant, arms } => {
+            let variant_ = map(variant, 0);
+            let bodies_ = arms
+                .iter()
+                .map(|arm| map(arm.body, 1))
+                .collect::<Vec<_>>();
+            if variant_.is_none() && bodies_.iter().all(Option::is_none) {
+                return None;
+            }
+            Some(Term::VariantCase {
+                variant: variant_.map_or(*variant, |variant| arena.alloc(variant)),
+                arms: arms
+                    .iter()
+                    .zip(bodies_)
+                    .map(|(arm, body_)| Arm {
+                        label: arm.label.clone(),
+                        hint: arm.hint.clone(),
+                        body: body_.map_or(arm.body, |body| arena.alloc(body)),
+                    })
+                    .collect(),
+            })
+        }
+        | Term::Fix(term) => Some(Term::Fix(arena.alloc(map(term, 0)?))),
+        }
+    }
+
+    fn fold<B, F>(&self, init: B, mut fold: F) -> B
+    where
+        F: FnMut(B, &'a Self, i64) -> B,
+    {
+        match self {
+        | Term::Bool(_) | Term::Var(_) | Term::Unit => init,
+        | Term::If { r#if, then, r#else } => {
+            let init = fold(init, r#if, 0);
+            let init = fold(init, then, 0);
+            fold(init, r#else, 0)
+        }
+        | Term::Abs { body, .. } => fold(init, body, 1),
+        | Term::App { fun, arg } => {
+            let init = fold(init, fun, 0);
+            fold(init, arg, 0)
+        }
+        | Term::Asc { term, .. } => fold(init, term, 0),
+        | Term::Let { arg, body, .. } => {
+            let init = fold(init, arg, 0);
+            fold(init, body, 1)
+        }
+        | Term::Tuple(terms) => terms.iter().fold(init, |init, term| fold(init, term, 0)),
+        | Term::TupleProject { tuple, .. } => fold(init, tuple, 0),
+        | Term::Record(terms) => terms.values().fold(init, |init, term| fold(init, term, 0)),
+        | Term::RecordProject { record, .. } => fold(init, record, 0),
+        | Term::Seq { first, second } => {
+            let init = fold(init, first, 0);
+            fold(init, second, 0)
+        }
+        | Term::Inl { term, .. } | Term::Inr { term, .. } => fold(init, term, 0),
+        | Term::Case { sum, inl, inr, .. } => {
+            let init = fold(init, sum, 0);
+            let init = fold(init, inl, 1);
+            fold(init, inr, 1)
+        }
+        | Term::Variant { term, .. } => fold(init, term, 0),
+        | Term::VariantCase { variant, arms } => {
+            let init = fold(init, variant, 0);
+            arms.iter().fold(init, |init, arm| fold(init, arm.body, 1))
+        }
+        | Term::Fix(term) => fold(init, term, 0),
+        }
+    }
+}
+
+/// Records are compared in field order, which is stricter than `PartialEq`:
+/// since evaluation order follows field order, records that only differ in
+/// the order of their fields are interned separately.
+impl<'a> HashCons for Term<'a> {
+    fn shallow_eq(&self, other: &Self) -> bool {
+        match (self, other) {
+        | (Term::Bool(l), Term::Bool(r)) => l == r,
+        | (Term::If { r#if: li, then: lt, r#else: le }, Term::If { r#if: ri, then: rt, r#else: re }) => {
+            ptr::eq(*li, *ri) && ptr::eq(*lt, *rt) && ptr::eq(*le, *re)
+        }
+        | (Term::Var(l), Term::Var(r)) => l == r,
+        | (Term::Abs { hint: lh, r#type: lt, body: lb }, Term::Abs { hint: rh, r#type: rt, body: rb }) => {
+            lh == rh && lt == rt && ptr::eq(*lb, *rb)
+        }
+        | (Term::App { fun: lf, arg: la }, Term::App { fun: rf, arg: ra }) => {
+            ptr::eq(*lf, *rf) && ptr::eq(*la, *ra)
+        }
+        | (Term::Asc { term: l, r#type: lt }, Term::Asc { term: r, r#type: rt }) => ptr::eq(*l, *r) && lt == rt,
+        | (Term::Let { hint: lh, arg: la, body: lb }, Term::Let { hint: rh, arg: ra, body: rb }) => {
+            lh == rh && ptr::eq(*la, *ra) && ptr::eq(*lb, *rb)
+        }
+        | (Term::Tuple(l), Term::Tuple(r)) => {
+            l.len() == r.len() && l.iter().zip(r).all(|(l, r)| ptr::eq(*l, *r))
+        }
+        | (Term::TupleProject { tuple: l, index: li }, Term::TupleProject { tuple: r, index: ri }) => {
+            ptr::eq(*l, *r) && li == ri
+        }
+        | (Term::Record(l), Term::Record(r)) => {
+            l.len() == r.len() && l.iter().zip(r).all(|((ll, l), (rl, r))| ll == rl && ptr::eq(*l, *r))
+        }
+        | (Term::RecordProject { record: l, label: ll }, Term::RecordProject { record: r, label: rl }) => {
+            ptr::eq(*l, *r) && ll == rl
+        }
+        | (Term::Unit, Term::Unit) => true,
+        | (Term::Seq { first: lf, second: ls }, Term::Seq { first: rf, second: rs }) => {
+            ptr::eq(*lf, *rf) && ptr::eq(*ls, *rs)
+        }
+        | (Term::Inl { term: l, r#type: lt }, Term::Inl { term: r, r#type: rt })
+        | (Term::Inr { term: l, r#type: lt }, Term::Inr { term: r, r#type: rt }) => ptr::eq(*l, *r) && lt == rt,
+        | (
+            Term::Case { sum: ls, inl_hint: llh, inl: ll, inr_hint: lrh, inr: lr },
+            Term::Case { sum: rs, inl_hint: rlh, inl: rl, inr_hint: rrh, inr: rr },
+        ) => {
+            ptr::eq(*ls, *rs) && llh == rlh && ptr::eq(*ll, *rl) && lrh == rrh && ptr::eq(*lr, *rr)
+        }
+        | (Term::Variant { label: ll, term: l, r#type: lt }, Term::Variant { label: rl, term: r, r#type: rt }) => {
+            ll == rl && ptr::eq(*l, *r) && lt == rt
+        }
+        | (Term::VariantCase { variant: l, arms: la }, Term::VariantCase { variant: r, arms: ra }) => {
+            ptr::eq(*l, *r)
+                && la.len() == ra.len()
+                && la.iter().zip(ra).all(|(l, r)| l.label == r.label && l.hint == r.hint && ptr::eq(l.body, r.body))
+        }
+        | (Term::Fix(l), Term::Fix(r)) => ptr::eq(*l, *r),
+        | _ => false,
+        }
     }
 
-    pub fn step(&self, arena: &'a Arena<Term<'a>>) -> Option<Self> {
+    /// Types are left out of the hash, since they are compared structurally.
+    fn shallow_hash<H: Hasher>(&self, hasher: &mut H) {
+        mem::discriminant(self).hash(hasher);
+        match self {
+        | Term::Bool(bool) => bool.hash(hasher),
+        | Term::If { r#if, then, r#else } => {
+            ptr::hash(*r#if, hasher);
+            ptr::hash(*then, hasher);
+            ptr::hash(*r#else, hasher);
+        }
+        | Term::Var(index) => index.hash(hasher),
+        | Term::Abs { hint, r#type: _, body } => {
+            hint.hash(hasher);
+            ptr::hash(*body, hasher);
+        }
+        | Term::App { fun, arg } => {
+            ptr::hash(*fun, hasher);
+            ptr::hash(*arg, hasher);
+        }
+        | Term::Asc { term, r#type: _ } => ptr::hash(*term, hasher),
+        | Term::Let { hint, arg, body } => {
+            hint.hash(hasher);
+            ptr::hash(*arg, hasher);
+            ptr::hash(*body, hasher);
+        }
+        | Term::Tuple(terms) => terms.iter().for_each(|term| ptr::hash(*term, hasher)),
+        | Term::TupleProject { tuple, index } => {
+            ptr::hash(*tuple, hasher);
+            index.hash(hasher);
+        }
+        | Term::Record(terms) => {
+            for (label, term) in terms {
+                label.hash(hasher);
+                ptr::hash(*term, hasher);
+            }
+        }
+        | Term::RecordProject { record, label } => {
+            ptr::hash(*record, hasher);
+            label.hash(hasher);
+        }
+        | Term::Unit => (),
+        | Term::Seq { first, second } => {
+            ptr::hash(*first, hasher);
+            ptr::hash(*second, hasher);
+        }
+        | Term::Inl { term, r#type: _ } | Term::Inr { term, r#type: _ } => ptr::hash(*term, hasher),
+        | Term::Case { sum, inl_hint, inl, inr_hint, inr } => {
+            ptr::hash(*sum, hasher);
+            inl_hint.hash(hasher);
+            ptr::hash(*inl, hasher);
+            inr_hint.hash(hasher);
+            ptr::hash(*inr, hasher);
+        }
+        | Term::Variant { label, term, r#type: _ } => {
+            label.hash(hasher);
+            ptr::hash(*term, hasher);
+        }
+        | Term::VariantCase { variant, arms } => {
+            ptr::hash(*variant, hasher);
+            for arm in arms {
+                arm.label.hash(hasher);
+                arm.hint.hash(hasher);
+                ptr::hash(arm.body, hasher);
+            }
+        }
+        | Term::Fix(term) => ptr::hash(*term, hasher),
+        }
+    }
+}
+
+/// Steps `eval` takes before giving up, since with `fix` even well-typed
+/// terms may diverge.
+pub const FUEL: usize = 1 << 20;
+
+impl<'a> Term<'a> {
+    pub fn eval<A: Alloc<'a, Term<'a>>>(&'a self, arena: &'a A) -> anyhow::Result<&'a Self> {
+        self.eval_with_fuel(arena, FUEL)
+    }
+
+    /// Evaluates this term to a normal form, failing if that takes more than
+    /// `fuel` steps.
+    pub fn eval_with_fuel<A: Alloc<'a, Term<'a>>>(&'a self, arena: &'a A, fuel: usize) -> anyhow::Result<&'a Self> {
+        let mut term = self;
+        for remaining in (0..=fuel).rev() {
+            match term.step(arena) {
+            | None => return Ok(term),
+            | Some(_) if remaining == 0 => break,
+            | Some(next) => term = arena.alloc(next),
+            }
+        }
+        Err(anyhow!("Evaluation ran out of fuel after {} steps", fuel))
+    }
+
+    pub fn step<A: Alloc<'a, Term<'a>>>(&self, arena: &'a A) -> Option<Self> {
         match self {
         | Term::Bool(_)
         | Term::Var(_)
-        | Term::Abs { .. } => None,
+        | Term::Abs { .. }
+        | Term::Unit => None,
 
         //
         // ------------------------------- E-AppAbs
@@ -184,7 +586,7 @@ impl<'a> Term<'a> {
             let mut before = terms.iter();
             let mut after = Vec::new();
 
-            while let Some(&term) = before.next() {
+            for &term in before.by_ref() {
                 if term.is_value() {
                     after.push(term);
                 } else {
@@ -222,7 +624,7 @@ impl<'a> Term<'a> {
             let mut before = terms.iter().map(|(label, &term)| (label.to_owned(), term));
             let mut after = IndexMap::new();
 
-            while let Some((label, term)) = before.next() {
+            for (label, term) in before.by_ref() {
                 if term.is_value() {
                     after.insert(label, term);
                 } else {
@@ -252,164 +654,160 @@ impl<'a> Term<'a> {
             })
         }
         | Term::RecordProject { .. } => panic!("Could not evaluate ill-typed record projection term"),
-        }
-    }
 
-    pub fn is_value(&self) -> bool {
-        match self {
-        | Term::Bool(_)
-        | Term::Var { .. }
-        | Term::Abs { .. } => true,
-        | Term::Tuple(terms) => terms.iter().all(|term| term.is_value()),
-        | Term::Record(terms) => terms.values().all(|term| term.is_value()),
-        | Term::If { .. }
-        | Term::App { .. }
-        | Term::Asc { .. }
-        | Term::Let { .. }
-        | Term::TupleProject { .. }
-        | Term::RecordProject { .. } => false,
+        //
+        // --------------- E-SeqNext
+        // unit; t₂ --> t₂
+        | Term::Seq { first: Term::Unit, second } => Some(Clone::clone(*second)),
+        //     t₁ --> t₁'
+        // ------------------ E-Seq
+        // t₁; t₂ --> t₁'; t₂
+        | Term::Seq { first, second } => {
+            Some(Term::Seq {
+                first: arena.alloc(first.step(arena)?),
+                second,
+            })
         }
-    }
 
-    fn substitute_top(&self, arena: &'a Arena<Term<'a>>, to: &'a Term<'a>) -> Self {
-        self.substitute(arena, 0, arena.alloc(to.shift(arena, 1))).shift(arena, -1)
-    }
-
-    fn substitute(&self, arena: &'a Arena<Term<'a>>, from: i64, to: &'a Term<'a>) -> Self {
-        self._substitute(arena, from, to, 0)
-    }
-
-    fn _substitute(
-        &self,
-        arena: &'a Arena<Term<'a>>,
-        from: i64,
-        to: &'a Term<'a>,
-        depth: i64,
-    ) -> Self {
-        match self {
-        | Term::Bool(bool) => Term::Bool(*bool),
-        | Term::If { r#if, then, r#else } => {
-            Term::If {
-                r#if: arena.alloc(r#if._substitute(arena, from, to, depth)),
-                then: arena.alloc(then._substitute(arena, from, to, depth)),
-                r#else: arena.alloc(r#else._substitute(arena, from, to, depth)),
-            }
+        //
+        // ------------------------------------------------------------------- E-CaseInl
+        // case (inl v₀ as T₀) of inl x₁ ⇒ t₁ | inr x₂ ⇒ t₂ --> [x₁ |-> v₀] t₁
+        | Term::Case { sum: Term::Inl { term, .. }, inl, .. } if term.is_value() => Some(inl.substitute_top(arena, term)),
+        //
+        // ------------------------------------------------------------------- E-CaseInr
+        // case (inr v₀ as T₀) of inl x₁ ⇒ t₁ | inr x₂ ⇒ t₂ --> [x₂ |-> v₀] t₂
+        | Term::Case { sum: Term::Inr { term, .. }, inr, .. } if term.is_value() => Some(inr.substitute_top(arena, term)),
+        //                                   t₀ --> t₀'
+        // ------------------------------------------------------------------------------ E-Case
+        // case t₀ of inl x₁ ⇒ t₁ | inr x₂ ⇒ t₂ --> case t₀' of inl x₁ ⇒ t₁ | inr x₂ ⇒ t₂
+        | Term::Case { sum, inl_hint, inl, inr_hint, inr } => {
+            Some(Term::Case {
+                sum: arena.alloc(sum.step(arena)?),
+                inl_hint: inl_hint.clone(),
+                inl,
+                inr_hint: inr_hint.clone(),
+                inr,
+            })
         }
-        | Term::Var(index) if *index == from + depth => to.shift(arena, depth),
-        | Term::Var(index) => Term::Var(*index),
-        | Term::Abs { hint, r#type, body } => {
-            Term::Abs {
-                hint: hint.clone(),
+
+        //           t₁ --> t₁'
+        // ------------------------------ E-Inl
+        // inl t₁ as T₂ --> inl t₁' as T₂
+        | Term::Inl { term, r#type } => {
+            Some(Term::Inl {
+                term: arena.alloc(term.step(arena)?),
                 r#type: r#type.clone(),
-                body: arena.alloc(body._substitute(arena, from, to, depth + 1)),
-            }
-        }
-        | Term::App { fun, arg } => {
-            Term::App {
-                fun: arena.alloc(fun._substitute(arena, from, to, depth)),
-                arg: arena.alloc(arg._substitute(arena, from, to, depth)),
-            }
+            })
         }
-        | Term::Asc { term, r#type } => {
-            Term::Asc {
-                term: arena.alloc(term._substitute(arena, from, to, depth)),
+        //           t₁ --> t₁'
+        // ------------------------------ E-Inr
+        // inr t₁ as T₂ --> inr t₁' as T₂
+        | Term::Inr { term, r#type } => {
+            Some(Term::Inr {
+                term: arena.alloc(term.step(arena)?),
                 r#type: r#type.clone(),
-            }
-        }
-        | Term::Let { hint, arg, body } => {
-            Term::Let {
-                hint: hint.clone(),
-                arg: arena.alloc(arg._substitute(arena, from, to, depth)),
-                body: arena.alloc(body._substitute(arena, from, to, depth + 1)),
-            }
-        }
-        | Term::Tuple(terms) => {
-            Term::Tuple(terms.iter().map(|term| &*arena.alloc(term._substitute(arena, from, to, depth))).collect())
+            })
         }
-        | Term::TupleProject { tuple, index } => {
-            Term::TupleProject {
-                tuple: arena.alloc(tuple._substitute(arena, from, to, depth)),
-                index: *index,
-            }
+
+        //
+        // ----------------------------------------------------------------------------- E-CaseVariant
+        // case (<l_j = v_j> as T) of <l_i = x_i> ⇒ t_i^{i ∈ 1..n} --> [x_j |-> v_j] t_j
+        | Term::VariantCase { variant: Term::Variant { label, term, .. }, arms } if term.is_value() => {
+            let arm = arms
+                .iter()
+                .find(|arm| arm.label == *label)?;
+            Some(arm.body.substitute_top(arena, term))
         }
-        | Term::Record(terms) => {
-            Term::Record(terms.iter().map(|(label, term)| (
-                label.to_owned(),
-                &*arena.alloc(term._substitute(arena, from, to, depth)),
-            )).collect())
+        //                                      t₀ --> t₀'
+        // ------------------------------------------------------------------------------------ E-Case
+        // case t₀ of <l_i = x_i> ⇒ t_i^{i ∈ 1..n} --> case t₀' of <l_i = x_i> ⇒ t_i^{i ∈ 1..n}
+        | Term::VariantCase { variant, arms } => {
+            Some(Term::VariantCase {
+                variant: arena.alloc(variant.step(arena)?),
+                arms: arms.clone(),
+            })
         }
-        | Term::RecordProject { record, label } => {
-            Term::RecordProject {
-                record: arena.alloc(record._substitute(arena, from, to, depth)),
-                label: label.to_owned(),
-            }
+
+        //            t₁ --> t₁'
+        // -------------------------------- E-Variant
+        // <l = t₁> as T --> <l = t₁'> as T
+        | Term::Variant { label, term, r#type } => {
+            Some(Term::Variant {
+                label: label.clone(),
+                term: arena.alloc(term.step(arena)?),
+                r#type: r#type.clone(),
+            })
         }
+
+        //
+        // ------------------------------------------------ E-FixBeta
+        // fix (λx: T₁. t₂) --> [x |-> fix (λx: T₁. t₂)] t₂
+        | Term::Fix(abs @ Term::Abs { body, .. }) => Some(body.substitute_top(arena, arena.alloc(Term::Fix(abs)))),
+        //     t₁ --> t₁'
+        // ------------------ E-Fix
+        // fix t₁ --> fix t₁'
+        | Term::Fix(term) => Some(Term::Fix(arena.alloc(term.step(arena)?))),
         }
     }
 
-    fn shift(&self, arena: &'a Arena<Term<'a>>, max_depth: i64) -> Self {
-        self._shift(arena, max_depth, 0)
+    /// Position of the redex contracted by `step`, as the indices of the
+    /// children leading to it in the order `fold` visits them, or `None` if
+    /// this term doesn't step.
+    pub fn redex(&self) -> Option<Vec<usize>> {
+        let (child, term) = match self {
+        | Term::App { fun: Term::Abs { .. }, arg } if arg.is_value() => return Some(Vec::new()),
+        | Term::App { fun, arg } if fun.is_value() => (1, *arg),
+        | Term::App { fun, .. } => (0, *fun),
+        | Term::If { r#if: Term::Bool(_), .. } => return Some(Vec::new()),
+        | Term::If { r#if, .. } => (0, *r#if),
+        | Term::Asc { term, .. } if term.is_value() => return Some(Vec::new()),
+        | Term::Asc { term, .. } => (0, *term),
+        | Term::Let { arg, .. } if arg.is_value() => return Some(Vec::new()),
+        | Term::Let { arg, .. } => (0, *arg),
+        | Term::Tuple(terms) => terms.iter().copied().enumerate().find(|(_, term)| !term.is_value())?,
+        | Term::TupleProject { tuple: tuple @ Term::Tuple(_), .. } if tuple.is_value() => return Some(Vec::new()),
+        | Term::TupleProject { tuple: tuple @ Term::Tuple(_), .. } => (0, *tuple),
+        | Term::Record(terms) => terms.values().copied().enumerate().find(|(_, term)| !term.is_value())?,
+        | Term::RecordProject { record: record @ Term::Record(_), .. } if record.is_value() => return Some(Vec::new()),
+        | Term::RecordProject { record: record @ Term::Record(_), .. } => (0, *record),
+        | Term::Seq { first: Term::Unit, .. } => return Some(Vec::new()),
+        | Term::Seq { first, .. } => (0, *first),
+        | Term::Case { sum: Term::Inl { term, .. }, .. } | Term::Case { sum: Term::Inr { term, .. }, .. } if term.is_value() => {
+            return Some(Vec::new())
+        }
+        | Term::Case { sum, .. } => (0, *sum),
+        | Term::Inl { term, .. } | Term::Inr { term, .. } => (0, *term),
+        | Term::VariantCase { variant: Term::Variant { term, .. }, .. } if term.is_value() => return Some(Vec::new()),
+        | Term::VariantCase { variant, .. } => (0, *variant),
+        | Term::Variant { term, .. } => (0, *term),
+        | Term::Fix(Term::Abs { .. }) => return Some(Vec::new()),
+        | Term::Fix(term) => (0, *term),
+        | _ => return None,
+        };
+        let mut position = term.redex()?;
+        position.insert(0, child);
+        Some(position)
     }
 
-    fn _shift(&self, arena: &'a Arena<Term<'a>>, max_depth: i64, depth: i64) -> Self {
+    pub fn is_value(&self) -> bool {
         match self {
-        | Term::Bool(bool) => Term::Bool(*bool),
-        | Term::If { r#if, then, r#else } => {
-            Term::If {
-                r#if: arena.alloc(r#if._shift(arena, max_depth, depth)),
-                then: arena.alloc(then._shift(arena, max_depth, depth)),
-                r#else: arena.alloc(r#else._shift(arena, max_depth, depth)),
-            }
-        }
-        | Term::Var(index) if *index >= depth => Term::Var(index + max_depth),
-        | Term::Var(index) => Term::Var(*index),
-        | Term::Abs { hint, r#type, body } => {
-            Term::Abs {
-                hint: hint.clone(),
-                r#type: r#type.clone(),
-                body: arena.alloc(body._shift(arena, max_depth, depth + 1)),
-            }
-        }
-        | Term::App { fun, arg } => {
-            Term::App {
-                fun: arena.alloc(fun._shift(arena, max_depth, depth)),
-                arg: arena.alloc(arg._shift(arena, max_depth, depth)),
-            }
-        }
-        | Term::Asc { term, r#type } => {
-            Term::Asc {
-                term: arena.alloc(term._shift(arena, max_depth, depth)),
-                r#type: r#type.clone(),
-            }
-        }
-        | Term::Let { hint, arg, body } => {
-            Term::Let {
-                hint: hint.clone(),
-                arg: arena.alloc(arg._shift(arena, max_depth, depth)),
-                body: arena.alloc(body._shift(arena, max_depth, depth + 1)),
-            }
-        }
-        | Term::Tuple(terms) => {
-            Term::Tuple(terms.iter().map(|term| &*arena.alloc(term._shift(arena, max_depth, depth))).collect())
-        }
-        | Term::TupleProject { tuple, index } => {
-            Term::TupleProject {
-                tuple: arena.alloc(tuple._shift(arena, max_depth, depth)),
-                index: *index,
-            }
-        }
-        | Term::Record(terms) => {
-            Term::Record(terms.iter().map(|(label, term)| (
-                label.to_owned(),
-                &*arena.alloc(term._shift(arena, max_depth, depth)),
-            )).collect())
-        }
-        | Term::RecordProject { record, label } => {
-            Term::RecordProject {
-                record: arena.alloc(record._shift(arena, max_depth, depth)),
-                label: label.to_owned(),
-            }
-        }
+        | Term::Bool(_)
+        | Term::Var { .. }
+        | Term::Abs { .. }
+        | Term::Unit => true,
+        | Term::Tuple(terms) => terms.iter().all(|term| term.is_value()),
+        | Term::Record(terms) => terms.values().all(|term| term.is_value()),
+        | Term::Inl { term, .. } | Term::Inr { term, .. } | Term::Variant { term, .. } => term.is_value(),
+        | Term::If { .. }
+        | Term::App { .. }
+        | Term::Asc { .. }
+        | Term::Let { .. }
+        | Term::TupleProject { .. }
+        | Term::RecordProject { .. }
+        | Term::Seq { .. }
+        | Term::Case { .. }
+        | Term::VariantCase { .. }
+        | Term::Fix(_) => false,
         }
     }
 
@@ -430,11 +828,12 @@ impl<'a> Term<'a> {
             write!(writer, "{}", context.name(*index))?;
         }
         | Term::Abs { hint, r#type, body } => {
-            let name = context.push(hint.to_owned());
-            write!(writer, "(λ{}: {}. ", r#type, name)?;
-            body.write(context, writer)?;
-            write!(writer, ")")?;
-            context.pop();
+            context.bind(hint, |context, name| -> anyhow::Result<()> {
+                write!(writer, "(λ{}: {}. ", name, r#type)?;
+                body.write(context, writer)?;
+                write!(writer, ")")?;
+                Ok(())
+            })?;
         }
         | Term::App { fun, arg } => {
             write!(writer, "(")?;
@@ -448,11 +847,10 @@ impl<'a> Term<'a> {
             write!(writer, " as {}", r#type)?;
         }
         | Term::Let { hint, arg, body } => {
-            let name = context.push(hint.to_owned());
-            write!(writer, "let {} = ", name)?;
+            write!(writer, "let {} = ", context.fresh(hint.to_owned()))?;
             arg.write(context, writer)?;
             write!(writer, " in ")?;
-            body.write(context, writer)?;
+            context.bind(hint, |context, _| body.write(context, writer))?;
         }
         | Term::Tuple(terms) => {
             let mut terms = terms.iter();
@@ -460,7 +858,7 @@ impl<'a> Term<'a> {
             if let Some(head) = terms.next() {
                 head.write(context, writer)?;
             }
-            while let Some(tail) = terms.next() {
+            for tail in terms {
                 write!(writer, ", ")?;
                 tail.write(context, writer)?;
             }
@@ -477,7 +875,7 @@ impl<'a> Term<'a> {
                 write!(writer, "{} = ", label)?;
                 term.write(context, writer)?;
             }
-            while let Some((label, term)) = terms.next() {
+            for (label, term) in terms {
                 write!(writer, ", {} =", label)?;
                 term.write(context, writer)?;
             }
@@ -487,7 +885,228 @@ impl<'a> Term<'a> {
             record.write(context, writer)?;
             write!(writer, ".{}", label)?;
         }
+        | Term::Unit => {
+            write!(writer, "unit")?;
+        }
+        | Term::Seq { first, second } => {
+            write!(writer, "(")?;
+            first.write(context, writer)?;
+            write!(writer, "; ")?;
+            second.write(context, writer)?;
+            write!(writer, ")")?;
+        }
+        | Term::Inl { term, r#type } => {
+            write!(writer, "inl ")?;
+            term.write(context, writer)?;
+            write!(writer, " as {}", r#type)?;
+        }
+        | Term::Inr { term, r#type } => {
+            write!(writer, "inr ")?;
+            term.write(context, writer)?;
+            write!(writer, " as {}", r#type)?;
+        }
+        | Term::Case { sum, inl_hint, inl, inr_hint, inr } => {
+            write!(writer, "case ")?;
+            sum.write(context, writer)?;
+            context.bind(inl_hint, |context, name| -> anyhow::Result<()> {
+                write!(writer, " of inl {} ⇒ ", name)?;
+                inl.write(context, writer)
+            })?;
+            context.bind(inr_hint, |context, name| -> anyhow::Result<()> {
+                write!(writer, " | inr {} ⇒ ", name)?;
+                inr.write(context, writer)
+            })?;
+        }
+        | Term::Variant { label, term, r#type } => {
+            write!(writer, "<{} = ", label)?;
+            term.write(context, writer)?;
+            write!(writer, "> as {}", r#type)?;
+        }
+        | Term::VariantCase { variant, arms } => {
+            write!(writer, "case ")?;
+            variant.write(context, writer)?;
+            write!(writer, " of")?;
+            for (index, arm) in arms.iter().enumerate() {
+                let separator = if index == 0 { "" } else { " |" };
+                context.bind(&arm.hint, |context, name| -> anyhow::Result<()> {
+                    write!(writer, "{} <{} = {}> ⇒ ", separator, arm.label, name)?;
+                    arm.body.write(context, writer)
+                })?;
+            }
+        }
+        | Term::Fix(term) => {
+            write!(writer, "(fix ")?;
+            term.write(context, writer)?;
+            write!(writer, ")")?;
+        }
         }
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+
+    use std::collections::BTreeSet;
+
+    use tapl_core::intern::Id;
+    use tapl_core::DeBruijn as _;
+    use tapl_core::Interner;
+    use tapl_core::Sexp;
+    use typed_arena::Arena;
+
+    use crate::r#type;
+    use crate::r#type::Type;
+    use crate::term::Context;
+    use crate::term::Term;
+
+    /// Evaluating in an interner gives the same results as in an arena, and
+    /// shares equal subterms.
+    #[test]
+    fn intern() {
+        let arena = Arena::new();
+        let interner = Interner::default();
+        let sexp = "
+            (let f (abs x bool (tuple (var 0) (var 0)))
+                 (record (a (app (var 0) (bool true)))
+                         (b (app (var 0) (bool true)))
+                         (c (app (var 0) (bool false)))))
+        ".parse::<Sexp>().unwrap();
+        let term = Term::from_sexp(&arena, &sexp).unwrap();
+        let expected = arena.alloc(term.clone()).eval(&arena).unwrap();
+        let actual = interner.intern(&term).eval(&interner).unwrap();
+        assert_eq!(actual, expected);
+
+        match actual {
+        | Term::Record(terms) => assert_eq!(Id(terms["a"]), Id(terms["b"])),
+        | _ => panic!("Expected record, but found {:?}", actual),
+        }
+
+        let ab = Term::from_sexp(&arena, &"(record (a (bool true)) (b (bool false)))".parse().unwrap()).unwrap();
+        let ba = Term::from_sexp(&arena, &"(record (b (bool false)) (a (bool true)))".parse().unwrap()).unwrap();
+        assert_ne!(Id(interner.intern(&ab)), Id(interner.intern(&ba)));
+    }
+
+    /// A diverging term runs out of fuel, while a terminating one finishes
+    /// with exactly as much fuel as it takes steps.
+    #[test]
+    fn fuel() {
+        let arena = Arena::new();
+        let diverge = &*arena.alloc(Term::parse(&arena, "fix (lambda x: Bool. x)").unwrap());
+        assert_eq!(diverge.check(&mut r#type::Context::default(), 0).unwrap(), Type::Bool);
+        assert_eq!(diverge.eval_with_fuel(&arena, 100).unwrap_err().to_string(), "Evaluation ran out of fuel after 100 steps");
+
+        let id = &*arena.alloc(Term::parse(&arena, "(lambda x: Bool. x) true").unwrap());
+        assert_eq!(id.eval_with_fuel(&arena, 1).unwrap(), &Term::Bool(true));
+        assert!(id.eval_with_fuel(&arena, 0).is_err());
+
+        let mismatch = Term::parse(&arena, "fix (lambda x: Bool. unit)").unwrap();
+        let error = mismatch.check(&mut r#type::Context::default(), 0).unwrap_err();
+        assert_eq!(error.to_string(), "Result of body not compatible with domain");
+    }
+
+    /// Binders are written name first, primed when they shadow a name in
+    /// scope, and free variables are named by the context.
+    #[test]
+    fn write() {
+        let arena = Arena::new();
+        let sexp = "(abs x bool (let x (var 0) (case (var 2) (inl x (tuple (var 0) (var 1))) (inr y (app (var 2) (var 4))))))";
+        let term = Term::from_sexp(&arena, &sexp.parse::<Sexp>().unwrap()).unwrap();
+        let mut buffer = Vec::new();
+        term.write(&mut Context::default(), &mut buffer).unwrap();
+        assert_eq!(String::from_utf8(buffer).unwrap(), "(λx: bool. let x' = x in case α of inl x'' ⇒ (x'', x') | inr y ⇒ (x β))");
+    }
+
+    /// The fused substitution agrees with shifting, substituting, and shifting
+    /// back, on open terms under every form that binds a variable.
+    #[test]
+    fn substitute_top_fused() {
+        let arena = Arena::new();
+        let parse = |sexp: &str| &*arena.alloc(Term::from_sexp(&arena, &sexp.parse::<Sexp>().unwrap()).unwrap());
+        let bodies = [
+            "(var 0)",
+            "(var 2)",
+            "(abs x bool (app (var 0) (app (var 1) (var 2))))",
+            "(let x (var 0) (tuple (var 0) (var 1) (var 2)))",
+            "(case (var 0) (inl a (app (var 1) (var 0))) (inr b (abs c bool (tuple (var 0) (var 1) (var 2) (var 3)))))",
+            "(variant-case (var 1) (l c (var 2)) (m d (let e (var 0) (tuple (var 0) (var 1) (var 3) (var 4)))))",
+            "(fix (abs f bool (app (var 0) (app (var 1) (var 2)))))",
+            "(record (a (var 0)) (b (seq (var 1) (record-project (var 2) a))))",
+            "(if (var 0) (inl (var 1) (sum bool bool)) (variant l (tuple-project (var 2) 0) (variant (l bool))))",
+            "(as (inr (let y (var 1) (var 1)) (sum bool bool)) (sum bool bool))",
+        ];
+        let tos = [
+            "(var 0)",
+            "(bool true)",
+            "(abs y bool (app (var 0) (var 1)))",
+            "(let z (var 0) (tuple (var 0) (var 1)))",
+            "(case (var 0) (inl a (var 0)) (inr b (var 1)))",
+            "(fix (abs f bool (variant-case (var 1) (l c (app (var 1) (var 2))))))",
+        ];
+        for body in &bodies {
+            let body = parse(body);
+            for to in &tos {
+                let to = parse(to);
+                let unfused = body
+                    .substitute(&arena, 0, arena.alloc(to.shift(&arena, 1)))
+                    .shift(&arena, -1);
+                assert_eq!(body.substitute_top(&arena, to), unfused, "[0 ↦ {}] {}", to.to_sexp(), body.to_sexp());
+            }
+        }
+    }
+
+    /// A case on a variant without an arm for its label is ill-typed, and
+    /// is stuck rather than evaluated.
+    #[test]
+    fn stuck() {
+        let arena = Arena::new();
+        let term = &*arena.alloc(Term::parse(&arena, "case <b = true> as <b: Bool> of <a = x> ==> x").unwrap());
+        assert!(term.check(&mut r#type::Context::default(), 0).is_err());
+        assert_eq!(term.step(&arena), None);
+        assert_eq!(term.eval(&arena).unwrap(), term);
+    }
+
+    /// `let`, `case` and variant `case` bind one variable in their bodies
+    /// and arms but none in their scrutinees, which `fold`, `free` and
+    /// `is_closed` all observe.
+    #[test]
+    fn binders() {
+        let arena = Arena::new();
+        let sexp = "
+            (let x (var 0)
+                 (case (var 1)
+                       (inl a (var 3))
+                       (inr b (variant-case (var 0)
+                                            (l c (var 5))
+                                            (m d (var 1))))))
+        ".parse::<Sexp>().unwrap();
+        let term = Term::from_sexp(&arena, &sexp).unwrap();
+        let bound = |term: &Term<'_>| term.fold(Vec::new(), |mut bound, _, count| {
+            bound.push(count);
+            bound
+        });
+        assert_eq!(bound(&term), [0, 1]);
+        let case = match term {
+        | Term::Let { body, .. } => body,
+        | _ => unreachable!(),
+        };
+        assert_eq!(bound(case), [0, 1, 1]);
+        let variant_case = match case {
+        | Term::Case { inr, .. } => inr,
+        | _ => unreachable!(),
+        };
+        assert_eq!(bound(variant_case), [0, 1, 1]);
+
+        assert_eq!(term.free(), BTreeSet::from([0, 1, 2]));
+        assert_eq!(case.free(), BTreeSet::from([1, 2, 3]));
+        assert_eq!(variant_case.free(), BTreeSet::from([0, 4]));
+        assert!(!term.is_closed());
+
+        let closed = (0..3).fold(term, |body, _| Term::Abs {
+            hint: String::from("y"),
+            r#type: Type::Bool,
+            body: arena.alloc(body),
+        });
+        assert!(closed.is_closed());
+    }
+}
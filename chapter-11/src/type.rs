@@ -1,23 +1,39 @@
 use std::collections::HashMap;
+use std::collections::HashSet;
 use std::fmt;
 
 use anyhow::anyhow;
 use indexmap::IndexMap;
+use serde::Deserialize;
+use serde::Serialize;
 
 use crate::term::Term;
 
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub enum Type {
+    /// Serialized as `"Bool"`.
     Bool,
+    /// Serialized as `{"Fun": [T₁, T₂]}`.
     Fun(Box<Type>, Box<Type>),
+    /// Serialized as `{"Tuple": [T₁, ..., Tₙ]}`.
     Tuple(Vec<Type>),
+    /// Serialized as `{"Record": {"l₁": T₁, ..., "lₙ": Tₙ}}`, in order.
     Record(IndexMap<String, Type>),
+    /// Serialized as `"Unit"`.
+    Unit,
+    /// Uninterpreted base type, serialized as `{"Base": "A"}`.
+    Base(String),
+    /// Serialized as `{"Sum": [T₁, T₂]}`.
+    Sum(Box<Type>, Box<Type>),
+    /// Serialized as `{"Variant": {"l₁": T₁, ..., "lₙ": Tₙ}}`, in order.
+    Variant(IndexMap<String, Type>),
 }
 
 impl fmt::Display for Type {
     fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
         match self {
         | Type::Bool => write!(fmt, "bool"),
+        | Type::Fun(from, to) if matches!(**from, Type::Fun(..)) => write!(fmt, "({}) -> {}", from, to),
         | Type::Fun(from, to) => write!(fmt, "{} -> {}", from, to),
         | Type::Tuple(types) => {
             let mut types = types.iter();
@@ -25,7 +41,7 @@ impl fmt::Display for Type {
             if let Some(head) = types.next() {
                 write!(fmt, "{}", head)?;
             }
-            while let Some(tail) = types.next() {
+            for tail in types {
                 write!(fmt, ", {}", tail)?;
             }
             write!(fmt, ")")
@@ -36,18 +52,58 @@ impl fmt::Display for Type {
             if let Some((label, r#type)) = types.next() {
                 write!(fmt, "{} = {}", label, r#type)?;
             }
-            while let Some((label, r#type)) = types.next() {
+            for (label, r#type) in types {
                 write!(fmt, ", {} = {}", label, r#type)?;
             }
             write!(fmt, "}}")
         }
+        | Type::Unit => write!(fmt, "unit"),
+        | Type::Base(name) => write!(fmt, "{}", name),
+        | Type::Sum(left, right) => {
+            match **left {
+            | Type::Fun(..) => write!(fmt, "({}) + ", left)?,
+            | _ => write!(fmt, "{} + ", left)?,
+            }
+            match **right {
+            | Type::Fun(..) | Type::Sum(..) => write!(fmt, "({})", right),
+            | _ => write!(fmt, "{}", right),
+            }
+        }
+        | Type::Variant(types) => {
+            let mut types = types.iter();
+            write!(fmt, "<")?;
+            if let Some((label, r#type)) = types.next() {
+                write!(fmt, "{}: {}", label, r#type)?;
+            }
+            for (label, r#type) in types {
+                write!(fmt, ", {}: {}", label, r#type)?;
+            }
+            write!(fmt, ">")
+        }
         }
     }
 }
 
+/// The typing context, mapping the de Bruijn level of each variable in
+/// scope to its type.
 #[derive(Clone, Debug, Default)]
 pub struct Context(HashMap<i64, Type>);
 
+impl Context {
+    /// Declares a free variable of type `r#type`, which terms checked in this
+    /// context refer to with index 0, shifting the variables declared before
+    /// it one index further out.
+    pub fn declare(&mut self, r#type: Type) {
+        self.0.insert(self.depth(), r#type);
+    }
+
+    /// The number of variables declared in this context, which is the depth
+    /// to check closed terms from.
+    pub fn depth(&self) -> i64 {
+        self.0.len() as i64
+    }
+}
+
 impl<'a> Term<'a> {
     pub fn check(&self, context: &mut Context, depth: i64) -> anyhow::Result<Type> {
         match self {
@@ -77,7 +133,7 @@ impl<'a> Term<'a> {
         // ---------- T-Var
         // Γ |- x : T
         | Term::Var(index) => context.0
-            .get(index)
+            .get(&(depth - 1 - index))
             .cloned()
             .ok_or_else(|| anyhow!("Unbound variable: {}", index)),
 
@@ -86,9 +142,9 @@ impl<'a> Term<'a> {
         // Γ |- λx : T₁. t₂ : T₁ -> T₂
         | Term::Abs { hint: _, r#type, body } => {
             context.0.insert(depth, r#type.clone());
-            let body_type = body.check(context, depth + 1)?;
+            let body_type = body.check(context, depth + 1);
             context.0.remove(&depth);
-            Ok(Type::Fun(Box::new(r#type.clone()), Box::new(body_type)))
+            Ok(Type::Fun(Box::new(r#type.clone()), Box::new(body_type?)))
         }
 
         // Γ |- t₁ : T₁₁ -> T₁₂    Γ |- t₂ : T₁₁
@@ -122,9 +178,9 @@ impl<'a> Term<'a> {
         | Term::Let { hint: _, arg, body } => {
             let arg_type = arg.check(context, depth)?;
             context.0.insert(depth, arg_type);
-            let body_type = body.check(context, depth + 1)?;
+            let body_type = body.check(context, depth + 1);
             context.0.remove(&depth);
-            Ok(body_type)
+            body_type
         }
 
         //         for each i, Γ |- t_i : T_i
@@ -176,6 +232,142 @@ impl<'a> Term<'a> {
             | _ => Err(anyhow!("Can only project records or tuples")),
             }
         }
+
+        // ---------------- T-Unit
+        // Γ |- unit : Unit
+        | Term::Unit => Ok(Type::Unit),
+
+        // Γ |- t₁ : Unit    Γ |- t₂ : T₂
+        // ----------------------------- T-Seq
+        //      Γ |- t₁; t₂ : T₂
+        | Term::Seq { first, second } => {
+            if first.check(context, depth)? != Type::Unit {
+                return Err(anyhow!("First term of sequence not a unit"));
+            }
+            second.check(context, depth)
+        }
+
+        //           Γ |- t₁ : T₁
+        // -------------------------------- T-Inl
+        // Γ |- inl t₁ as T₁ + T₂ : T₁ + T₂
+        | Term::Inl { term, r#type } => {
+            match r#type {
+            | Type::Sum(left, _) => {
+                let actual_type = term.check(context, depth)?;
+                if actual_type == **left {
+                    Ok(r#type.clone())
+                } else {
+                    Err(anyhow!("Expected type {}, but found type {}", left, actual_type))
+                }
+            }
+            | _ => Err(anyhow!("Annotation of inl not a sum type")),
+            }
+        }
+        //           Γ |- t₁ : T₂
+        // -------------------------------- T-Inr
+        // Γ |- inr t₁ as T₁ + T₂ : T₁ + T₂
+        | Term::Inr { term, r#type } => {
+            match r#type {
+            | Type::Sum(_, right) => {
+                let actual_type = term.check(context, depth)?;
+                if actual_type == **right {
+                    Ok(r#type.clone())
+                } else {
+                    Err(anyhow!("Expected type {}, but found type {}", right, actual_type))
+                }
+            }
+            | _ => Err(anyhow!("Annotation of inr not a sum type")),
+            }
+        }
+
+        // Γ |- t₀ : T₁ + T₂    Γ, x₁ : T₁ |- t₁ : T    Γ, x₂ : T₂ |- t₂ : T
+        // ---------------------------------------------------------------- T-Case
+        //          Γ |- case t₀ of inl x₁ ⇒ t₁ | inr x₂ ⇒ t₂ : T
+        | Term::Case { sum, inl_hint: _, inl, inr_hint: _, inr } => {
+            let (left, right) = match sum.check(context, depth)? {
+            | Type::Sum(left, right) => (*left, *right),
+            | _ => return Err(anyhow!("Can only case on sums")),
+            };
+            context.0.insert(depth, left);
+            let inl_type = inl.check(context, depth + 1);
+            context.0.insert(depth, right);
+            let inr_type = inr.check(context, depth + 1);
+            context.0.remove(&depth);
+            let (inl_type, inr_type) = (inl_type?, inr_type?);
+            if inl_type != inr_type {
+                return Err(anyhow!("Arms of case have different types"));
+            }
+            Ok(inl_type)
+        }
+
+        //               Γ |- t_j : T_j
+        // ------------------------------------------- T-Variant
+        // Γ |- <l_j = t_j> as <l_i: T_i> : <l_i: T_i>
+        | Term::Variant { label, term, r#type } => {
+            let expected_type = match r#type {
+            | Type::Variant(types) => {
+                types
+                    .get(label)
+                    .ok_or_else(|| anyhow!("Label {} not in variant type {}", label, r#type))?
+            }
+            | _ => return Err(anyhow!("Annotation of variant not a variant type")),
+            };
+            let actual_type = term.check(context, depth)?;
+            if actual_type == *expected_type {
+                Ok(r#type.clone())
+            } else {
+                Err(anyhow!("Expected type {}, but found type {}", expected_type, actual_type))
+            }
+        }
+
+        // Γ |- t₀ : <l_i: T_i>    for each i, Γ, x_i : T_i |- t_i : T
+        // ---------------------------------------------------------- T-Case
+        //          Γ |- case t₀ of <l_i = x_i> ⇒ t_i : T
+        | Term::VariantCase { variant, arms } => {
+            let types = match variant.check(context, depth)? {
+            | Type::Variant(types) => types,
+            | _ => return Err(anyhow!("Can only case on variants")),
+            };
+            let mut case_type = None;
+            let mut covered = HashSet::new();
+            for arm in arms {
+                let arm_type = types
+                    .get(&arm.label)
+                    .ok_or_else(|| anyhow!("Label {} not in variant type {}", arm.label, Type::Variant(types.clone())))?;
+                if !covered.insert(&arm.label) {
+                    return Err(anyhow!("Duplicate case arm for label {}", arm.label));
+                }
+                context.0.insert(depth, arm_type.clone());
+                let body_type = arm.body.check(context, depth + 1);
+                context.0.remove(&depth);
+                let body_type = body_type?;
+                match &case_type {
+                | None => case_type = Some(body_type),
+                | Some(case_type) if *case_type == body_type => (),
+                | Some(_) => return Err(anyhow!("Arms of case have different types")),
+                }
+            }
+            let missing = types
+                .keys()
+                .filter(|label| !covered.contains(label))
+                .map(String::as_str)
+                .collect::<Vec<_>>();
+            if !missing.is_empty() {
+                return Err(anyhow!("Missing case arms for labels {}", missing.join(", ")));
+            }
+            case_type.ok_or_else(|| anyhow!("Case on empty variant type"))
+        }
+
+        // Γ |- t₁ : T₁ -> T₁
+        // ------------------ T-Fix
+        //  Γ |- fix t₁ : T₁
+        | Term::Fix(term) => {
+            match term.check(context, depth)? {
+            | Type::Fun(from, to) if from == to => Ok(*from),
+            | Type::Fun(_, _) => Err(anyhow!("Result of body not compatible with domain")),
+            | _ => Err(anyhow!("Function type expected")),
+            }
+        }
         }
     }
 }
@@ -0,0 +1,15 @@
+use typed_arena::Arena;
+
+/// Allocates terms that live as long as the allocator is borrowed.
+///
+/// Evaluation is generic over this trait so that terms can be allocated
+/// either in a plain `Arena`, or in an `Interner` that shares equal terms.
+pub trait Alloc<'a, T> {
+    fn alloc(&'a self, value: T) -> &'a T;
+}
+
+impl<'a, T> Alloc<'a, T> for Arena<T> {
+    fn alloc(&'a self, value: T) -> &'a T {
+        Arena::alloc(self, value)
+    }
+}
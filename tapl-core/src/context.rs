@@ -0,0 +1,87 @@
+use std::borrow::Cow;
+
+/// The naming context used to print nameless terms, mapping each de Bruijn
+/// index to the name of the binder it refers to.
+#[derive(Clone, Debug, Default)]
+pub struct Context(Vec<String>);
+
+impl Context {
+    pub fn len(&self) -> i64 {
+        self.0.len() as i64
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Primes `var` until it no longer shadows any name in scope.
+    pub fn fresh(&self, mut var: String) -> String {
+        while self.0.contains(&var) {
+            var.push('\'');
+        }
+        var
+    }
+
+    /// Binds the fresh version of `var`.
+    pub fn push(&mut self, var: String) -> &str {
+        let var = self.fresh(var);
+        self.0.push(var);
+        self.0.last().unwrap()
+    }
+
+    pub fn pop(&mut self) {
+        self.0.pop();
+    }
+
+    /// Binds `hint` for the duration of `scope`, which is given the name
+    /// actually chosen for it.
+    pub fn bind<T>(&mut self, hint: &str, scope: impl FnOnce(&mut Self, &str) -> T) -> T {
+        let name = self.push(hint.to_owned()).to_owned();
+        let result = scope(self, &name);
+        self.pop();
+        result
+    }
+
+    /// The name of the variable with de Bruijn index `index`. Free variables
+    /// are named `α`, `β`, `ξ`, `α1`, `β1`, `ξ1`, `α2`, and so on.
+    pub fn name(&self, index: i64) -> Cow<'_, str> {
+        const FREE: [&str; 3] = ["α", "β", "ξ"];
+        assert!(index >= 0);
+        if index >= self.len() {
+            let free = (index - self.len()) as usize;
+            match free / FREE.len() {
+            | 0 => Cow::Borrowed(FREE[free]),
+            | suffix => Cow::Owned(format!("{}{}", FREE[free % FREE.len()], suffix)),
+            }
+        } else {
+            let index = self.0.len() - 1 - index as usize;
+            Cow::Borrowed(&self.0[index])
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::context::Context;
+
+    #[test]
+    fn shadowing() {
+        let mut context = Context::default();
+        context.push(String::from("x"));
+        assert_eq!(context.push(String::from("x")), "x'");
+        context.bind("x", |context, name| {
+            assert_eq!(name, "x''");
+            assert_eq!(context.name(0), "x''");
+        });
+        assert_eq!(context.name(0), "x'");
+        assert_eq!(context.name(1), "x");
+    }
+
+    #[test]
+    fn free() {
+        let context = Context::default();
+        let names = (0..7).map(|index| context.name(index).into_owned()).collect::<Vec<_>>();
+        assert_eq!(names, ["α", "β", "ξ", "α1", "β1", "ξ1", "α2"]);
+    }
+}
@@ -0,0 +1,103 @@
+use std::collections::BTreeSet;
+
+use crate::alloc::Alloc;
+
+/// The binder structure of a nameless term, from which shifting,
+/// substitution and free variables are derived once for every calculus.
+///
+/// Implementations only describe how to traverse the immediate subterms of
+/// each constructor, so adding a constructor means extending `map` and `fold`
+/// rather than every traversal.
+pub trait DeBruijn<'a>: Clone + 'a {
+    /// The variable with de Bruijn index `index`.
+    fn var(index: i64) -> Self;
+
+    /// The de Bruijn index of this term, if it is a variable.
+    fn index(&self) -> Option<i64>;
+
+    /// Rebuilds this term with each immediate subterm `t` replaced by
+    /// `map(t, bound)`, where `bound` is the number of variables this term
+    /// binds in the scope of `t`. A `None` from `map` leaves `t` in place,
+    /// and the result is `None` if every subterm was left in place.
+    fn map<A, F>(&self, arena: &'a A, map: F) -> Option<Self>
+    where
+        A: Alloc<'a, Self>,
+        F: FnMut(&'a Self, i64) -> Option<Self>;
+
+    /// Folds `fold` over each immediate subterm `t` in order, along with the
+    /// number of variables this term binds in the scope of `t`.
+    fn fold<B, F>(&self, init: B, fold: F) -> B
+    where
+        F: FnMut(B, &'a Self, i64) -> B;
+
+    /// The de Bruijn indices of the free variables of this term, relative to
+    /// the context it appears in.
+    fn free(&self) -> BTreeSet<i64> {
+        let mut free = BTreeSet::new();
+        free_into(self, 0, &mut free);
+        free
+    }
+
+    fn is_closed(&self) -> bool {
+        self.free().is_empty()
+    }
+
+    /// Definition 6.2.1 - The `max_depth`-place shift `↑ self`. Subterms
+    /// without any free variable are shared rather than copied.
+    fn shift<A: Alloc<'a, Self>>(&self, arena: &'a A, max_depth: i64) -> Self {
+        shift(self, arena, max_depth, 0)
+            .unwrap_or_else(|| self.clone())
+    }
+
+    /// Definition 6.2.4 - The substitution `[from ↦ to] self`.
+    fn substitute<A: Alloc<'a, Self>>(&self, arena: &'a A, from: i64, to: &'a Self) -> Self {
+        substitute(self, arena, from, to, 0)
+            .unwrap_or_else(|| self.clone())
+    }
+
+    /// The substitution `↑⁻¹ ([0 ↦ ↑¹ to] self)` performed when a binder is
+    /// eliminated, fused into a single traversal. Subterms without any
+    /// affected variable are shared rather than copied.
+    fn substitute_top<A: Alloc<'a, Self>>(&self, arena: &'a A, to: &'a Self) -> Self {
+        substitute_top(self, arena, to, 0)
+            .unwrap_or_else(|| self.clone())
+    }
+}
+
+fn free_into<'a, T: DeBruijn<'a>>(term: &T, depth: i64, free: &mut BTreeSet<i64>) {
+    match term.index() {
+    | Some(index) if index >= depth => {
+        free.insert(index - depth);
+    }
+    | Some(_) => (),
+    | None => term.fold((), |(), term, bound| free_into(term, depth + bound, free)),
+    }
+}
+
+/// Returns `None` if `term` is unchanged by the shift.
+fn shift<'a, T: DeBruijn<'a>, A: Alloc<'a, T>>(term: &T, arena: &'a A, max_depth: i64, depth: i64) -> Option<T> {
+    match term.index() {
+    | Some(index) if index >= depth && max_depth != 0 => Some(T::var(index + max_depth)),
+    | Some(_) => None,
+    | None => term.map(arena, |term, bound| shift(term, arena, max_depth, depth + bound)),
+    }
+}
+
+/// Returns `None` if `term` is unchanged by the substitution.
+fn substitute<'a, T: DeBruijn<'a>, A: Alloc<'a, T>>(term: &T, arena: &'a A, from: i64, to: &'a T, depth: i64) -> Option<T> {
+    match term.index() {
+    | Some(index) if index == from + depth => Some(to.shift(arena, depth)),
+    | Some(_) => None,
+    | None => term.map(arena, |term, bound| substitute(term, arena, from, to, depth + bound)),
+    }
+}
+
+/// Returns `None` if `term` is unchanged by the substitution.
+fn substitute_top<'a, T: DeBruijn<'a>, A: Alloc<'a, T>>(term: &T, arena: &'a A, to: &'a T, depth: i64) -> Option<T> {
+    match term.index() {
+    | Some(index) if index == depth => Some(to.shift(arena, depth)),
+    | Some(index) if index > depth => Some(T::var(index - 1)),
+    | Some(_) => None,
+    | None => term.map(arena, |term, bound| substitute_top(term, arena, to, depth + bound)),
+    }
+}
@@ -0,0 +1,131 @@
+//! Graphviz DOT output for abstract syntax trees.
+//!
+//! Each calculus builds a `Tree` from its terms, drawing an edge from every
+//! node to its children, and a dashed edge from every bound variable to the
+//! node that binds it. Several trees can be written to the same file, one
+//! graph each, which `dot -O` renders to separate images.
+
+use std::io;
+
+/// An abstract syntax tree, built one node at a time.
+#[derive(Clone, Debug, Default)]
+pub struct Tree {
+    labels: Vec<String>,
+    children: Vec<(usize, usize, String)>,
+    binders: Vec<(usize, usize)>,
+    highlight: Option<usize>,
+}
+
+impl Tree {
+    /// Adds a node labeled `label`, returning its identifier.
+    pub fn node<S: Into<String>>(&mut self, label: S) -> usize {
+        self.labels.push(label.into());
+        self.labels.len() - 1
+    }
+
+    /// Adds an edge from `parent` to `child`, labeled `label` unless it is
+    /// empty. Children are drawn in the order they are added.
+    pub fn child(&mut self, parent: usize, label: &str, child: usize) {
+        self.children.push((parent, child, label.to_owned()));
+    }
+
+    /// Adds an edge from the variable `var` to its `binder`.
+    pub fn binder(&mut self, var: usize, binder: usize) {
+        self.binders.push((var, binder));
+    }
+
+    /// Highlights the subtree rooted at `node`, such as a redex.
+    pub fn highlight(&mut self, node: usize) {
+        self.highlight = Some(node);
+    }
+
+    /// Writes this tree as the graph `name` in the Graphviz DOT language.
+    pub fn write<W: io::Write>(&self, name: &str, writer: &mut W) -> anyhow::Result<()> {
+        let highlighted = self.highlighted();
+
+        writeln!(writer, "digraph {} {{", name)?;
+        writeln!(writer, "    node [shape=box];")?;
+
+        for (node, label) in self.labels.iter().enumerate() {
+            let style = if highlighted[node] { ", style=filled, fillcolor=yellow" } else { "" };
+            writeln!(writer, "    {} [label=\"{}\"{}];", node, escape(label), style)?;
+        }
+
+        for (parent, child, label) in &self.children {
+            if label.is_empty() {
+                writeln!(writer, "    {} -> {};", parent, child)?;
+            } else {
+                writeln!(writer, "    {} -> {} [label=\"{}\"];", parent, child, escape(label))?;
+            }
+        }
+
+        for (var, binder) in &self.binders {
+            writeln!(writer, "    {} -> {} [style=dashed, constraint=false];", var, binder)?;
+        }
+
+        writeln!(writer, "}}")?;
+        Ok(())
+    }
+
+    fn highlighted(&self) -> Vec<bool> {
+        let mut children = vec![Vec::new(); self.labels.len()];
+        for (parent, child, _) in &self.children {
+            children[*parent].push(*child);
+        }
+
+        let mut highlighted = vec![false; self.labels.len()];
+        let mut stack = self.highlight.into_iter().collect::<Vec<_>>();
+        while let Some(node) = stack.pop() {
+            highlighted[node] = true;
+            stack.extend(&children[node]);
+        }
+        highlighted
+    }
+}
+
+/// The rest of `position` below `child`, if `position` passes through it.
+///
+/// Used to follow the position of a redex down a term while building its
+/// tree, highlighting the node where the position runs out.
+pub fn descend<T: PartialEq>(position: Option<&[T]>, child: T) -> Option<&[T]> {
+    match position? {
+    | [head, tail @ ..] if *head == child => Some(tail),
+    | _ => None,
+    }
+}
+
+fn escape(label: &str) -> String {
+    label.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::dot::Tree;
+
+    #[test]
+    fn write() {
+        let mut tree = Tree::default();
+        let abs = tree.node("λx");
+        let app = tree.node("app");
+        let var = tree.node("\"x\"");
+        tree.child(abs, "body", app);
+        tree.child(app, "", var);
+        tree.binder(var, abs);
+        tree.highlight(app);
+
+        let mut dot = Vec::new();
+        tree.write("term", &mut dot).unwrap();
+        assert_eq!(String::from_utf8(dot).unwrap(), "\
+digraph term {
+    node [shape=box];
+    0 [label=\"λx\"];
+    1 [label=\"app\", style=filled, fillcolor=yellow];
+    2 [label=\"\\\"x\\\"\", style=filled, fillcolor=yellow];
+    0 -> 1 [label=\"body\"];
+    1 -> 2;
+    2 -> 0 [style=dashed, constraint=false];
+}
+");
+    }
+}
@@ -0,0 +1,122 @@
+//! Hash-consing, so that structurally equal terms share a single allocation.
+//!
+//! Once every subterm of a term is interned, two terms are equal exactly when
+//! they are the same allocation, so equality and hashing only need to look at
+//! a term's own fields and the addresses of its immediate subterms.
+
+use std::cell::RefCell;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::fmt;
+use std::hash::Hash;
+use std::hash::Hasher;
+use std::ops;
+use std::ptr;
+
+use typed_arena::Arena;
+
+use crate::alloc::Alloc;
+use crate::de_bruijn::DeBruijn;
+
+/// Equality and hashing that compare immediate subterms by address, which
+/// agree with structural equality and hashing on interned terms.
+pub trait HashCons {
+    fn shallow_eq(&self, other: &Self) -> bool;
+
+    fn shallow_hash<H: Hasher>(&self, hasher: &mut H);
+}
+
+/// An arena that allocates each distinct term exactly once.
+pub struct Interner<'a, T> {
+    arena: Arena<T>,
+    table: RefCell<HashMap<u64, Vec<&'a T>>>,
+}
+
+impl<'a, T> Default for Interner<'a, T> {
+    fn default() -> Self {
+        Interner {
+            arena: Arena::new(),
+            table: RefCell::default(),
+        }
+    }
+}
+
+impl<'a, T: HashCons> Interner<'a, T> {
+    /// Interns `term` and all of its subterms.
+    pub fn intern(&'a self, term: &T) -> &'a T
+    where
+        T: DeBruijn<'a>,
+    {
+        match term.map(self, |term, _| Some(self.intern(term).clone())) {
+        | Some(term) => self.alloc(term),
+        | None => self.alloc(term.clone()),
+        }
+    }
+
+    /// The number of distinct terms interned so far.
+    pub fn len(&self) -> usize {
+        self.arena.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<'a, T: HashCons> Alloc<'a, T> for Interner<'a, T> {
+    /// Returns the existing allocation equal to `value`, if any. The
+    /// immediate subterms of `value` must already be interned.
+    fn alloc(&'a self, value: T) -> &'a T {
+        let mut hasher = DefaultHasher::new();
+        value.shallow_hash(&mut hasher);
+
+        let mut table = self.table.borrow_mut();
+        let bucket = table.entry(hasher.finish()).or_default();
+        if let Some(term) = bucket.iter().find(|term| term.shallow_eq(&value)) {
+            return term;
+        }
+
+        let term = &*self.arena.alloc(value);
+        bucket.push(term);
+        term
+    }
+}
+
+/// An interned term, compared and hashed by address in constant time, for
+/// use as the key of a cache.
+pub struct Id<'a, T>(pub &'a T);
+
+impl<'a, T> Clone for Id<'a, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<'a, T> Copy for Id<'a, T> {}
+
+impl<'a, T> PartialEq for Id<'a, T> {
+    fn eq(&self, other: &Self) -> bool {
+        ptr::eq(self.0, other.0)
+    }
+}
+
+impl<'a, T> Eq for Id<'a, T> {}
+
+impl<'a, T> Hash for Id<'a, T> {
+    fn hash<H: Hasher>(&self, hasher: &mut H) {
+        ptr::hash(self.0, hasher)
+    }
+}
+
+impl<'a, T> ops::Deref for Id<'a, T> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        self.0
+    }
+}
+
+impl<'a, T: fmt::Debug> fmt::Debug for Id<'a, T> {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmt, "Id({:p}, {:?})", self.0, self.0)
+    }
+}
@@ -0,0 +1,145 @@
+//! Helpers for reading terms back from the externally tagged JSON produced by
+//! `#[derive(Serialize)]`. Terms cannot derive `Deserialize`, since their
+//! subterms must be allocated in a caller-provided arena.
+//!
+//! `read` raises the limit of 128 levels of nesting that `serde_json`
+//! otherwise enforces to `MAX_DEPTH`, and reads JSON up to that depth
+//! without overflowing the stack: parsing, and reading subterms through
+//! `grow`, move onto a fresh stack segment when the current one runs low,
+//! and the parsed `Value` is taken apart iteratively rather than dropped
+//! recursively. Comparing,
+//! serializing, or evaluating the term read back still recurses on its
+//! depth, which the limit bounds: deeper JSON is rejected with an error
+//! before it is parsed.
+
+use std::convert::TryInto;
+
+use anyhow::anyhow;
+use serde::Deserialize;
+use serde_json::Value;
+
+static UNIT: Value = Value::Null;
+
+/// The deepest nesting of arrays and objects accepted by `read`.
+pub const MAX_DEPTH: usize = 1 << 12;
+
+/// Parses JSON nested at most `MAX_DEPTH` levels deep, and reads the
+/// resulting `Value` with `read`.
+pub fn read<T>(json: &str, read: impl FnOnce(&Value) -> anyhow::Result<T>) -> anyhow::Result<T> {
+    let value = parse(json)?;
+    let result = read(&value);
+    dismantle(value);
+    result
+}
+
+fn parse(json: &str) -> anyhow::Result<Value> {
+    if depth(json) > MAX_DEPTH {
+        return Err(anyhow!("JSON nested deeper than {} levels", MAX_DEPTH));
+    }
+    let mut deserializer = serde_json::Deserializer::from_str(json);
+    deserializer.disable_recursion_limit();
+    let value = Value::deserialize(serde_stacker::Deserializer::new(&mut deserializer))?;
+    deserializer.end()?;
+    Ok(value)
+}
+
+/// Drops `value` without recursing on its depth, by moving the elements of
+/// each array and object out before dropping it.
+fn dismantle(value: Value) {
+    let mut stack = vec![value];
+    while let Some(value) = stack.pop() {
+        match value {
+        | Value::Array(elements) => stack.extend(elements),
+        | Value::Object(object) => stack.extend(object.into_iter().map(|(_, value)| value)),
+        | _ => (),
+        }
+    }
+}
+
+/// The deepest nesting of arrays and objects in `json`, found without
+/// recursing, and ignoring brackets inside strings. Malformed JSON is left
+/// for the parser to reject.
+fn depth(json: &str) -> usize {
+    let (mut depth, mut max) = (0usize, 0);
+    let mut chars = json.chars();
+    while let Some(char) = chars.next() {
+        match char {
+        | '[' | '{' => {
+            depth += 1;
+            max = max.max(depth);
+        }
+        | ']' | '}' => depth = depth.saturating_sub(1),
+        | '"' => {
+            while let Some(char) = chars.next() {
+                match char {
+                | '"' => break,
+                | '\\' => { chars.next(); }
+                | _ => (),
+                }
+            }
+        }
+        | _ => (),
+        }
+    }
+    max
+}
+
+/// Runs `read` on a subterm, first growing the stack if it is running low.
+pub fn grow<T>(read: impl FnOnce() -> T) -> T {
+    stacker::maybe_grow(64 * 1024, 1024 * 1024, read)
+}
+
+/// Splits a unit variant `"Tag"` or a variant `{"Tag": content}` into its tag
+/// and content, which is `null` for unit variants.
+pub fn variant(value: &Value) -> anyhow::Result<(&str, &Value)> {
+    match value {
+    | Value::String(tag) => Ok((tag, &UNIT)),
+    | Value::Object(object) if object.len() == 1 => {
+        let (tag, content) = object.iter().next().unwrap();
+        Ok((tag, content))
+    }
+    | _ => Err(anyhow!("Expected variant, but found {}", value)),
+    }
+}
+
+/// The field `name` of a struct variant's content.
+pub fn field<'v>(value: &'v Value, name: &str) -> anyhow::Result<&'v Value> {
+    value
+        .get(name)
+        .ok_or_else(|| anyhow!("Missing field {} in {}", name, value))
+}
+
+/// The elements of an array, such as a tuple variant's content.
+pub fn array(value: &Value) -> anyhow::Result<&[Value]> {
+    match value {
+    | Value::Array(elements) => Ok(elements),
+    | _ => Err(anyhow!("Expected array, but found {}", value)),
+    }
+}
+
+/// The elements of an array of exactly `N` elements.
+pub fn elements<const N: usize>(value: &Value) -> anyhow::Result<&[Value; N]> {
+    array(value)?
+        .try_into()
+        .map_err(|_| anyhow!("Expected {} elements, but found {}", N, value))
+}
+
+/// Deserializes a leaf of a term, such as an index, hint, or type.
+pub fn leaf<'v, T: Deserialize<'v>>(value: &'v Value) -> anyhow::Result<T> {
+    Ok(T::deserialize(value)?)
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::json;
+
+    #[test]
+    fn depth() {
+        assert_eq!(json::depth(r#"{"a": [1, {"b": "[[{{"}], "c": "\"]"}"#), 3);
+        let deep = "[".repeat(json::MAX_DEPTH + 1) + &"]".repeat(json::MAX_DEPTH + 1);
+        assert_eq!(json::read(&deep, |_| Ok(())).unwrap_err().to_string(), "JSON nested deeper than 4096 levels");
+        let deep = r#"{"a":["#.repeat(json::MAX_DEPTH / 2) + &"]}".repeat(json::MAX_DEPTH / 2);
+        assert!(json::read(&deep, |_| Ok(())).is_ok());
+    }
+}
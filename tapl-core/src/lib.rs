@@ -0,0 +1,14 @@
+pub mod alloc;
+pub mod context;
+pub mod de_bruijn;
+pub mod dot;
+pub mod intern;
+pub mod json;
+pub mod sexp;
+pub mod source;
+
+pub use alloc::Alloc;
+pub use context::Context;
+pub use de_bruijn::DeBruijn;
+pub use intern::Interner;
+pub use sexp::Sexp;
@@ -0,0 +1,171 @@
+//! A minimal S-expression syntax for exchanging terms with external tools.
+//!
+//! ```text
+//! sexp ::= atom | ( sexp* )
+//! ```
+//!
+//! Atoms are maximal runs of characters other than whitespace, parentheses,
+//! and `;`, which starts a comment running to the end of the line.
+
+use std::convert::TryInto;
+use std::fmt;
+use std::iter;
+use std::str;
+
+use anyhow::anyhow;
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Sexp {
+    Atom(String),
+    List(Vec<Sexp>),
+}
+
+impl Sexp {
+    pub fn atom<S: Into<String>>(atom: S) -> Self {
+        Sexp::Atom(atom.into())
+    }
+
+    /// The list `(head tail...)`.
+    pub fn form<I: IntoIterator<Item = Sexp>>(head: &str, tail: I) -> Self {
+        Sexp::List(iter::once(Sexp::atom(head)).chain(tail).collect())
+    }
+
+    /// Parses every S-expression in `source`.
+    pub fn parse_all(source: &str) -> anyhow::Result<Vec<Self>> {
+        let mut chars = source.chars().peekable();
+        let mut sexps = Vec::new();
+        while skip(&mut chars).is_some() {
+            sexps.push(parse(&mut chars)?);
+        }
+        Ok(sexps)
+    }
+
+    pub fn as_atom(&self) -> anyhow::Result<&str> {
+        match self {
+        | Sexp::Atom(atom) => Ok(atom),
+        | Sexp::List(_) => Err(anyhow!("Expected atom, but found {}", self)),
+        }
+    }
+
+    pub fn as_list(&self) -> anyhow::Result<&[Sexp]> {
+        match self {
+        | Sexp::List(list) => Ok(list),
+        | Sexp::Atom(_) => Err(anyhow!("Expected list, but found {}", self)),
+        }
+    }
+
+    /// Splits the list `(head tail...)` into its head atom and tail.
+    pub fn as_form(&self) -> anyhow::Result<(&str, &[Sexp])> {
+        match self.as_list()? {
+        | [head, tail @ ..] => Ok((head.as_atom()?, tail)),
+        | [] => Err(anyhow!("Expected form, but found ()")),
+        }
+    }
+
+    /// Parses this atom with `FromStr`.
+    pub fn parse_atom<T>(&self) -> anyhow::Result<T>
+    where
+        T: str::FromStr,
+        T::Err: std::error::Error + Send + Sync + 'static,
+    {
+        Ok(self.as_atom()?.parse()?)
+    }
+
+    /// Parses this atom as a de Bruijn index, which may not be negative.
+    pub fn parse_index(&self) -> anyhow::Result<i64> {
+        match self.parse_atom()? {
+        | index if index >= 0 => Ok(index),
+        | index => Err(anyhow!("Expected index, but found {}", index)),
+        }
+    }
+}
+
+/// The arguments `tail` of a form, which must number exactly `N`.
+pub fn arguments<'s, const N: usize>(head: &str, tail: &'s [Sexp]) -> anyhow::Result<&'s [Sexp; N]> {
+    tail.try_into()
+        .map_err(|_| anyhow!("Expected {} arguments to {}, but found {}", N, head, tail.len()))
+}
+
+impl str::FromStr for Sexp {
+    type Err = anyhow::Error;
+    fn from_str(source: &str) -> anyhow::Result<Self> {
+        let mut sexps = Sexp::parse_all(source)?;
+        match sexps.len() {
+        | 1 => Ok(sexps.pop().unwrap()),
+        | len => Err(anyhow!("Expected one S-expression, but found {}", len)),
+        }
+    }
+}
+
+impl fmt::Display for Sexp {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+        | Sexp::Atom(atom) => write!(fmt, "{}", atom),
+        | Sexp::List(list) => {
+            let mut list = list.iter();
+            write!(fmt, "(")?;
+            if let Some(head) = list.next() {
+                write!(fmt, "{}", head)?;
+            }
+            for tail in list {
+                write!(fmt, " {}", tail)?;
+            }
+            write!(fmt, ")")
+        }
+        }
+    }
+}
+
+type Chars<'s> = iter::Peekable<str::Chars<'s>>;
+
+/// Skips whitespace and comments, returning the next character if any.
+fn skip(chars: &mut Chars) -> Option<char> {
+    loop {
+        match *chars.peek()? {
+        | ';' => while chars.next_if(|char| *char != '\n').is_some() {},
+        | char if char.is_whitespace() => (),
+        | char => return Some(char),
+        }
+        chars.next();
+    }
+}
+
+fn parse(chars: &mut Chars) -> anyhow::Result<Sexp> {
+    match skip(chars) {
+    | None => Err(anyhow!("Unexpected end of input")),
+    | Some(')') => Err(anyhow!("Unexpected )")),
+    | Some('(') => {
+        chars.next();
+        let mut list = Vec::new();
+        while skip(chars) != Some(')') {
+            list.push(parse(chars)?);
+        }
+        chars.next();
+        Ok(Sexp::List(list))
+    }
+    | Some(_) => {
+        let mut atom = String::new();
+        while let Some(char) = chars.next_if(|char| !char.is_whitespace() && !"();".contains(*char)) {
+            atom.push(char);
+        }
+        Ok(Sexp::Atom(atom))
+    }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::sexp::Sexp;
+
+    #[test]
+    fn round_trip() {
+        let source = "(app (abs x bool (var 0)) ; identity\n (bool true))";
+        let sexp = source.parse::<Sexp>().unwrap();
+        assert_eq!(sexp.to_string(), "(app (abs x bool (var 0)) (bool true))");
+        assert_eq!(sexp.to_string().parse::<Sexp>().unwrap(), sexp);
+        assert_eq!(Sexp::parse_all("a (b) ()").unwrap().len(), 3);
+        assert!("(a".parse::<Sexp>().is_err());
+        assert!("a)".parse::<Sexp>().is_err());
+    }
+}
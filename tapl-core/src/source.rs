@@ -0,0 +1,159 @@
+//! The file format of the book's reference implementations, whose `test.f`
+//! files hold a sequence of `;`-terminated commands, with `/* ... */`
+//! comments that may nest.
+//!
+//! Each chapter parses the commands of its own calculus. Files are split
+//! into commands before parsing, so that an error in one command can be
+//! reported without losing the rest of the file.
+
+use std::iter;
+
+/// The tokens of a single command, without its terminating `;`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Command<'s> {
+    /// Line of the first token, counted from 1
+    pub line: usize,
+    pub tokens: Vec<&'s str>,
+}
+
+/// Splits `source` into commands, at each `;` that is not nested inside
+/// parentheses or braces. A final command need not be terminated, and an
+/// unmatched closing bracket is kept in its command for the parser to reject.
+pub fn commands(source: &str) -> Vec<Command<'_>> {
+    let mut commands = Vec::new();
+    let mut command = Command { line: 1, tokens: Vec::new() };
+    let mut depth = 0usize;
+    for (line, token) in lex(source) {
+        if command.tokens.is_empty() {
+            command.line = line;
+        }
+        match token {
+        | ";" if depth == 0 => {
+            commands.push(command);
+            command = Command { line, tokens: Vec::new() };
+            continue;
+        }
+        | "(" | "{" => depth += 1,
+        | ")" | "}" => depth = depth.saturating_sub(1),
+        | _ => (),
+        }
+        command.tokens.push(token);
+    }
+    if !command.tokens.is_empty() {
+        commands.push(command);
+    }
+    commands
+}
+
+/// Symbols of more than one character, which are lexed as single tokens.
+const SYMBOLS: [&str; 2] = ["->", "==>"];
+
+/// Splits `source` into tokens, each with the line it is on. Identifiers may
+/// contain primes and underscores, and any other character that is not
+/// whitespace is a token of its own, except for a string literal or one of
+/// `SYMBOLS`. Since rejecting unexpected tokens is left to the parser,
+/// lexing never fails, and an unterminated comment extends to the end.
+pub fn lex(source: &str) -> Vec<(usize, &str)> {
+    let mut tokens = Vec::new();
+    let mut line = 1;
+    let mut chars = source.char_indices().peekable();
+    while let Some((start, char)) = chars.next() {
+        let end = match char {
+        | '\n' => {
+            line += 1;
+            continue;
+        }
+        | char if char.is_whitespace() => continue,
+        | '/' if source[start..].starts_with("/*") => {
+            chars.next();
+            let mut depth = 1;
+            while depth > 0 {
+                match chars.next() {
+                | Some((_, '\n')) => line += 1,
+                | Some((index, '/')) if source[index..].starts_with("/*") => {
+                    chars.next();
+                    depth += 1;
+                }
+                | Some((index, '*')) if source[index..].starts_with("*/") => {
+                    chars.next();
+                    depth -= 1;
+                }
+                | Some(_) => (),
+                | None => break,
+                }
+            }
+            continue;
+        }
+        | char if char.is_alphanumeric() || char == '_' => {
+            iter::from_fn(|| chars.next_if(|(_, char)| char.is_alphanumeric() || *char == '_' || *char == '\''))
+                .last()
+                .map_or(start + char.len_utf8(), |(index, char)| index + char.len_utf8())
+        }
+        | '"' => {
+            let mut end = start + 1;
+            while let Some((index, char)) = chars.next_if(|(_, char)| *char != '\n') {
+                end = index + char.len_utf8();
+                if char == '"' {
+                    break;
+                }
+            }
+            end
+        }
+        | _ => {
+            let symbol = SYMBOLS
+                .iter()
+                .filter(|symbol| source[start..].starts_with(*symbol))
+                .max_by_key(|symbol| symbol.len());
+            match symbol {
+            | Some(symbol) => {
+                chars.nth(symbol.len() - 2);
+                start + symbol.len()
+            }
+            | None => start + char.len_utf8(),
+            }
+        }
+        };
+        tokens.push((line, &source[start..end]));
+    }
+    tokens
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::source;
+    use crate::source::Command;
+
+    #[test]
+    fn lex() {
+        let tokens = source::lex("/* a /* nested */ comment */ lambda x'. \"hello\" x->y; {a=1}.a_b");
+        let tokens = tokens.into_iter().map(|(_, token)| token).collect::<Vec<_>>();
+        assert_eq!(tokens, ["lambda", "x'", ".", "\"hello\"", "x", "->", "y", ";", "{", "a", "=", "1", "}", ".", "a_b"]);
+    }
+
+    #[test]
+    fn commands() {
+        let source = "
+            /* Examples for testing */
+            x/;
+            (lambda x. x; x)
+              x;
+
+            true";
+        assert_eq!(source::commands(source), [
+            Command { line: 3, tokens: vec!["x", "/"] },
+            Command { line: 4, tokens: vec!["(", "lambda", "x", ".", "x", ";", "x", ")", "x"] },
+            Command { line: 7, tokens: vec!["true"] },
+        ]);
+    }
+
+    /// An unmatched closing parenthesis does not swallow the `;`s after it.
+    #[test]
+    fn commands_unmatched() {
+        assert_eq!(source::commands("x); y; z"), [
+            Command { line: 1, tokens: vec!["x", ")"] },
+            Command { line: 1, tokens: vec!["y"] },
+            Command { line: 1, tokens: vec!["z"] },
+        ]);
+    }
+}
@@ -0,0 +1,158 @@
+//! Generating the expression that allocates a parsed term, following the
+//! representation of each chapter.
+
+use proc_macro2::Literal;
+use proc_macro2::TokenStream;
+use proc_macro2::TokenTree;
+use quote::quote;
+
+use crate::syntax::Dialect;
+use crate::syntax::Term;
+use crate::syntax::Type;
+
+pub struct Expander<'a> {
+    dialect: Dialect,
+    /// Path to the crate defining `term::Term` and `r#type::Type`
+    krate: &'a TokenTree,
+    /// Local variable bound to the arena
+    arena: &'a TokenStream,
+}
+
+impl<'a> Expander<'a> {
+    pub fn new(dialect: Dialect, krate: &'a TokenTree, arena: &'a TokenStream) -> Self {
+        Expander { dialect, krate, arena }
+    }
+
+    /// An expression for `term`, whose subterms are allocated in the arena.
+    pub fn term(&self, term: &Term) -> TokenStream {
+        let krate = self.krate;
+        let hint = |name: &str| quote!(::std::string::String::from(#name));
+        match (self.dialect, term) {
+        | (Dialect::Extended, Term::Var(index)) => {
+            let index = Literal::i64_unsuffixed(*index);
+            quote!(#krate::term::Term::Var(#index))
+        }
+        | (_, Term::Var(index)) => {
+            let index = Literal::i64_unsuffixed(*index);
+            quote!(#krate::term::Term::Var { index: #index })
+        }
+        | (_, Term::Bool(bool)) => quote!(#krate::term::Term::Bool(#bool)),
+        | (_, Term::If(r#if, then, r#else)) => {
+            let (r#if, then, r#else) = (self.alloc(r#if), self.alloc(then), self.alloc(r#else));
+            quote!(#krate::term::Term::If { r#if: #r#if, then: #then, r#else: #r#else })
+        }
+        | (Dialect::Untyped, Term::Abs(name, _, body)) => {
+            let (hint, body) = (hint(name), self.alloc(body));
+            quote!(#krate::term::Term::Abs { hint: #hint, term: #body })
+        }
+        | (Dialect::Simple, Term::Abs(name, Some(r#type), body)) => {
+            let (hint, r#type, body) = (hint(name), self.r#type(r#type), self.alloc(body));
+            quote!(#krate::term::Term::Abs { hint: #hint, r#type: #r#type, term: #body })
+        }
+        | (_, Term::Abs(name, r#type, body)) => {
+            let r#type = r#type.as_ref().expect("[INTERNAL ERROR]: parser accepted missing type annotation");
+            let (hint, r#type, body) = (hint(name), self.r#type(r#type), self.alloc(body));
+            quote!(#krate::term::Term::Abs { hint: #hint, r#type: #r#type, body: #body })
+        }
+        | (_, Term::App(fun, arg)) => {
+            let (fun, arg) = (self.alloc(fun), self.alloc(arg));
+            quote!(#krate::term::Term::App { fun: #fun, arg: #arg })
+        }
+        | (_, Term::Asc(term, r#type)) => {
+            let (term, r#type) = (self.alloc(term), self.r#type(r#type));
+            quote!(#krate::term::Term::Asc { term: #term, r#type: #r#type })
+        }
+        | (_, Term::Let(name, arg, body)) => {
+            let (hint, arg, body) = (hint(name), self.alloc(arg), self.alloc(body));
+            quote!(#krate::term::Term::Let { hint: #hint, arg: #arg, body: #body })
+        }
+        | (_, Term::Tuple(terms)) => {
+            let terms = terms.iter().map(|term| self.alloc(term));
+            quote!(#krate::term::Term::Tuple(::std::vec![#(#terms),*]))
+        }
+        | (_, Term::TupleProject(tuple, index)) => {
+            let (tuple, index) = (self.alloc(tuple), Literal::usize_unsuffixed(*index));
+            quote!(#krate::term::Term::TupleProject { tuple: #tuple, index: #index })
+        }
+        | (_, Term::Record(fields)) => {
+            let labels = fields.iter().map(|(label, _)| hint(label));
+            let terms = fields.iter().map(|(_, term)| self.alloc(term));
+            quote!(#krate::term::Term::Record(::std::vec![#((#labels, #terms)),*].into_iter().collect()))
+        }
+        | (_, Term::RecordProject(record, label)) => {
+            let (record, label) = (self.alloc(record), hint(label));
+            quote!(#krate::term::Term::RecordProject { record: #record, label: #label })
+        }
+        | (_, Term::Unit) => quote!(#krate::term::Term::Unit),
+        | (_, Term::Seq(first, second)) => {
+            let (first, second) = (self.alloc(first), self.alloc(second));
+            quote!(#krate::term::Term::Seq { first: #first, second: #second })
+        }
+        | (_, Term::Inl(term, r#type)) => {
+            let (term, r#type) = (self.alloc(term), self.r#type(r#type));
+            quote!(#krate::term::Term::Inl { term: #term, r#type: #r#type })
+        }
+        | (_, Term::Inr(term, r#type)) => {
+            let (term, r#type) = (self.alloc(term), self.r#type(r#type));
+            quote!(#krate::term::Term::Inr { term: #term, r#type: #r#type })
+        }
+        | (_, Term::Case(sum, inl_name, inl, inr_name, inr)) => {
+            let (sum, inl_hint, inl, inr_hint, inr) = (self.alloc(sum), hint(inl_name), self.alloc(inl), hint(inr_name), self.alloc(inr));
+            quote!(#krate::term::Term::Case { sum: #sum, inl_hint: #inl_hint, inl: #inl, inr_hint: #inr_hint, inr: #inr })
+        }
+        | (_, Term::Variant(label, term, r#type)) => {
+            let (label, term, r#type) = (hint(label), self.alloc(term), self.r#type(r#type));
+            quote!(#krate::term::Term::Variant { label: #label, term: #term, r#type: #r#type })
+        }
+        | (_, Term::VariantCase(variant, arms)) => {
+            let variant = self.alloc(variant);
+            let arms = arms.iter().map(|(label, name, body)| {
+                let (label, hint, body) = (hint(label), hint(name), self.alloc(body));
+                quote!(#krate::term::Arm { label: #label, hint: #hint, body: #body })
+            });
+            quote!(#krate::term::Term::VariantCase { variant: #variant, arms: ::std::vec![#(#arms),*] })
+        }
+        | (_, Term::Fix(term)) => {
+            let term = self.alloc(term);
+            quote!(#krate::term::Term::Fix(#term))
+        }
+        }
+    }
+
+    fn alloc(&self, term: &Term) -> TokenStream {
+        let arena = self.arena;
+        let term = self.term(term);
+        quote!(&*#arena.alloc(#term))
+    }
+
+    fn r#type(&self, r#type: &Type) -> TokenStream {
+        let krate = self.krate;
+        match r#type {
+        | Type::Bool => quote!(#krate::r#type::Type::Bool),
+        | Type::Fun(from, to) => {
+            let (from, to) = (self.r#type(from), self.r#type(to));
+            quote!(#krate::r#type::Type::Fun(::std::boxed::Box::new(#from), ::std::boxed::Box::new(#to)))
+        }
+        | Type::Tuple(types) => {
+            let types = types.iter().map(|r#type| self.r#type(r#type));
+            quote!(#krate::r#type::Type::Tuple(::std::vec![#(#types),*]))
+        }
+        | Type::Record(fields) => {
+            let labels = fields.iter().map(|(label, _)| quote!(::std::string::String::from(#label)));
+            let types = fields.iter().map(|(_, r#type)| self.r#type(r#type));
+            quote!(#krate::r#type::Type::Record(::std::vec![#((#labels, #types)),*].into_iter().collect()))
+        }
+        | Type::Unit => quote!(#krate::r#type::Type::Unit),
+        | Type::Base(name) => quote!(#krate::r#type::Type::Base(::std::string::String::from(#name))),
+        | Type::Sum(left, right) => {
+            let (left, right) = (self.r#type(left), self.r#type(right));
+            quote!(#krate::r#type::Type::Sum(::std::boxed::Box::new(#left), ::std::boxed::Box::new(#right)))
+        }
+        | Type::Variant(fields) => {
+            let labels = fields.iter().map(|(label, _)| quote!(::std::string::String::from(#label)));
+            let types = fields.iter().map(|(_, r#type)| self.r#type(r#type));
+            quote!(#krate::r#type::Type::Variant(::std::vec![#((#labels, #types)),*].into_iter().collect()))
+        }
+        }
+    }
+}
@@ -0,0 +1,63 @@
+//! The `term!` macro, which builds a term from its concrete syntax, resolving
+//! variables to de Bruijn indices at compile time.
+//!
+//! Each chapter wraps this macro in its own `term!`, which supplies the
+//! dialect and the path to the chapter's `Term` and `Type`.
+
+mod expand;
+mod syntax;
+
+use proc_macro2::Span;
+use proc_macro2::TokenStream;
+use proc_macro2::TokenTree;
+use quote::quote;
+use syn::parse::ParseStream;
+use syn::parse::Parser as _;
+use syn::Ident;
+use syn::Token;
+
+use crate::expand::Expander;
+use crate::syntax::Dialect;
+use crate::syntax::Parser;
+
+/// `term!(dialect, krate, arena, t)`, an expression of type `Term<'a>` for
+/// the term `t` in the given dialect, whose subterms are allocated by
+/// calling `alloc` on `arena`.
+#[proc_macro]
+pub fn term(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    match parse.parse(input) {
+    | Ok(tokens) => tokens.into(),
+    | Err(error) => error.to_compile_error().into(),
+    }
+}
+
+fn parse(input: ParseStream) -> syn::Result<TokenStream> {
+    let dialect = input.parse::<Ident>()?;
+    let dialect = match dialect.to_string().as_str() {
+    | "untyped" => Dialect::Untyped,
+    | "simple" => Dialect::Simple,
+    | "extended" => Dialect::Extended,
+    | _ => return Err(syn::Error::new(dialect.span(), "Expected untyped, simple, or extended")),
+    };
+    input.parse::<Token![,]>()?;
+    let krate = input.parse::<TokenTree>()?;
+    input.parse::<Token![,]>()?;
+    let arena_expr = input.parse::<TokenTree>()?;
+    input.parse::<Token![,]>()?;
+
+    let term = Parser::new(dialect).term(input)?;
+    if !input.is_empty() {
+        return Err(input.error("Unexpected token after term"));
+    }
+
+    let arena = Ident::new("arena", Span::mixed_site());
+    let arena = quote!(#arena);
+    let term = Expander::new(dialect, &krate, &arena).term(&term);
+    Ok(quote! {
+        {
+            #[allow(unused_variables)]
+            let #arena = &#arena_expr;
+            #term
+        }
+    })
+}
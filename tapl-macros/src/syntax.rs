@@ -0,0 +1,709 @@
+//! Parsing the concrete syntax of terms, resolving variables to de Bruijn
+//! indices as they are read.
+//!
+//! ```text
+//! t ::= λx. t | λx: T. t            abstraction
+//!     | if t then t else t          conditional
+//!     | let x = t in t              let binding
+//!     | letrec x: T = t in t        recursive let binding
+//!     | t t                         application
+//!     | fix t                       fixed point
+//!     | t as T                      ascription
+//!     | t.i | t.l                   projection, with i counted from 0
+//!     | (t, ..., t) | {l = t, ...}  tuple, record
+//!     | (t; t)                      sequence
+//!     | inl t as T | inr t as T     injection
+//!     | case t of inl x => t | inr x => t
+//!     | <l = t> as T                variant
+//!     | case t of <l = x> => t | ...
+//!     | (t) | x | true | false | unit
+//!
+//! T ::= bool | Unit | A | T -> T | T + T | (T) | (T, ..., T) | {l: T, ...}
+//!     | <l: T, ...>
+//! ```
+//!
+//! As in the book, `letrec x: T = t₁ in t₂` is read as its expansion
+//! `let x = fix (λx: T. t₁) in t₂`, and `fix` only appears in function
+//! position.
+//!
+//! Base types `A` are the capitalized names other than `Unit`, and sums are
+//! left associative, binding more tightly than function types.
+//!
+//! Rust lexes `λx` as a single identifier, so `λx` and `λ x` are both
+//! accepted. A parenthesized term followed by a comma, as in `(t,)`, is a
+//! tuple of one element.
+
+use std::collections::HashSet;
+
+use proc_macro2::Span;
+use syn::ext::IdentExt as _;
+use syn::parse::ParseStream;
+use syn::Ident;
+use syn::Token;
+
+/// The calculus a term is written in, which determines the forms it may use.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Dialect {
+    /// The untyped lambda calculus of chapter 7
+    Untyped,
+    /// The simply typed lambda calculus over booleans of chapter 10
+    Simple,
+    /// The simply typed lambda calculus with the extensions of chapter 11
+    Extended,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Term {
+    Var(i64),
+    Bool(bool),
+    If(Box<Term>, Box<Term>, Box<Term>),
+    Abs(String, Option<Type>, Box<Term>),
+    App(Box<Term>, Box<Term>),
+    Asc(Box<Term>, Type),
+    Let(String, Box<Term>, Box<Term>),
+    Tuple(Vec<Term>),
+    TupleProject(Box<Term>, usize),
+    Record(Vec<(String, Term)>),
+    RecordProject(Box<Term>, String),
+    Unit,
+    Seq(Box<Term>, Box<Term>),
+    Inl(Box<Term>, Type),
+    Inr(Box<Term>, Type),
+    Case(Box<Term>, String, Box<Term>, String, Box<Term>),
+    Variant(String, Box<Term>, Type),
+    /// The arms `<l = x> => t` in order, as `(l, x, t)`
+    VariantCase(Box<Term>, Vec<(String, String, Term)>),
+    Fix(Box<Term>),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Type {
+    Bool,
+    Fun(Box<Type>, Box<Type>),
+    Tuple(Vec<Type>),
+    Record(Vec<(String, Type)>),
+    Unit,
+    Base(String),
+    Sum(Box<Type>, Box<Type>),
+    Variant(Vec<(String, Type)>),
+}
+
+/// Words that cannot be variables, since they delimit other forms.
+const RESERVED: &[&str] = &[
+    "if", "then", "else", "let", "in", "as", "true", "false", "unit", "inl", "inr", "case", "of", "fix", "letrec",
+];
+
+pub struct Parser {
+    dialect: Dialect,
+    /// Names of the variables in scope, from the outermost binder in
+    context: Vec<String>,
+}
+
+impl Parser {
+    pub fn new(dialect: Dialect) -> Self {
+        Parser { dialect, context: Vec::new() }
+    }
+
+    pub fn term(&mut self, input: ParseStream) -> syn::Result<Term> {
+        if lambda(input).is_some() {
+            self.abs(input)
+        } else if keyword(input, "if") {
+            self.r#if(input)
+        } else if keyword(input, "let") {
+            self.r#let(input)
+        } else if keyword(input, "letrec") {
+            self.letrec(input)
+        } else if keyword(input, "inl") || keyword(input, "inr") {
+            self.inject(input)
+        } else if keyword(input, "case") {
+            self.case(input)
+        } else if input.peek(Token![<]) {
+            self.variant(input)
+        } else {
+            self.app(input)
+        }
+    }
+
+    fn abs(&mut self, input: ParseStream) -> syn::Result<Term> {
+        let name = match lambda(input) {
+        | Some(name) if name.is_empty() => {
+            input.parse::<Ident>()?;
+            input.call(Ident::parse_any)?
+        }
+        | Some(name) => Ident::new(&name, input.parse::<Ident>()?.span()),
+        | None => return Err(input.error("Expected abstraction")),
+        };
+
+        let r#type = if input.peek(Token![:]) {
+            let colon = input.parse::<Token![:]>()?;
+            if self.dialect == Dialect::Untyped {
+                return Err(syn::Error::new(colon.span, "Type annotation in untyped term"));
+            }
+            Some(self.r#type(input)?)
+        } else if self.dialect != Dialect::Untyped {
+            return Err(syn::Error::new(name.span(), format!("Missing type annotation for {}", name)));
+        } else {
+            None
+        };
+
+        input.parse::<Token![.]>()?;
+        let body = self.bind(&name, input)?;
+        Ok(Term::Abs(name.to_string(), r#type, Box::new(body)))
+    }
+
+    fn r#if(&mut self, input: ParseStream) -> syn::Result<Term> {
+        self.allow(Dialect::Simple, expect(input, "if")?, "Conditional")?;
+        let r#if = self.term(input)?;
+        expect(input, "then")?;
+        let then = self.term(input)?;
+        expect(input, "else")?;
+        let r#else = self.term(input)?;
+        Ok(Term::If(Box::new(r#if), Box::new(then), Box::new(r#else)))
+    }
+
+    fn r#let(&mut self, input: ParseStream) -> syn::Result<Term> {
+        self.allow(Dialect::Extended, expect(input, "let")?, "Let binding")?;
+        let name = input.call(Ident::parse_any)?;
+        input.parse::<Token![=]>()?;
+        let arg = self.term(input)?;
+        expect(input, "in")?;
+        let body = self.bind(&name, input)?;
+        Ok(Term::Let(name.to_string(), Box::new(arg), Box::new(body)))
+    }
+
+    fn letrec(&mut self, input: ParseStream) -> syn::Result<Term> {
+        self.allow(Dialect::Extended, expect(input, "letrec")?, "Recursive let binding")?;
+        let name = input.call(Ident::parse_any)?;
+        input.parse::<Token![:]>()?;
+        let r#type = self.r#type(input)?;
+        input.parse::<Token![=]>()?;
+        let arg = self.bind(&name, input)?;
+        expect(input, "in")?;
+        let body = self.bind(&name, input)?;
+        let fix = Term::Fix(Box::new(Term::Abs(name.to_string(), Some(r#type), Box::new(arg))));
+        Ok(Term::Let(name.to_string(), Box::new(fix), Box::new(body)))
+    }
+
+    /// An injection, whose ascription is part of its syntax.
+    fn inject(&mut self, input: ParseStream) -> syn::Result<Term> {
+        let inject = input.call(Ident::parse_any)?;
+        self.allow(Dialect::Extended, inject.span(), "Injection")?;
+        let term = Box::new(self.path(input)?);
+        expect(input, "as")?;
+        let r#type = self.r#type(input)?;
+        if inject == "inl" {
+            Ok(Term::Inl(term, r#type))
+        } else {
+            Ok(Term::Inr(term, r#type))
+        }
+    }
+
+    /// A variant, whose ascription is part of its syntax.
+    fn variant(&mut self, input: ParseStream) -> syn::Result<Term> {
+        let open = input.parse::<Token![<]>()?;
+        self.allow(Dialect::Extended, open.span, "Variant")?;
+        let label = input.call(Ident::parse_any)?;
+        input.parse::<Token![=]>()?;
+        let term = self.term(input)?;
+        input.parse::<Token![>]>()?;
+        expect(input, "as")?;
+        let r#type = self.r#type(input)?;
+        Ok(Term::Variant(label.to_string(), Box::new(term), r#type))
+    }
+
+    /// A case on a sum or a variant, whose arms other than the last extend
+    /// as far right as possible, so a case nested in them must be
+    /// parenthesized.
+    fn case(&mut self, input: ParseStream) -> syn::Result<Term> {
+        self.allow(Dialect::Extended, expect(input, "case")?, "Case")?;
+        let sum = self.term(input)?;
+        expect(input, "of")?;
+        if input.peek(Token![<]) {
+            return self.variant_case(sum, input);
+        }
+        expect(input, "inl")?;
+        let inl_name = input.call(Ident::parse_any)?;
+        input.parse::<Token![=>]>()?;
+        let inl = self.bind(&inl_name, input)?;
+        input.parse::<Token![|]>()?;
+        expect(input, "inr")?;
+        let inr_name = input.call(Ident::parse_any)?;
+        input.parse::<Token![=>]>()?;
+        let inr = self.bind(&inr_name, input)?;
+        Ok(Term::Case(Box::new(sum), inl_name.to_string(), Box::new(inl), inr_name.to_string(), Box::new(inr)))
+    }
+
+    /// The arms `<l = x> => t | ...` of a case on `variant`, in order.
+    fn variant_case(&mut self, variant: Term, input: ParseStream) -> syn::Result<Term> {
+        let mut arms = Vec::new();
+        loop {
+            input.parse::<Token![<]>()?;
+            let label = input.call(Ident::parse_any)?;
+            input.parse::<Token![=]>()?;
+            let name = input.call(Ident::parse_any)?;
+            input.parse::<Token![>]>()?;
+            input.parse::<Token![=>]>()?;
+            let body = self.bind(&name, input)?;
+            arms.push((label.to_string(), name.to_string(), body));
+            if !input.peek(Token![|]) {
+                return Ok(Term::VariantCase(Box::new(variant), arms));
+            }
+            input.parse::<Token![|]>()?;
+        }
+    }
+
+    /// Sequencing, which is right associative and binds more loosely than
+    /// any other term, so it only appears in parentheses.
+    fn seq(&mut self, input: ParseStream) -> syn::Result<Term> {
+        let first = self.term(input)?;
+        if input.peek(Token![;]) {
+            let semi = input.parse::<Token![;]>()?;
+            self.allow(Dialect::Extended, semi.span, "Sequence")?;
+            let second = self.seq(input)?;
+            return Ok(Term::Seq(Box::new(first), Box::new(second)));
+        }
+        Ok(first)
+    }
+
+    /// Parses the rest of a term with `name` in scope.
+    fn bind(&mut self, name: &Ident, input: ParseStream) -> syn::Result<Term> {
+        self.context.push(name.to_string());
+        let term = self.term(input);
+        self.context.pop();
+        term
+    }
+
+    /// Application, which is left associative. An abstraction, conditional,
+    /// let binding, injection, variant, or case in argument position extends
+    /// as far right as possible.
+    fn app(&mut self, input: ParseStream) -> syn::Result<Term> {
+        let mut fun = self.fix(input)?;
+        loop {
+            let extends = ["if", "let", "inl", "inr", "case"].iter().any(|word| keyword(input, word));
+            if lambda(input).is_some() || input.peek(Token![<]) || extends {
+                let arg = self.term(input)?;
+                return Ok(Term::App(Box::new(fun), Box::new(arg)));
+            } else if atom(input) {
+                let arg = self.asc(input)?;
+                fun = Term::App(Box::new(fun), Box::new(arg));
+            } else {
+                return Ok(fun);
+            }
+        }
+    }
+
+    fn fix(&mut self, input: ParseStream) -> syn::Result<Term> {
+        if keyword(input, "fix") {
+            self.allow(Dialect::Extended, expect(input, "fix")?, "Fixed point")?;
+            return Ok(Term::Fix(Box::new(self.asc(input)?)));
+        }
+        self.asc(input)
+    }
+
+    fn asc(&mut self, input: ParseStream) -> syn::Result<Term> {
+        let term = self.path(input)?;
+        if keyword(input, "as") {
+            self.allow(Dialect::Extended, expect(input, "as")?, "Ascription")?;
+            let r#type = self.r#type(input)?;
+            return Ok(Term::Asc(Box::new(term), r#type));
+        }
+        Ok(term)
+    }
+
+    fn path(&mut self, input: ParseStream) -> syn::Result<Term> {
+        let mut term = self.atom(input)?;
+        while input.peek(Token![.]) {
+            let dot = input.parse::<Token![.]>()?;
+            self.allow(Dialect::Extended, dot.span, "Projection")?;
+            if input.peek(syn::LitInt) {
+                let index = input.parse::<syn::LitInt>()?.base10_parse()?;
+                term = Term::TupleProject(Box::new(term), index);
+            } else if input.peek(syn::LitFloat) {
+                // `t.0.1` is lexed as `t`, `.`, and the float literal `0.1`
+                let float = input.parse::<syn::LitFloat>()?;
+                for index in float.base10_digits().split('.') {
+                    let index = index.parse().map_err(|_| syn::Error::new(float.span(), "Expected tuple index"))?;
+                    term = Term::TupleProject(Box::new(term), index);
+                }
+            } else {
+                let label = input.call(Ident::parse_any)?;
+                term = Term::RecordProject(Box::new(term), label.to_string());
+            }
+        }
+        Ok(term)
+    }
+
+    fn atom(&mut self, input: ParseStream) -> syn::Result<Term> {
+        if input.peek(syn::token::Paren) {
+            let content;
+            let paren = syn::parenthesized!(content in input);
+            let (mut terms, comma) = sequence(&content, |input| self.seq(input))?;
+            if terms.len() == 1 && !comma {
+                return Ok(terms.remove(0));
+            }
+            self.allow(Dialect::Extended, paren.span.join(), "Tuple")?;
+            return Ok(Term::Tuple(terms));
+        }
+
+        if input.peek(syn::token::Brace) {
+            let content;
+            let brace = syn::braced!(content in input);
+            self.allow(Dialect::Extended, brace.span.join(), "Record")?;
+            let fields = fields(&content, |input| {
+                input.parse::<Token![=]>()?;
+                self.term(input)
+            })?;
+            return Ok(Term::Record(fields));
+        }
+
+        let ident = input.call(Ident::parse_any)?;
+        match ident.to_string().as_str() {
+        | bool @ "true" | bool @ "false" => {
+            self.allow(Dialect::Simple, ident.span(), "Boolean")?;
+            Ok(Term::Bool(bool == "true"))
+        }
+        | "unit" => {
+            self.allow(Dialect::Extended, ident.span(), "Unit")?;
+            Ok(Term::Unit)
+        }
+        | name if RESERVED.contains(&name) => Err(syn::Error::new(ident.span(), format!("Unexpected {}", name))),
+        | name => {
+            self.context
+                .iter()
+                .rev()
+                .position(|bound| bound == name)
+                .map(|index| Term::Var(index as i64))
+                .ok_or_else(|| syn::Error::new(ident.span(), format!("Unbound variable: {}", name)))
+        }
+        }
+    }
+
+    /// Function types, which are right associative.
+    fn r#type(&mut self, input: ParseStream) -> syn::Result<Type> {
+        let from = self.sum_type(input)?;
+        if input.peek(Token![->]) {
+            input.parse::<Token![->]>()?;
+            let to = self.r#type(input)?;
+            return Ok(Type::Fun(Box::new(from), Box::new(to)));
+        }
+        Ok(from)
+    }
+
+    /// Sum types, which are left associative.
+    fn sum_type(&mut self, input: ParseStream) -> syn::Result<Type> {
+        let mut left = self.atom_type(input)?;
+        while input.peek(Token![+]) {
+            let plus = input.parse::<Token![+]>()?;
+            self.allow(Dialect::Extended, plus.span, "Sum type")?;
+            left = Type::Sum(Box::new(left), Box::new(self.atom_type(input)?));
+        }
+        Ok(left)
+    }
+
+    fn atom_type(&mut self, input: ParseStream) -> syn::Result<Type> {
+        if input.peek(Token![<]) {
+            let open = input.parse::<Token![<]>()?;
+            self.allow(Dialect::Extended, open.span, "Variant type")?;
+            let mut labels = HashSet::new();
+            let mut fields = Vec::new();
+            loop {
+                let label = input.call(Ident::parse_any)?;
+                if !labels.insert(label.to_string()) {
+                    return Err(syn::Error::new(label.span(), format!("Duplicate variant label: {}", label)));
+                }
+                input.parse::<Token![:]>()?;
+                fields.push((label.to_string(), self.r#type(input)?));
+                if input.peek(Token![>]) {
+                    input.parse::<Token![>]>()?;
+                    return Ok(Type::Variant(fields));
+                }
+                input.parse::<Token![,]>()?;
+            }
+        }
+
+        if input.peek(syn::token::Paren) {
+            let content;
+            let paren = syn::parenthesized!(content in input);
+            let (mut types, comma) = sequence(&content, |input| self.r#type(input))?;
+            if types.len() == 1 && !comma {
+                return Ok(types.remove(0));
+            }
+            self.allow(Dialect::Extended, paren.span.join(), "Tuple type")?;
+            return Ok(Type::Tuple(types));
+        }
+
+        if input.peek(syn::token::Brace) {
+            let content;
+            let brace = syn::braced!(content in input);
+            self.allow(Dialect::Extended, brace.span.join(), "Record type")?;
+            let fields = fields(&content, |input| {
+                input.parse::<Token![:]>()?;
+                self.r#type(input)
+            })?;
+            return Ok(Type::Record(fields));
+        }
+
+        let ident = input.call(Ident::parse_any)?;
+        match ident.to_string().as_str() {
+        | "Bool" | "bool" => Ok(Type::Bool),
+        | "Unit" => {
+            self.allow(Dialect::Extended, ident.span(), "Unit type")?;
+            Ok(Type::Unit)
+        }
+        | name if name.starts_with(char::is_uppercase) => {
+            self.allow(Dialect::Extended, ident.span(), "Base type")?;
+            Ok(Type::Base(name.to_owned()))
+        }
+        | name => Err(syn::Error::new(ident.span(), format!("Unknown type: {}", name))),
+        }
+    }
+
+    /// Fails if the current dialect lacks `form`, which chapter `since`
+    /// introduced.
+    fn allow(&self, since: Dialect, span: Span, form: &str) -> syn::Result<()> {
+        let allowed = matches!(
+            (self.dialect, since),
+            (_, Dialect::Untyped) | (Dialect::Simple, Dialect::Simple) | (Dialect::Extended, _)
+        );
+        if allowed {
+            Ok(())
+        } else {
+            Err(syn::Error::new(span, format!("{} is not supported in this calculus", form)))
+        }
+    }
+}
+
+/// The name bound by the abstraction starting at the next token, which is
+/// empty if the `λ` is followed by whitespace.
+fn lambda(input: ParseStream) -> Option<String> {
+    let (ident, _) = input.cursor().ident()?;
+    ident.to_string().strip_prefix('λ').map(String::from)
+}
+
+fn keyword(input: ParseStream, keyword: &str) -> bool {
+    input
+        .cursor()
+        .ident()
+        .is_some_and(|(ident, _)| ident == keyword)
+}
+
+fn expect(input: ParseStream, keyword: &str) -> syn::Result<Span> {
+    match input.cursor().ident() {
+    | Some((ident, _)) if ident == keyword => Ok(input.call(Ident::parse_any)?.span()),
+    | _ => Err(input.error(format!("Expected {}", keyword))),
+    }
+}
+
+/// Whether the next token can start an argument without extending past it.
+fn atom(input: ParseStream) -> bool {
+    if input.peek(syn::token::Paren) || input.peek(syn::token::Brace) {
+        return true;
+    }
+    match input.cursor().ident() {
+    | Some((ident, _)) => {
+        let ident = ident.to_string();
+        ["true", "false", "unit"].contains(&ident.as_str()) || !RESERVED.contains(&ident.as_str())
+    }
+    | None => false,
+    }
+}
+
+/// Comma-separated items, and whether there was a trailing comma.
+fn sequence<T, F>(input: ParseStream, mut item: F) -> syn::Result<(Vec<T>, bool)>
+where
+    F: FnMut(ParseStream) -> syn::Result<T>,
+{
+    let mut items = Vec::new();
+    let mut comma = false;
+    while !input.is_empty() {
+        items.push(item(input)?);
+        comma = input.peek(Token![,]);
+        if !comma {
+            break;
+        }
+        input.parse::<Token![,]>()?;
+    }
+    if !input.is_empty() {
+        return Err(input.error("Expected , or end of sequence"));
+    }
+    Ok((items, comma))
+}
+
+/// Comma-separated fields `l _`, read in order, where `item` reads the part
+/// after the label.
+fn fields<T, F>(input: ParseStream, mut item: F) -> syn::Result<Vec<(String, T)>>
+where
+    F: FnMut(ParseStream) -> syn::Result<T>,
+{
+    let mut labels = HashSet::new();
+    let (fields, _) = sequence(input, |input| {
+        let label = input.call(Ident::parse_any)?;
+        if !labels.insert(label.to_string()) {
+            return Err(syn::Error::new(label.span(), format!("Duplicate record label: {}", label)));
+        }
+        Ok((label.to_string(), item(input)?))
+    })?;
+    Ok(fields)
+}
+
+#[cfg(test)]
+mod tests {
+
+    use quote::quote;
+    use syn::parse::Parser as _;
+
+    use super::Dialect;
+    use super::Parser;
+    use super::Term;
+    use super::Type;
+
+    fn parse(dialect: Dialect, tokens: proc_macro2::TokenStream) -> syn::Result<Term> {
+        let mut parser = Parser::new(dialect);
+        (|input: syn::parse::ParseStream| parser.term(input)).parse2(tokens)
+    }
+
+    fn var(index: i64) -> Box<Term> {
+        Box::new(Term::Var(index))
+    }
+
+    fn abs(name: &str, r#type: Option<Type>, body: Term) -> Term {
+        Term::Abs(name.to_owned(), r#type, Box::new(body))
+    }
+
+    /// Variables resolve to the number of binders between them and their
+    /// binder, and application associates to the left.
+    #[test]
+    fn de_bruijn() {
+        let term = parse(Dialect::Untyped, quote!(λx. λ y. x y (λz. z x))).unwrap();
+        let body = Term::App(
+            Box::new(Term::App(var(1), var(0))),
+            Box::new(abs("z", None, Term::App(var(0), var(2)))),
+        );
+        assert_eq!(term, abs("x", None, abs("y", None, body)));
+    }
+
+    #[test]
+    fn extended() {
+        let term = parse(Dialect::Extended, quote! {
+            let r = {a = (true, λx: bool -> bool. x)} in r.a.1 as bool -> bool
+        }).unwrap();
+        let fun = Type::Fun(Box::new(Type::Bool), Box::new(Type::Bool));
+        let record = Term::Record(vec![(String::from("a"), Term::Tuple(vec![Term::Bool(true), abs("x", Some(fun.clone()), Term::Var(0))]))]);
+        let body = Term::Asc(Box::new(Term::TupleProject(Box::new(Term::RecordProject(var(0), String::from("a"))), 1)), fun);
+        assert_eq!(term, Term::Let(String::from("r"), Box::new(record), Box::new(body)));
+
+        let term = parse(Dialect::Extended, quote!((((true,),)).0.0)).unwrap();
+        let tuple = Term::Tuple(vec![Term::Tuple(vec![Term::Bool(true)])]);
+        assert_eq!(term, Term::TupleProject(Box::new(Term::TupleProject(Box::new(tuple), 0)), 0));
+    }
+
+    /// Sequencing is right associative, and a sequence in a tuple extends
+    /// to the next comma.
+    #[test]
+    fn seq() {
+        let term = parse(Dialect::Extended, quote!(λu: Unit. (u; unit; (u; true), false))).unwrap();
+        let seq = Term::Seq(var(0), Box::new(Term::Seq(Box::new(Term::Unit), Box::new(Term::Seq(var(0), Box::new(Term::Bool(true)))))));
+        assert_eq!(term, abs("u", Some(Type::Unit), Term::Tuple(vec![seq, Term::Bool(false)])));
+
+        let term = parse(Dialect::Extended, quote!((λu: Unit. u) unit)).unwrap();
+        assert_eq!(term, Term::App(Box::new(abs("u", Some(Type::Unit), Term::Var(0))), Box::new(Term::Unit)));
+    }
+
+    /// Sums bind more tightly than function types, and the first arm of a
+    /// case ends at `|`.
+    #[test]
+    fn sum() {
+        let term = parse(Dialect::Extended, quote! {
+            λf: bool + Unit -> bool. case inr unit as bool + Unit of inl x => f (inl x as bool + Unit) | inr y => false
+        }).unwrap();
+        let sum = Type::Sum(Box::new(Type::Bool), Box::new(Type::Unit));
+        let inl = Term::App(var(1), Box::new(Term::Inl(var(0), sum.clone())));
+        let case = Term::Case(
+            Box::new(Term::Inr(Box::new(Term::Unit), sum.clone())),
+            String::from("x"),
+            Box::new(inl),
+            String::from("y"),
+            Box::new(Term::Bool(false)),
+        );
+        assert_eq!(term, abs("f", Some(Type::Fun(Box::new(sum), Box::new(Type::Bool))), case));
+
+        let term = parse(Dialect::Extended, quote!(λx: A + B + C. x)).unwrap();
+        let base = |name: &str| Box::new(Type::Base(name.to_owned()));
+        let sum = Type::Sum(Box::new(Type::Sum(base("A"), base("B"))), base("C"));
+        assert_eq!(term, abs("x", Some(sum), Term::Var(0)));
+    }
+
+    /// The arms of a case on a variant are read in order, and `>` closes a
+    /// variant type even when it ends in a function type.
+    #[test]
+    fn variant() {
+        let term = parse(Dialect::Extended, quote! {
+            λt: <none: Unit, some: bool -> bool>. case t of <some = f> => f true | <none = u> => (u; false)
+        }).unwrap();
+        let fun = Type::Fun(Box::new(Type::Bool), Box::new(Type::Bool));
+        let r#type = Type::Variant(vec![(String::from("none"), Type::Unit), (String::from("some"), fun)]);
+        let arms = vec![
+            (String::from("some"), String::from("f"), Term::App(var(0), Box::new(Term::Bool(true)))),
+            (String::from("none"), String::from("u"), Term::Seq(var(0), Box::new(Term::Bool(false)))),
+        ];
+        assert_eq!(term, abs("t", Some(r#type), Term::VariantCase(var(0), arms)));
+
+        let term = parse(Dialect::Extended, quote!(λf: <a: bool> -> bool. f <a = true> as <a: bool>)).unwrap();
+        let r#type = Type::Variant(vec![(String::from("a"), Type::Bool)]);
+        let variant = Term::Variant(String::from("a"), Box::new(Term::Bool(true)), r#type.clone());
+        let fun = Type::Fun(Box::new(r#type), Box::new(Type::Bool));
+        assert_eq!(term, abs("f", Some(fun), Term::App(var(0), Box::new(variant))));
+    }
+
+    /// `letrec` is read as its expansion, binding its name in both its
+    /// argument and its body.
+    #[test]
+    fn fix() {
+        let term = parse(Dialect::Extended, quote!(letrec f: bool -> bool = λb: bool. f b in f true)).unwrap();
+        let fun = Type::Fun(Box::new(Type::Bool), Box::new(Type::Bool));
+        let arg = abs("f", Some(fun), abs("b", Some(Type::Bool), Term::App(var(1), var(0))));
+        let body = Term::App(var(0), Box::new(Term::Bool(true)));
+        assert_eq!(term, Term::Let(String::from("f"), Box::new(Term::Fix(Box::new(arg))), Box::new(body)));
+
+        let term = parse(Dialect::Extended, quote!(fix (λx: bool. x) as bool)).unwrap();
+        let asc = Term::Asc(Box::new(abs("x", Some(Type::Bool), Term::Var(0))), Type::Bool);
+        assert_eq!(term, Term::Fix(Box::new(asc)));
+    }
+
+    /// Capitalized type names other than `Bool` and `Unit` are base types,
+    /// and `Bool` may also be written `bool`.
+    #[test]
+    fn base() {
+        let term = parse(Dialect::Extended, quote!(λf: A -> A. λx: A. f (f x))).unwrap();
+        let a = || Box::new(Type::Base(String::from("A")));
+        let body = abs("x", Some(*a()), Term::App(var(1), Box::new(Term::App(var(1), var(0)))));
+        assert_eq!(term, abs("f", Some(Type::Fun(a(), a())), body));
+
+        for dialect in [Dialect::Simple, Dialect::Extended] {
+            let term = parse(dialect, quote!(λb: Bool -> bool. b)).unwrap();
+            assert_eq!(term, abs("b", Some(Type::Fun(Box::new(Type::Bool), Box::new(Type::Bool))), Term::Var(0)));
+        }
+    }
+
+    #[test]
+    fn errors() {
+        let message = |dialect, tokens| parse(dialect, tokens).unwrap_err().to_string();
+        assert_eq!(message(Dialect::Untyped, quote!(λx. y)), "Unbound variable: y");
+        assert_eq!(message(Dialect::Untyped, quote!(λx: bool. x)), "Type annotation in untyped term");
+        assert_eq!(message(Dialect::Simple, quote!(λx. x)), "Missing type annotation for x");
+        assert_eq!(message(Dialect::Simple, quote!((true, false))), "Tuple is not supported in this calculus");
+        assert_eq!(message(Dialect::Simple, quote!((true; false))), "Sequence is not supported in this calculus");
+        assert_eq!(message(Dialect::Simple, quote!(λu: Unit. u)), "Unit type is not supported in this calculus");
+        assert_eq!(message(Dialect::Untyped, quote!(λx. x unit)), "Unit is not supported in this calculus");
+        assert_eq!(message(Dialect::Simple, quote!(λa: A. a)), "Base type is not supported in this calculus");
+        assert_eq!(message(Dialect::Extended, quote!(λa: string. a)), "Unknown type: string");
+        assert_eq!(message(Dialect::Simple, quote!(inl true as bool)), "Injection is not supported in this calculus");
+        assert_eq!(message(Dialect::Simple, quote!(λx: bool + bool. x)), "Sum type is not supported in this calculus");
+        assert!(message(Dialect::Extended, quote!(case x of inr y => y | inl z => z)).contains("Unbound variable: x"));
+        assert_eq!(message(Dialect::Simple, quote!(<a = true> as <a: bool>)), "Variant is not supported in this calculus");
+        assert_eq!(message(Dialect::Extended, quote!(λv: <a: bool, a: Unit>. v)), "Duplicate variant label: a");
+        assert_eq!(message(Dialect::Simple, quote!(fix λx: bool. x)), "Fixed point is not supported in this calculus");
+        assert_eq!(message(Dialect::Extended, quote!({a = true, a = false})), "Duplicate record label: a");
+        assert!(message(Dialect::Extended, quote!(if true then false)).ends_with("Expected else"));
+    }
+}